@@ -0,0 +1,5 @@
+//! Utilities for classifying IP addresses.
+
+mod global_ip;
+
+pub use global_ip::{is_global_ip, is_link_local_ip, is_private_ip};
@@ -20,7 +20,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::net::IpAddr;
+use core::net::IpAddr;
 
 /// This module contains an implementation of the `is_global` IPv4 address space.
 ///
@@ -28,7 +28,7 @@ use std::net::IpAddr;
 ///
 /// Unstable tracking issue: [#27709](https://github.com/rust-lang/rust/issues/27709)
 mod ipv4_global {
-    use std::net::Ipv4Addr;
+    use core::net::Ipv4Addr;
 
     /// Returns [`true`] if this address is reserved by IANA for future use. [IETF RFC 1112]
     /// defines the block of reserved addresses as `240.0.0.0/4`. This range normally includes the
@@ -138,7 +138,7 @@ mod ipv4_global {
 ///
 /// Unstable tracking issue: [#27709](https://github.com/rust-lang/rust/issues/27709)
 mod ipv6_global {
-    use std::net::Ipv6Addr;
+    use core::net::Ipv6Addr;
 
     /// Returns `true` if the address is a unicast address with link-local scope,
     /// as defined in [RFC 4291].
@@ -205,10 +205,12 @@ mod ipv6_global {
     /// - The [unspecified address] ([`is_unspecified`](Ipv6Addr::is_unspecified))
     /// - The [loopback address] ([`is_loopback`](Ipv6Addr::is_loopback))
     /// - IPv4-mapped addresses
+    /// - IPv4-IPv6 translation addresses (`64:ff9b::/96` and `64:ff9b:1::/48`)
     /// - Addresses reserved for benchmarking
     /// - Addresses reserved for documentation ([`is_documentation`](Ipv6Addr::is_documentation))
     /// - Unique local addresses ([`is_unique_local`](Ipv6Addr::is_unique_local))
     /// - Unicast addresses with link-local scope ([`is_unicast_link_local`](Ipv6Addr::is_unicast_link_local))
+    /// - Multicast addresses ([`is_multicast`](Ipv6Addr::is_multicast)), whatever their scope
     ///
     /// For the complete overview of which addresses are globally reachable, see the table at the [IANA IPv6 Special-Purpose Address Registry].
     ///
@@ -228,6 +230,8 @@ mod ipv6_global {
             || a.is_loopback()
             // IPv4-mapped Address (`::ffff:0:0/96`)
             || matches!(a.segments(), [0, 0, 0, 0, 0, 0xffff, _, _])
+            // IPv4-IPv6 Translat. (`64:ff9b::/96`), which can embed any IPv4 address
+            || matches!(a.segments(), [0x64, 0xff9b, 0, 0, 0, 0, _, _])
             // IPv4-IPv6 Translat. (`64:ff9b:1::/48`)
             || matches!(a.segments(), [0x64, 0xff9b, 1, _, _, _, _, _])
             // Discard-Only Address Block (`100::/64`)
@@ -247,21 +251,37 @@ mod ipv6_global {
                     || matches!(a.segments(), [0x2001, b, _, _, _, _, _, _] if b >= 0x20 && b <= 0x2F)
                 ))
             || is_documentation(a)
+            // Documentation (`3fff::/20`)
+            || (a.segments()[0] == 0x3fff && a.segments()[1] < 0x1000)
+            // Segment Routing (SRv6) SIDs (`5f00::/16`)
+            || a.segments()[0] == 0x5f00
             || is_unique_local(a)
-            || is_unicast_link_local(a))
+            || is_unicast_link_local(a)
+            // Multicast (`ff00::/8`)
+            || a.is_multicast())
     }
 }
 
-pub(crate) fn is_global_ip(ip: &IpAddr) -> bool {
+/// Returns whether an IP address is globally reachable.
+pub fn is_global_ip(ip: &IpAddr) -> bool {
     match ip {
         IpAddr::V4(ipv4) => ipv4_global::is_global(*ipv4),
         IpAddr::V6(ipv6) => ipv6_global::is_global(*ipv6),
     }
 }
 
-pub(crate) fn is_private_ip(ip: &IpAddr) -> bool {
+/// Returns whether an IP address is in a private range, or a unique local IPv6 address.
+pub fn is_private_ip(ip: &IpAddr) -> bool {
     match ip {
         IpAddr::V4(ipv4) => ipv4_global::is_private(*ipv4),
         IpAddr::V6(ipv6) => ipv6_global::is_unique_local(*ipv6),
     }
 }
+
+/// Returns whether an IP address is link-local.
+pub fn is_link_local_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ipv4) => ipv4.is_link_local(),
+        IpAddr::V6(ipv6) => ipv6_global::is_unicast_link_local(*ipv6),
+    }
+}
@@ -0,0 +1,189 @@
+//! Utilities for converting IP ranges.
+
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use core::ops::RangeInclusive;
+
+use alloc::{string::String, vec::Vec};
+use ipnet::{IpNet, Ipv4Subnets, Ipv6Subnets};
+
+/// Converts a type into an IP range.
+pub trait IntoIpRange {
+    /// Converts the type into an IP range.
+    fn into_range(self) -> Option<RangeInclusive<IpAddr>>;
+
+    /// Validates the IP range.
+    ///
+    /// Ranges with endpoints of different IP versions are rejected.
+    fn validate(ip_range: RangeInclusive<IpAddr>) -> Option<RangeInclusive<IpAddr>> {
+        if ip_range.start().is_ipv4() == ip_range.end().is_ipv4()
+            && ip_range.start() <= ip_range.end()
+        {
+            Some(ip_range)
+        } else {
+            None
+        }
+    }
+}
+
+impl IntoIpRange for IpNet {
+    fn into_range(self) -> Option<RangeInclusive<IpAddr>> {
+        let start = self.network();
+        let end = self.broadcast();
+        Some(start..=end)
+    }
+}
+
+impl IntoIpRange for RangeInclusive<IpAddr> {
+    fn into_range(self) -> Option<RangeInclusive<IpAddr>> {
+        Self::validate(self)
+    }
+}
+
+impl IntoIpRange for (IpAddr, IpAddr) {
+    fn into_range(self) -> Option<RangeInclusive<IpAddr>> {
+        Self::validate(self.0..=self.1)
+    }
+}
+
+impl IntoIpRange for (&str, &str) {
+    fn into_range(self) -> Option<RangeInclusive<IpAddr>> {
+        Self::validate(self.0.trim().parse().ok()?..=self.1.trim().parse().ok()?)
+    }
+}
+
+impl IntoIpRange for (String, String) {
+    fn into_range(self) -> Option<RangeInclusive<IpAddr>> {
+        (self.0.as_str(), self.1.as_str()).into_range()
+    }
+}
+
+/// Converts an IP range into the smallest list of CIDR networks covering it.
+///
+/// A range spanning from an IPv4 to an IPv6 address is split at the end of the IPv4 space.
+pub fn ip_range_to_ip_nets(ip_range: &RangeInclusive<IpAddr>) -> Vec<IpNet> {
+    match (*ip_range.start(), *ip_range.end()) {
+        (IpAddr::V4(start), IpAddr::V4(end)) => {
+            Ipv4Subnets::new(start, end, 0).map(IpNet::V4).collect()
+        }
+        (IpAddr::V6(start), IpAddr::V6(end)) => {
+            Ipv6Subnets::new(start, end, 0).map(IpNet::V6).collect()
+        }
+        (IpAddr::V4(start), IpAddr::V6(end)) => Ipv4Subnets::new(start, Ipv4Addr::BROADCAST, 0)
+            .map(IpNet::V4)
+            .chain(Ipv6Subnets::new(Ipv6Addr::UNSPECIFIED, end, 0).map(IpNet::V6))
+            .collect(),
+        (IpAddr::V6(_), IpAddr::V4(_)) => Vec::new(),
+    }
+}
+
+/// Creates an IP range spanning from an IP address to the IP address at an offset from it.
+///
+/// A negative offset spans the addresses before the IP address. The range is clamped at
+/// the unspecified address (`0.0.0.0` or `::`) and the maximum address of the IP family
+/// instead of wrapping around.
+pub fn saturating_range(base: IpAddr, offset: i64) -> RangeInclusive<IpAddr> {
+    let end = match base {
+        IpAddr::V4(ip) => {
+            let end = (u32::from(ip) as i64).saturating_add(offset);
+            IpAddr::V4(Ipv4Addr::from(end.clamp(0, u32::MAX as i64) as u32))
+        }
+        IpAddr::V6(ip) => IpAddr::V6(Ipv6Addr::from(
+            u128::from(ip).saturating_add_signed(offset as i128),
+        )),
+    };
+    if offset < 0 {
+        end..=base
+    } else {
+        base..=end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    #[test]
+    fn test_ip_range_to_ip_nets() {
+        let ip_range = "10.0.0.0/8".parse::<IpNet>().unwrap().into_range().unwrap();
+        assert_eq!(
+            ip_range_to_ip_nets(&ip_range),
+            vec!["10.0.0.0/8".parse::<IpNet>().unwrap()]
+        );
+
+        let ip_range = "1.1.1.1".parse().unwrap()..="1.1.1.4".parse().unwrap();
+        assert_eq!(
+            ip_range_to_ip_nets(&ip_range),
+            vec![
+                "1.1.1.1/32".parse::<IpNet>().unwrap(),
+                "1.1.1.2/31".parse().unwrap(),
+                "1.1.1.4/32".parse().unwrap(),
+            ]
+        );
+
+        let ip_range = "fe80::".parse().unwrap()..="fe80::ffff".parse().unwrap();
+        assert_eq!(
+            ip_range_to_ip_nets(&ip_range),
+            vec!["fe80::/112".parse::<IpNet>().unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_str_pair_into_range() {
+        let start = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5));
+        let end = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 50));
+        assert_eq!(("10.0.0.5", "10.0.0.50").into_range(), Some(start..=end));
+        assert_eq!(
+            ("10.0.0.5".to_string(), " 10.0.0.50".to_string()).into_range(),
+            Some(start..=end)
+        );
+        assert_eq!(("10.0.0.50", "10.0.0.5").into_range(), None);
+        assert_eq!(("10.0.0.5", "not an ip").into_range(), None);
+    }
+
+    #[test]
+    fn test_mixed_version_range() {
+        let v4 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0));
+        let v6 = IpAddr::V6(Ipv6Addr::LOCALHOST);
+        assert_eq!((v4, v6).into_range(), None);
+        assert_eq!((v4..=v6).into_range(), None);
+        assert_eq!(("10.0.0.0", "::1").into_range(), None);
+        assert_eq!((v4, v4).into_range(), Some(v4..=v4));
+        assert_eq!((v6, v6).into_range(), Some(v6..=v6));
+    }
+
+    #[test]
+    fn test_saturating_range() {
+        let ip = "10.0.0.10".parse().unwrap();
+        assert_eq!(saturating_range(ip, 5), ip..="10.0.0.15".parse().unwrap());
+        assert_eq!(saturating_range(ip, -10), "10.0.0.0".parse().unwrap()..=ip);
+        assert_eq!(saturating_range(ip, 0), ip..=ip);
+
+        let ip = "0.0.0.5".parse().unwrap();
+        assert_eq!(
+            saturating_range(ip, -10),
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED)..=ip
+        );
+        let ip = "255.255.255.250".parse().unwrap();
+        assert_eq!(
+            saturating_range(ip, i64::MAX),
+            ip..=IpAddr::V4(Ipv4Addr::BROADCAST)
+        );
+
+        let ip = "::5".parse().unwrap();
+        assert_eq!(
+            saturating_range(ip, i64::MIN),
+            IpAddr::V6(Ipv6Addr::UNSPECIFIED)..=ip
+        );
+        let ip = "ffff:ffff:ffff:ffff:ffff:ffff:ffff:fff0".parse().unwrap();
+        assert_eq!(
+            saturating_range(ip, 100),
+            ip..=IpAddr::V6(Ipv6Addr::from(u128::MAX))
+        );
+
+        assert!(saturating_range("1.1.1.1".parse().unwrap(), -1)
+            .into_range()
+            .is_some());
+    }
+}
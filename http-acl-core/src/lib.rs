@@ -0,0 +1,13 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+#![no_std]
+
+extern crate alloc;
+
+pub use ipnet::IpNet;
+
+pub mod ip;
+mod ip_range;
+pub mod range;
+
+pub use ip_range::{ip_range_to_ip_nets, saturating_range, IntoIpRange};
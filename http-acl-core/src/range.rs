@@ -0,0 +1,155 @@
+//! Utilities for querying ranges.
+
+use core::ops::RangeInclusive;
+
+use alloc::vec::Vec;
+
+/// An index over a list of ranges, answering containment and overlap queries in `O(log n)`.
+///
+/// The ranges are sorted and overlapping ranges are merged, so the index is a list of
+/// disjoint ranges that can be binary searched.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RangeIndex<T> {
+    ranges: Vec<RangeInclusive<T>>,
+}
+
+impl<T> Default for RangeIndex<T> {
+    fn default() -> Self {
+        Self { ranges: Vec::new() }
+    }
+}
+
+impl<T: Copy + Ord> RangeIndex<T> {
+    /// Creates an index over a list of ranges, ignoring empty ranges.
+    pub fn new(ranges: &[RangeInclusive<T>]) -> Self {
+        let mut sorted = ranges
+            .iter()
+            .filter(|range| !range.is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+        sorted.sort_by_key(|range| *range.start());
+
+        let mut merged: Vec<RangeInclusive<T>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match merged.last_mut() {
+                Some(last) if range.start() <= last.end() => {
+                    if range.end() > last.end() {
+                        *last = *last.start()..=*range.end();
+                    }
+                }
+                _ => merged.push(range),
+            }
+        }
+
+        Self { ranges: merged }
+    }
+
+    /// Returns the sorted, disjoint ranges of the index.
+    pub fn into_ranges(self) -> Vec<RangeInclusive<T>> {
+        self.ranges
+    }
+
+    /// Returns whether a value is contained in any of the ranges.
+    pub fn contains(&self, value: &T) -> bool {
+        let index = self.ranges.partition_point(|range| range.end() < value);
+        self.ranges
+            .get(index)
+            .is_some_and(|range| range.start() <= value)
+    }
+
+    /// Returns whether a range overlaps any of the ranges.
+    pub fn overlaps(&self, range: &RangeInclusive<T>) -> bool {
+        if range.is_empty() {
+            return false;
+        }
+        let index = self.ranges.partition_point(|r| r.end() < range.start());
+        self.ranges
+            .get(index)
+            .is_some_and(|r| r.start() <= range.end())
+    }
+
+    /// Returns whether a range is entirely contained in one of the ranges.
+    pub fn covers(&self, range: &RangeInclusive<T>) -> bool {
+        if range.is_empty() {
+            return false;
+        }
+        let index = self.ranges.partition_point(|r| r.end() < range.start());
+        self.ranges
+            .get(index)
+            .is_some_and(|r| r.start() <= range.start() && range.end() <= r.end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    /// A xorshift generator, so the randomized tests are reproducible without extra dependencies.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn range(&mut self) -> RangeInclusive<u16> {
+            let start = (self.next() % 10_000) as u16;
+            let len = (self.next() % 200) as u16;
+            start..=start + len
+        }
+    }
+
+    #[test]
+    fn test_range_index() {
+        let index = RangeIndex::new(&[40..=50, 10..=20, 15..=30, 12..=14]);
+        assert_eq!(index.ranges, vec![10..=30, 40..=50]);
+        assert!(index.contains(&10));
+        assert!(index.contains(&30));
+        assert!(!index.contains(&35));
+        assert!(!index.contains(&5));
+        assert!(index.overlaps(&(31..=40)));
+        assert!(!index.overlaps(&(31..=39)));
+        assert!(!index.overlaps(&(51..=60)));
+        assert!(index.covers(&(12..=30)));
+        assert!(index.covers(&(40..=40)));
+        assert!(!index.covers(&(25..=40)));
+        assert!(!index.covers(&(5..=10)));
+        assert!(!RangeIndex::<u16>::default().contains(&0));
+    }
+
+    #[test]
+    fn test_range_index_matches_naive() {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        for _ in 0..50 {
+            let ranges = (0..(rng.next() % 500))
+                .map(|_| rng.range())
+                .collect::<Vec<_>>();
+            let index = RangeIndex::new(&ranges);
+
+            for _ in 0..500 {
+                let value = (rng.next() % 10_500) as u16;
+                assert_eq!(
+                    index.contains(&value),
+                    ranges.iter().any(|range| range.contains(&value)),
+                    "{}",
+                    value
+                );
+
+                let query = rng.range();
+                assert_eq!(
+                    index.overlaps(&query),
+                    ranges
+                        .iter()
+                        .any(|range| range.start() <= query.end() && query.start() <= range.end()),
+                    "{:?}",
+                    query
+                );
+            }
+        }
+    }
+}
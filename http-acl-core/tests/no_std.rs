@@ -0,0 +1,48 @@
+//! Checks that the crate can be used from a `no_std` crate, with only `core` and `alloc`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::net::IpAddr;
+
+use http_acl_core::{
+    ip::{is_global_ip, is_link_local_ip, is_private_ip},
+    ip_range_to_ip_nets,
+    range::RangeIndex,
+    saturating_range, IntoIpRange, IpNet,
+};
+
+#[test]
+fn no_std_ip_and_port_matching() {
+    let ranges = [
+        "10.0.0.0/8".parse::<IpNet>().unwrap().into_range().unwrap(),
+        ("192.168.0.1", "192.168.0.10").into_range().unwrap(),
+        saturating_range("1.1.1.1".parse().unwrap(), 3),
+    ];
+    let index = RangeIndex::new(&ranges);
+
+    let ip: IpAddr = "10.1.2.3".parse().unwrap();
+    assert!(index.contains(&ip));
+    assert!(!is_global_ip(&ip));
+    assert!(is_private_ip(&ip));
+    assert!(index.contains(&"1.1.1.4".parse().unwrap()));
+    assert!(!index.contains(&"1.1.1.5".parse().unwrap()));
+    assert!(is_global_ip(&"1.1.1.5".parse().unwrap()));
+    assert!(is_link_local_ip(&"fe80::1".parse().unwrap()));
+
+    let nets = ip_range_to_ip_nets(&ranges[1]);
+    assert_eq!(nets.len(), 5);
+    assert!(nets
+        .iter()
+        .all(|net| index.covers(&net.into_range().unwrap())));
+
+    let ports = RangeIndex::new(&[80..=80, 443..=443, 8000..=8999]);
+    assert!(ports.contains(&8080));
+    assert!(!ports.contains(&22));
+    assert_eq!(
+        ports.into_ranges(),
+        Vec::from([80..=80, 443..=443, 8000..=8999])
+    );
+}
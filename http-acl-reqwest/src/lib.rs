@@ -7,31 +7,85 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use http::Extensions;
-use http_acl::utils::authority::{Authority, Host};
+use http_acl::{
+    acl::AclClassification,
+    utils::authority::{Authority, Host},
+    Dimension, HttpRequestMethod,
+};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
 use reqwest::{
     dns::{Name, Resolve, Resolving},
-    Request, Response,
+    redirect::Policy,
+    Method, Request, Response, Url,
 };
 use reqwest_middleware::{Error, Middleware, Next};
 use thiserror::Error;
 
-pub use http_acl::{self, HttpAcl, HttpAclBuilder};
+pub use http_acl::{self, HttpAcl, HttpAclBuilder, LiveHttpAcl};
+
+/// A callback invoked with the dimension, the input and the classification of every ACL decision.
+pub type OnDecisionFn = Arc<dyn Fn(Dimension, &str, &AclClassification) + Send + Sync>;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 /// A reqwest middleware that enforces an HTTP ACL.
 pub struct HttpAclMiddleware {
-    acl: Arc<HttpAcl>,
+    acl: LiveHttpAcl,
+    on_decision: Option<OnDecisionFn>,
+    max_buffered_body_size: Option<usize>,
+}
+
+impl std::fmt::Debug for HttpAclMiddleware {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpAclMiddleware")
+            .field("acl", &self.acl)
+            .field("max_buffered_body_size", &self.max_buffered_body_size)
+            .finish_non_exhaustive()
+    }
 }
 
 impl HttpAclMiddleware {
     /// Create a new HTTP ACL middleware.
     pub fn new(acl: HttpAcl) -> Self {
-        Self { acl: Arc::new(acl) }
+        Self::with_live_acl(LiveHttpAcl::new(acl))
+    }
+
+    /// Create a new HTTP ACL middleware from a [`LiveHttpAcl`].
+    ///
+    /// Each request is checked against the current snapshot of the ACL, so updates to the
+    /// [`LiveHttpAcl`] apply to the following requests without rebuilding the client.
+    pub fn with_live_acl(acl: LiveHttpAcl) -> Self {
+        Self {
+            acl,
+            on_decision: None,
+            max_buffered_body_size: None,
+        }
+    }
+
+    /// Buffer streaming request bodies into memory, up to `max_body_size` bytes,
+    /// so the validate function of the ACL can inspect them.
+    ///
+    /// By default, only bodies already held in memory are passed to the validate function,
+    /// and streaming bodies are passed as `None`. A streaming body larger than `max_body_size`
+    /// fails the request.
+    pub fn buffer_body(mut self, max_body_size: usize) -> Self {
+        self.max_buffered_body_size = Some(max_body_size);
+        self
+    }
+
+    /// Set a callback invoked for every dimension evaluated, whether it is allowed or denied.
+    pub fn on_decision(mut self, on_decision: OnDecisionFn) -> Self {
+        self.on_decision = Some(on_decision);
+        self
     }
 
-    /// Get the ACL.
+    /// Get the current snapshot of the ACL.
     pub fn acl(&self) -> Arc<HttpAcl> {
-        self.acl.clone()
+        self.acl.snapshot()
+    }
+
+    /// Get the live ACL, to update the ACL enforced by the middleware.
+    pub fn live_acl(&self) -> &LiveHttpAcl {
+        &self.acl
     }
 
     /// Create a DNS resolver that enforces the ACL.
@@ -43,87 +97,280 @@ impl HttpAclMiddleware {
     pub fn with_dns_resolver(&self, dns_resolver: Arc<dyn Resolve>) -> Arc<HttpAclDnsResolver> {
         Arc::new(HttpAclDnsResolver::with_dns_resolver(self, dns_resolver))
     }
+
+    /// Create a redirect policy that enforces the ACL on every redirect target.
+    ///
+    /// The middleware only checks the initial URL of a request, so the redirect policy
+    /// needs to be set on the reqwest Client to ensure the ACL is enforced on redirects.
+    /// Redirect targets are checked with [`HttpAcl::check_url`] as `GET` requests,
+    /// and at most 10 redirects are followed.
+    pub fn redirect_policy(&self) -> Policy {
+        let acl = self.acl.clone();
+        Policy::custom(move |attempt| {
+            if attempt.previous().len() > 10 {
+                return attempt.error("too many redirects");
+            }
+            let classification = acl.snapshot().check_url("GET", attempt.url().as_str());
+            if classification.is_denied() {
+                let url = attempt.url().to_string();
+                attempt.error(HttpAclError::RedirectDenied {
+                    url,
+                    classification,
+                })
+            } else {
+                attempt.follow()
+            }
+        })
+    }
+
+    /// Buffers a streaming request body into memory if enabled, replacing the body of the request.
+    async fn buffer_body_of(&self, req: &mut Request) -> std::result::Result<(), Error> {
+        let Some(max_body_size) = self.max_buffered_body_size else {
+            return Ok(());
+        };
+        if req.body().is_none_or(|body| body.as_bytes().is_some()) {
+            return Ok(());
+        }
+        let Some(body) = req.body_mut().take() else {
+            return Ok(());
+        };
+        let body = Limited::new(body, max_body_size)
+            .collect()
+            .await
+            .map_err(|e| {
+                if e.is::<LengthLimitError>() {
+                    Error::Middleware(anyhow!(
+                        "body exceeds the maximum buffered size of {} bytes",
+                        max_body_size
+                    ))
+                } else {
+                    Error::Middleware(anyhow!("failed to buffer body: {}", e))
+                }
+            })?;
+        *req.body_mut() = Some(body.to_bytes().into());
+        Ok(())
+    }
+
+    /// Reports a decision to the callback and returns an error if it is a denial.
+    fn decide(
+        &self,
+        dimension: Dimension,
+        input: &str,
+        classification: AclClassification,
+    ) -> std::result::Result<(), Error> {
+        if let Some(on_decision) = &self.on_decision {
+            on_decision(dimension, input, &classification);
+        }
+        if classification.is_allowed() {
+            Ok(())
+        } else if input.is_empty() {
+            Err(Error::Middleware(anyhow!(
+                "{} is denied - {}",
+                dimension,
+                classification
+            )))
+        } else {
+            Err(Error::Middleware(anyhow!(
+                "{} {} is denied - {}",
+                dimension,
+                input,
+                classification
+            )))
+        }
+    }
 }
 
 #[async_trait::async_trait]
 impl Middleware for HttpAclMiddleware {
     async fn handle(
         &self,
-        req: Request,
+        mut req: Request,
         extensions: &mut Extensions,
         next: Next<'_>,
     ) -> std::result::Result<Response, Error> {
-        let scheme = req.url().scheme();
-        let acl_scheme_match = self.acl.is_scheme_allowed(scheme);
-        if acl_scheme_match.is_denied() {
-            return Err(Error::Middleware(anyhow!(
-                "scheme {} is denied - {}",
-                scheme,
-                acl_scheme_match
-            )));
+        let acl = self.acl();
+        let method = req.method().as_str();
+        let Some(host) = req.url().host_str() else {
+            return Err(Error::Middleware(anyhow!("missing host")));
+        };
+        let authority = match Authority::parse(host) {
+            Ok(authority) => authority,
+            Err(e) => {
+                return self
+                    .decide(Dimension::Host, host, e.into())
+                    .and_then(|()| Err(Error::Middleware(anyhow!("invalid host: {}", host))));
+            }
+        };
+
+        check_url_dimensions(
+            &acl,
+            req.url(),
+            method,
+            &authority,
+            |dimension, input, classification| self.decide(dimension, input, classification),
+        )?;
+
+        if acl.max_header_count().is_some() || acl.max_total_header_bytes().is_some() {
+            let headers = req
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_bytes()));
+            self.decide(
+                Dimension::Header,
+                "",
+                acl.are_headers_within_limits(headers),
+            )?;
         }
 
-        let method = req.method().as_str();
-        let acl_method_match = self.acl.is_method_allowed(method);
-        if acl_method_match.is_denied() {
-            return Err(Error::Middleware(anyhow!(
-                "method {} is denied - {}",
-                method,
-                acl_method_match
-            )));
+        for (name, value) in req.headers() {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            self.decide(
+                Dimension::Header,
+                name.as_str(),
+                acl.is_header_allowed(name.as_str(), &value),
+            )?;
+        }
+
+        if !acl.required_headers().is_empty() {
+            let headers = req
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.to_str().unwrap_or_default()));
+            self.decide(
+                Dimension::Header,
+                "",
+                acl.are_required_headers_present(headers),
+            )?;
         }
 
-        if let Some(host) = req.url().host_str() {
-            let authority = Authority::parse(host)
-                .map_err(|_| Error::Middleware(anyhow!("invalid host: {}", host)))?;
+        self.buffer_body_of(&mut req).await?;
 
-            match authority.host {
-                Host::Ip(ip) => {
-                    let acl_ip_match = self.acl.is_ip_allowed(&ip);
-                    if acl_ip_match.is_denied() {
-                        return Err(Error::Middleware(anyhow!(
-                            "ip {} is denied - {}",
-                            ip,
-                            acl_ip_match
-                        )));
-                    }
-                }
+        let scheme = req.url().scheme();
+        let port = req
+            .url()
+            .port_or_known_default()
+            .or(authority.port)
+            .unwrap_or_default();
+        let authority = Authority {
+            host: authority.host,
+            port: Some(port),
+        };
+        let headers = req
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        let body = req.body().and_then(|body| body.as_bytes());
+        let acl_valid_match = acl
+            .is_valid_async(
+                scheme,
+                &authority,
+                &HttpRequestMethod::from(req.method()),
+                port,
+                Box::new(headers),
+                body,
+            )
+            .await;
+        self.decide(Dimension::Request, "", acl_valid_match)?;
+
+        next.run(req, extensions).await
+    }
+}
+
+/// Checks the URL dimensions of a request in the order the middleware enforces them:
+/// scheme, method, host or IP, port, URL path, query and compound rules.
+///
+/// Each classification is passed to `decide`, stopping at its first error.
+fn check_url_dimensions<E>(
+    acl: &HttpAcl,
+    url: &Url,
+    method: &str,
+    authority: &Authority,
+    mut decide: impl FnMut(Dimension, &str, AclClassification) -> Result<(), E>,
+) -> Result<(), E> {
+    let scheme = url.scheme();
+    decide(Dimension::Scheme, scheme, acl.is_scheme_allowed(scheme))?;
+    decide(Dimension::Method, method, acl.is_method_allowed(method))?;
+
+    let port = url.port_or_known_default();
+    let authority = Authority {
+        host: authority.host.clone(),
+        port: port.or(authority.port),
+    };
+
+    match acl.authority_rule_classification(&authority) {
+        Some(classification) => {
+            decide(Dimension::Host, &authority.to_string(), classification)?;
+        }
+        None => {
+            match &authority.host {
+                Host::Ip(ip) => decide(Dimension::Ip, &ip.to_string(), acl.is_ip_allowed(ip))?,
                 Host::Domain(domain) => {
-                    let acl_host_match = self.acl.is_host_allowed(&domain);
-                    if acl_host_match.is_denied() {
-                        return Err(Error::Middleware(anyhow!(
-                            "host {} is denied - {}",
-                            domain,
-                            acl_host_match
-                        )));
-                    }
+                    decide(Dimension::Host, domain, acl.is_host_allowed(domain))?
                 }
             }
 
-            if let Some(port) = req.url().port_or_known_default() {
-                let acl_port_match = self.acl.is_port_allowed(port);
-                if acl_port_match.is_denied() {
-                    return Err(Error::Middleware(anyhow!(
-                        "port {} is denied - {}",
-                        port,
-                        acl_port_match
-                    )));
-                }
+            if let Some(port) = port {
+                decide(
+                    Dimension::Port,
+                    &port.to_string(),
+                    acl.is_port_allowed(port),
+                )?;
             }
+        }
+    }
 
-            let acl_url_path_match = self.acl.is_url_path_allowed(req.url().path());
-            if acl_url_path_match.is_denied() {
-                return Err(Error::Middleware(anyhow!(
-                    "path {} is denied - {}",
-                    req.url().path(),
-                    acl_url_path_match
-                )));
-            }
+    let path = url.path();
+    decide(
+        Dimension::UrlPath,
+        path,
+        acl.is_url_path_allowed_for_method(method, path),
+    )?;
 
-            next.run(req, extensions).await
-        } else {
-            return Err(Error::Middleware(anyhow!("missing host")));
-        }
+    if let Some(query) = url.query() {
+        decide(Dimension::Query, query, acl.is_url_query_allowed(query))?;
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    decide(
+        Dimension::Compound,
+        &format!("{} {}://{}", method, scheme, host),
+        acl.is_compound_allowed(scheme, method, host),
+    )
+}
+
+/// Extension methods to check reqwest types against an [`HttpAcl`] without the middleware.
+pub trait HttpAclExt {
+    /// Checks a reqwest URL and method against the ACL, as the middleware does before sending a request.
+    ///
+    /// Returns the classification of the first denied dimension,
+    /// or [`AclClassification::AllowedDefault`] if no dimension is denied.
+    fn check_reqwest_url(&self, url: &Url, method: &Method) -> AclClassification;
+}
+
+impl HttpAclExt for HttpAcl {
+    fn check_reqwest_url(&self, url: &Url, method: &Method) -> AclClassification {
+        let Some(host) = url.host_str() else {
+            return AclClassification::Denied("missing host".to_string());
+        };
+        let authority = match Authority::parse(host) {
+            Ok(authority) => authority,
+            Err(e) => return e.into(),
+        };
+        check_url_dimensions(
+            self,
+            url,
+            method.as_str(),
+            &authority,
+            |_, _, classification| {
+                if classification.is_denied() {
+                    Err(classification)
+                } else {
+                    Ok(())
+                }
+            },
+        )
+        .err()
+        .unwrap_or(AclClassification::AllowedDefault)
     }
 }
 
@@ -134,8 +381,7 @@ struct GaiResolver;
 impl Resolve for GaiResolver {
     fn resolve(&self, name: Name) -> Resolving {
         Box::pin(async move {
-            let addresses = name
-                .as_str()
+            let addresses = (name.as_str(), 0)
                 .to_socket_addrs()
                 .map_err(|e| Box::new(e) as BoxError)?;
             Ok(Box::new(addresses.into_iter()) as Box<dyn Iterator<Item = SocketAddr> + Send>)
@@ -144,9 +390,18 @@ impl Resolve for GaiResolver {
 }
 
 /// A DNS resolver that enforces an HTTP ACL.
+///
+/// Hosts with a static DNS mapping resolve to their pinned addresses
+/// allowed by the ACL, only falling back to DNS if none are allowed and
+/// the static mappings are not authoritative.
+///
+/// Only the IP ACL is enforced on the resolved addresses. A reqwest resolver
+/// is given a bare host name, and the port of the request URL replaces the port
+/// of the resolved addresses when connecting, so the port ACL is enforced by
+/// [`HttpAclMiddleware`] instead.
 pub struct HttpAclDnsResolver {
     dns_resolver: Arc<dyn Resolve>,
-    acl: Arc<HttpAcl>,
+    acl: LiveHttpAcl,
 }
 
 impl HttpAclDnsResolver {
@@ -154,7 +409,7 @@ impl HttpAclDnsResolver {
     pub fn new(middleware: &HttpAclMiddleware) -> Self {
         Self {
             dns_resolver: Arc::new(GaiResolver),
-            acl: middleware.acl(),
+            acl: middleware.acl.clone(),
         }
     }
 
@@ -165,35 +420,44 @@ impl HttpAclDnsResolver {
     ) -> Self {
         Self {
             dns_resolver,
-            acl: middleware.acl(),
+            acl: middleware.acl.clone(),
         }
     }
 }
 
 impl Resolve for HttpAclDnsResolver {
     fn resolve(&self, name: Name) -> Resolving {
-        if self.acl.is_host_allowed(name.as_str()).is_denied() {
-            let err: BoxError = Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Host denied by ACL",
+        let acl = self.acl.snapshot();
+        let classification = acl.is_host_allowed(name.as_str());
+        if classification.is_denied() {
+            let err: BoxError = Box::new(HttpAclError::HostDenied {
+                host: name.as_str().to_string(),
+                classification,
+            });
+            return Box::pin(future::ready(Err(err)));
+        }
+
+        let mapped = acl.resolve_static_dns_mapping(name.as_str());
+        let pinned = filter_addresses(&acl, mapped.iter().copied());
+        if !pinned.is_empty() {
+            return Box::pin(future::ready(Ok(
+                Box::new(pinned.into_iter()) as Box<dyn Iterator<Item = SocketAddr> + Send>
+            )));
+        }
+        if !mapped.is_empty() && acl.static_mappings_authoritative() {
+            let err: BoxError = Box::new(std::io::Error::other(
+                "Statically mapped addresses denied by ACL",
             ));
             return Box::pin(future::ready(Err(err)));
         }
 
-        let acl = self.acl.clone();
         let resolver = self.dns_resolver.clone();
 
         Box::pin(async move {
             let resolved = resolver.resolve(name).await;
             match resolved {
                 Ok(addresses) => {
-                    let filtered = addresses
-                        .into_iter()
-                        .filter(|addr| {
-                            acl.is_ip_allowed(&addr.ip()).is_allowed()
-                                && acl.is_port_allowed(addr.port()).is_allowed()
-                        })
-                        .collect::<Vec<_>>();
+                    let filtered = filter_addresses(&acl, addresses);
                     Ok(Box::new(filtered.into_iter())
                         as Box<dyn Iterator<Item = SocketAddr> + Send>)
                 }
@@ -203,14 +467,37 @@ impl Resolve for HttpAclDnsResolver {
     }
 }
 
+/// Filters out the addresses whose IP is denied by the ACL.
+///
+/// The ports are ignored, as they are replaced by the port of the request URL.
+fn filter_addresses(
+    acl: &HttpAcl,
+    addresses: impl IntoIterator<Item = SocketAddr>,
+) -> Vec<SocketAddr> {
+    addresses
+        .into_iter()
+        .filter(|addr| acl.is_ip_allowed(&addr.ip()).is_allowed())
+        .collect()
+}
+
 #[derive(Error, Debug)]
 /// An error that can occur when resolving a host.
 pub enum HttpAclError {
     /// Host resolution denied by ACL.
-    #[error("Host resolution denied by ACL: {host}")]
+    #[error("Host resolution denied by ACL: {host} - {classification}")]
     HostDenied {
         /// The host that was denied.
         host: String,
+        /// The classification of the host.
+        classification: AclClassification,
+    },
+    /// Redirect denied by ACL.
+    #[error("Redirect to {url} denied by ACL: {classification}")]
+    RedirectDenied {
+        /// The URL of the redirect target.
+        url: String,
+        /// The classification of the redirect target.
+        classification: AclClassification,
     },
 }
 
@@ -221,7 +508,7 @@ mod tests {
     #[tokio::test]
     async fn test_http_acl_middleware() {
         let acl = HttpAcl::builder()
-            .add_denied_host("example.com".to_string())
+            .add_denied_host("example.com")
             .unwrap()
             .build();
 
@@ -242,7 +529,716 @@ mod tests {
         assert_eq!(request
             .unwrap_err()
             .to_string(),
-            "Middleware error: host example.com is denied - The entiy is denied according to the denied ACL."
+            "Middleware error: host example.com is denied - The entity is denied according to the denied ACL."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_live_acl() {
+        let live = LiveHttpAcl::new(HttpAcl::builder().build());
+        let middleware = HttpAclMiddleware::with_live_acl(live.clone());
+        let resolver = middleware.dns_resolver();
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(resolver.clone())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        live.add_denied_host("example.com").unwrap();
+
+        let request = client.get("http://example.com/").send().await;
+        assert_eq!(request
+            .unwrap_err()
+            .to_string(),
+            "Middleware error: host example.com is denied - The entity is denied according to the denied ACL."
+        );
+        let err = match resolver.resolve("example.com".parse().unwrap()).await {
+            Ok(_) => panic!("example.com should be denied"),
+            Err(err) => err,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Host resolution denied by ACL: example.com - The entity is denied according to the denied ACL."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_authority() {
+        let acl = HttpAcl::builder()
+            .host_acl_default(true)
+            .add_allowed_authority("example.com:8080")
+            .unwrap()
+            .add_denied_authority("example.com:443")
+            .unwrap()
+            .build();
+
+        let url = Url::parse("http://example.com:8080/").unwrap();
+        assert_eq!(
+            acl.check_reqwest_url(&url, &Method::GET),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.check_url("GET", "http://example.com:8080/"),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.check_url("GET", "https://example.com/"),
+            AclClassification::DeniedUserAcl
+        );
+
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let request = client.get("https://example.com/").send().await;
+
+        assert!(request.is_err());
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: host example.com:443 is denied - The entity is denied according to the denied ACL."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_url_path_for_method() {
+        let acl = HttpAcl::builder()
+            .host_acl_default(true)
+            .add_allowed_url_path_for_method("GET", "/admin")
+            .unwrap()
+            .add_denied_url_path_for_method("POST", "/admin")
+            .unwrap()
+            .build();
+
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let request = client.post("http://example.com/admin").send().await;
+
+        assert!(request.is_err());
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: path /admin is denied - The entity is denied according to the denied ACL."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_dns_resolver_static_dns_mapping() {
+        let v4 = "93.184.215.14:443".parse().unwrap();
+        let v6 = "[2606:2800:21f:cb07:6820:80da:af6b:8b2c]:443"
+            .parse()
+            .unwrap();
+        let denied = "10.0.0.1:443".parse().unwrap();
+        let acl = HttpAcl::builder()
+            .ip_acl_default(true)
+            .add_static_dns_mappings("example.com", vec![v4, v6, denied])
+            .unwrap()
+            .host_acl_default(true)
+            .build();
+
+        let middleware = HttpAclMiddleware::new(acl);
+        let resolver = middleware.dns_resolver();
+
+        let addresses = resolver
+            .resolve("example.com".parse().unwrap())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>();
+
+        assert_eq!(addresses, vec![v4, v6]);
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_dns_resolver_ignores_port() {
+        struct PortlessResolver;
+
+        impl Resolve for PortlessResolver {
+            fn resolve(&self, _name: Name) -> Resolving {
+                Box::pin(future::ready(Ok(Box::new(
+                    ["1.1.1.1:0", "10.0.0.1:0"]
+                        .into_iter()
+                        .map(|addr| addr.parse().unwrap()),
+                )
+                    as Box<dyn Iterator<Item = SocketAddr> + Send>)))
+            }
+        }
+
+        let acl = HttpAcl::builder()
+            .host_acl_default(true)
+            .ip_acl_default(true)
+            .add_allowed_ip_range("127.0.0.1/32".parse::<http_acl::IpNet>().unwrap())
+            .unwrap()
+            .build();
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let resolver =
+            HttpAclDnsResolver::with_dns_resolver(&middleware, Arc::new(PortlessResolver));
+        let addresses = resolver
+            .resolve("example.com".parse().unwrap())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(addresses, vec!["1.1.1.1:0".parse().unwrap()]);
+
+        let addresses = middleware
+            .dns_resolver()
+            .resolve("localhost".parse().unwrap())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert!(addresses.contains(&"127.0.0.1:0".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_dns_resolver_host_denied() {
+        let acl = HttpAcl::builder()
+            .add_denied_host("example.com")
+            .unwrap()
+            .build();
+
+        let middleware = HttpAclMiddleware::new(acl);
+        let resolver = middleware.dns_resolver();
+
+        let err = match resolver.resolve("example.com".parse().unwrap()).await {
+            Ok(_) => panic!("example.com should be denied"),
+            Err(err) => err,
+        };
+        match err.downcast_ref::<HttpAclError>() {
+            Some(HttpAclError::HostDenied {
+                host,
+                classification,
+            }) => {
+                assert_eq!(host, "example.com");
+                assert_eq!(classification, &AclClassification::DeniedUserAcl);
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Host resolution denied by ACL: example.com - The entity is denied according to the denied ACL."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_dns_resolver_static_mappings_authoritative() {
+        struct StaticResolver(SocketAddr);
+
+        impl Resolve for StaticResolver {
+            fn resolve(&self, _name: Name) -> Resolving {
+                let addr = self.0;
+                Box::pin(future::ready(Ok(
+                    Box::new(std::iter::once(addr)) as Box<dyn Iterator<Item = SocketAddr> + Send>
+                )))
+            }
+        }
+
+        let resolved = "93.184.215.14:443".parse().unwrap();
+        let denied = "10.0.0.1:443".parse().unwrap();
+        let builder = HttpAcl::builder()
+            .add_static_dns_mapping("example.com", denied)
+            .unwrap()
+            .host_acl_default(true)
+            .ip_acl_default(true);
+
+        let middleware = HttpAclMiddleware::new(builder.clone().build());
+        let resolver =
+            HttpAclDnsResolver::with_dns_resolver(&middleware, Arc::new(StaticResolver(resolved)));
+        let addresses = resolver
+            .resolve("example.com".parse().unwrap())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(addresses, vec![resolved]);
+
+        let middleware =
+            HttpAclMiddleware::new(builder.static_mappings_authoritative(true).build());
+        let resolver =
+            HttpAclDnsResolver::with_dns_resolver(&middleware, Arc::new(StaticResolver(resolved)));
+        let result = resolver.resolve("example.com".parse().unwrap()).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_async_validate_fn() {
+        let acl = HttpAcl::builder()
+            .host_acl_default(true)
+            .build_full_async(Arc::new(
+                |_scheme, _authority, _method, _port, headers, _body| {
+                    Box::pin(async move {
+                        if headers.iter().any(|(name, _)| name == "x-forbidden") {
+                            AclClassification::Denied("forbidden header".to_string())
+                        } else {
+                            AclClassification::AllowedUserAcl
+                        }
+                    })
+                },
+            ));
+
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let request = client
+            .get("http://example.com/")
+            .header("x-forbidden", "1")
+            .send()
+            .await;
+
+        assert!(request.is_err());
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: request is denied - The entity is denied because forbidden header."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_header() {
+        let acl = HttpAcl::builder()
+            .host_acl_default(true)
+            .add_denied_header("x-debug", None)
+            .unwrap()
+            .build();
+
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let request = client
+            .get("http://example.com/")
+            .header("X-Debug", "1")
+            .send()
+            .await;
+
+        assert!(request.is_err());
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: header x-debug is denied - The entity is denied according to the denied ACL."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_validate_fn_method_and_port() {
+        let acl = HttpAcl::builder()
+            .host_acl_default(true)
+            .add_allowed_port_range(8022..=8022)
+            .unwrap()
+            .build_full(Some(Arc::new(
+                |_scheme, _authority, method, port, _headers, _body| {
+                    if *method == HttpRequestMethod::POST && port == 8022 {
+                        AclClassification::Denied("POST to port 8022".to_string())
+                    } else {
+                        AclClassification::AllowedUserAcl
+                    }
+                },
+            )));
+
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let request = client.post("http://example.com:8022/").send().await;
+
+        assert!(request.is_err());
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: request is denied - The entity is denied because POST to port 8022."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_validate_fn_header() {
+        let acl = HttpAcl::builder()
+            .host_acl_default(true)
+            .build_full(Some(Arc::new(
+                |_scheme, _authority, _method, _port, mut headers, _body| {
+                    if headers.any(|(name, value)| name == "x-tenant" && value == "blocked") {
+                        AclClassification::Denied("blocked tenant".to_string())
+                    } else {
+                        AclClassification::AllowedUserAcl
+                    }
+                },
+            )));
+
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let request = client
+            .get("http://example.com/")
+            .header("x-tenant", "blocked")
+            .send()
+            .await;
+
+        assert!(request.is_err());
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: request is denied - The entity is denied because blocked tenant."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_validate_fn_body() {
+        let acl = HttpAcl::builder()
+            .host_acl_default(true)
+            .build_full(Some(Arc::new(
+                |_scheme, _authority, _method, _port, _headers, body| match body {
+                    Some(body) if body.windows(6).any(|w| w == b"secret") => {
+                        AclClassification::Denied("secret body".to_string())
+                    }
+                    Some(_) => AclClassification::AllowedUserAcl,
+                    None => AclClassification::Denied("uninspected body".to_string()),
+                },
+            )));
+
+        let client = |middleware: HttpAclMiddleware| {
+            reqwest_middleware::ClientBuilder::new(
+                reqwest::Client::builder()
+                    .dns_resolver(middleware.dns_resolver())
+                    .build()
+                    .unwrap(),
+            )
+            .with(middleware)
+            .build()
+        };
+        let streaming = || reqwest::Body::wrap("a secret".to_string());
+
+        let middleware = HttpAclMiddleware::new(acl);
+        let request = client(middleware.clone())
+            .post("http://example.com/")
+            .body("a secret")
+            .send()
+            .await;
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: request is denied - The entity is denied because secret body."
+        );
+
+        let request = client(middleware.clone())
+            .post("http://example.com/")
+            .body(streaming())
+            .send()
+            .await;
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: request is denied - The entity is denied because uninspected body."
+        );
+
+        let request = client(middleware.clone().buffer_body(1024))
+            .post("http://example.com/")
+            .body(streaming())
+            .send()
+            .await;
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: request is denied - The entity is denied because secret body."
+        );
+
+        let request = client(middleware.buffer_body(4))
+            .post("http://example.com/")
+            .body(streaming())
+            .send()
+            .await;
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: body exceeds the maximum buffered size of 4 bytes"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_on_decision() {
+        use std::sync::Mutex;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        let acl = HttpAcl::builder()
+            .add_allowed_ip_range("127.0.0.1/32".parse::<http_acl::IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_port_range(addr.port()..=addr.port())
+            .unwrap()
+            .build();
+
+        let decisions = Arc::new(Mutex::new(Vec::new()));
+        let recorded = decisions.clone();
+        let middleware = HttpAclMiddleware::new(acl).on_decision(Arc::new(
+            move |dimension, _input, classification| {
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push((dimension, classification.is_allowed()));
+            },
+        ));
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let response = client
+            .get(format!("http://{}/", addr))
+            .header("x-request-id", "1")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let decisions = decisions.lock().unwrap();
+        assert_eq!(
+            *decisions,
+            vec![
+                (Dimension::Scheme, true),
+                (Dimension::Method, true),
+                (Dimension::Ip, true),
+                (Dimension::Port, true),
+                (Dimension::UrlPath, true),
+                (Dimension::Compound, true),
+                (Dimension::Header, true),
+                (Dimension::Request, true),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_required_header() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        let acl = HttpAcl::builder()
+            .add_allowed_ip_range("127.0.0.1/32".parse::<http_acl::IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_port_range(addr.port()..=addr.port())
+            .unwrap()
+            .add_required_header("Authorization")
+            .unwrap()
+            .build();
+
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let request = client.get(format!("http://{}/", addr)).send().await;
+
+        assert!(request.is_err());
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: header is denied - The entity is denied because missing required header authorization."
+        );
+
+        let response = client
+            .get(format!("http://{}/", addr))
+            .header("Authorization", "Bearer token")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_header_limits() {
+        let acl = HttpAcl::builder()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .max_header_count(Some(1))
+            .max_total_header_bytes(Some(16))
+            .build();
+
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let request = client
+            .get("http://example.com/")
+            .header("x-a", "1")
+            .header("x-b", "2")
+            .send()
+            .await;
+
+        assert!(request.is_err());
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: header is denied - The entity is denied because 2 headers exceed the maximum header count of 1."
+        );
+
+        let request = client
+            .get("http://example.com/")
+            .header("x-large", "0123456789")
+            .send()
+            .await;
+
+        assert!(request.is_err());
+        assert_eq!(
+            request.unwrap_err().to_string(),
+            "Middleware error: header is denied - The entity is denied because 17 header bytes exceed the maximum total header size of 16 bytes."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_acl_middleware_redirect_policy() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(
+                    b"HTTP/1.1 302 Found\r\nlocation: http://example.com/\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                )
+                .await
+                .unwrap();
+        });
+
+        let acl = HttpAcl::builder()
+            .add_allowed_ip_range("127.0.0.1/32".parse::<http_acl::IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_port_range(addr.port()..=addr.port())
+            .unwrap()
+            .add_denied_host("example.com")
+            .unwrap()
+            .build();
+
+        let middleware = HttpAclMiddleware::new(acl);
+
+        let client = reqwest_middleware::ClientBuilder::new(
+            reqwest::Client::builder()
+                .dns_resolver(middleware.dns_resolver())
+                .redirect(middleware.redirect_policy())
+                .build()
+                .unwrap(),
+        )
+        .with(middleware)
+        .build();
+
+        let err = client
+            .get(format!("http://{}/", addr))
+            .send()
+            .await
+            .unwrap_err();
+
+        let Error::Reqwest(err) = err else {
+            panic!("expected a reqwest error, got {}", err);
+        };
+        assert!(err.is_redirect());
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(
+            source.to_string(),
+            "Redirect to http://example.com/ denied by ACL: The entity is denied according to the denied ACL."
+        );
+    }
+
+    #[test]
+    fn test_check_reqwest_url() {
+        let acl = HttpAcl::builder()
+            .host_acl_default(true)
+            .add_denied_url_path_for_method("POST", "/admin")
+            .unwrap()
+            .add_denied_host("example.net")
+            .unwrap()
+            .build();
+
+        let url = Url::parse("http://example.com/admin").unwrap();
+        assert_eq!(
+            acl.check_reqwest_url(&url, &Method::GET),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.check_reqwest_url(&url, &Method::POST),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.check_reqwest_url(&Url::parse("https://example.net/").unwrap(), &Method::GET),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.check_reqwest_url(&Url::parse("http://10.0.0.1/").unwrap(), &Method::GET),
+            AclClassification::DeniedPrivateRange
         );
     }
 }
@@ -0,0 +1,239 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+
+use http_acl::{
+    acl::AclClassification,
+    utils::authority::{Authority, Host},
+};
+use ureq::Resolver;
+
+pub use http_acl::{self, HttpAcl, HttpAclBuilder};
+
+/// Checks a URL against an HTTP ACL, as a `GET` request.
+///
+/// The URL is checked with [`HttpAcl::check_url`], returning the classification
+/// of the first denied dimension.
+pub fn check_url(acl: &HttpAcl, url: &str) -> Result<(), AclClassification> {
+    match acl.check_url("GET", url) {
+        classification if classification.is_denied() => Err(classification),
+        _ => Ok(()),
+    }
+}
+
+struct StdResolver;
+
+impl Resolver for StdResolver {
+    fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+        netloc.to_socket_addrs().map(Iterator::collect)
+    }
+}
+
+/// A ureq resolver that enforces an HTTP ACL.
+///
+/// Set it on the agent with [`ureq::AgentBuilder::resolver`] to enforce the
+/// IP and port ACL at connect time, on the addresses the host resolves to.
+///
+/// Hosts with a static DNS mapping resolve to their pinned addresses
+/// allowed by the ACL, only falling back to DNS if none are allowed and
+/// the static mappings are not authoritative.
+pub struct HttpAclResolver {
+    resolver: Arc<dyn Resolver>,
+    acl: Arc<HttpAcl>,
+}
+
+impl HttpAclResolver {
+    /// Create a new ACL resolver.
+    pub fn new(acl: HttpAcl) -> Self {
+        Self::with_resolver(acl, Arc::new(StdResolver))
+    }
+
+    /// Create a new ACL resolver with a custom resolver.
+    pub fn with_resolver(acl: HttpAcl, resolver: Arc<dyn Resolver>) -> Self {
+        Self {
+            resolver,
+            acl: Arc::new(acl),
+        }
+    }
+
+    /// Get the ACL.
+    pub fn acl(&self) -> Arc<HttpAcl> {
+        self.acl.clone()
+    }
+}
+
+impl std::fmt::Debug for HttpAclResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpAclResolver")
+            .field("acl", &self.acl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Resolver for HttpAclResolver {
+    fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+        let authority = Authority::parse(netloc)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Invalid host"))?;
+
+        if let Host::Domain(domain) = &authority.host {
+            if self.acl.is_host_allowed(domain).is_denied() {
+                return Err(io::Error::other("Host denied by ACL"));
+            }
+
+            let mapped = self
+                .acl
+                .resolve_static_dns_mapping(domain)
+                .iter()
+                .map(|addr| SocketAddr::new(addr.ip(), authority.port.unwrap_or(addr.port())));
+            let pinned = filter_addresses(&self.acl, mapped);
+            if !pinned.is_empty() {
+                return Ok(pinned);
+            }
+            if !self.acl.resolve_static_dns_mapping(domain).is_empty()
+                && self.acl.static_mappings_authoritative()
+            {
+                return Err(io::Error::other(
+                    "Statically mapped addresses denied by ACL",
+                ));
+            }
+        }
+
+        let addresses = filter_addresses(&self.acl, self.resolver.resolve(netloc)?);
+        if addresses.is_empty() {
+            return Err(io::Error::other("Resolved addresses denied by ACL"));
+        }
+        Ok(addresses)
+    }
+}
+
+/// Filters out the addresses denied by the ACL.
+fn filter_addresses(
+    acl: &HttpAcl,
+    addresses: impl IntoIterator<Item = SocketAddr>,
+) -> Vec<SocketAddr> {
+    addresses
+        .into_iter()
+        .filter(|addr| {
+            acl.is_ip_allowed(&addr.ip()).is_allowed()
+                && acl.is_port_allowed(addr.port()).is_allowed()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_url() {
+        let acl = HttpAcl::builder()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_denied_url_path("/admin")
+            .unwrap()
+            .build();
+
+        assert_eq!(check_url(&acl, "https://example.com/"), Ok(()));
+        assert_eq!(
+            check_url(&acl, "https://example.net/"),
+            Err(AclClassification::DeniedDefault)
+        );
+        assert_eq!(
+            check_url(&acl, "https://example.com:8443/"),
+            Err(AclClassification::DeniedDefault)
+        );
+        assert_eq!(
+            check_url(&acl, "https://example.com/admin"),
+            Err(AclClassification::DeniedUserAcl)
+        );
+        assert_eq!(
+            check_url(&acl, "http://10.0.0.1/"),
+            Err(AclClassification::DeniedPrivateRange)
+        );
+        assert!(check_url(&acl, "not a url").is_err());
+    }
+
+    #[test]
+    fn test_check_url_compound_rules() {
+        let acl = HttpAcl::builder()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_rule(http_acl::acl::CompoundRule::new(
+                "https",
+                "POST",
+                "example.com",
+            ))
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            check_url(&acl, "https://example.com/"),
+            Err(AclClassification::DeniedDefault)
+        );
+    }
+
+    #[test]
+    fn test_http_acl_resolver() {
+        let acl = HttpAcl::builder()
+            .add_allowed_ip_range("1.1.1.0/24".parse::<http_acl::IpNet>().unwrap())
+            .unwrap()
+            .add_static_dns_mappings(
+                "example.com",
+                vec![
+                    "1.1.1.1:80".parse().unwrap(),
+                    "10.0.0.1:80".parse().unwrap(),
+                ],
+            )
+            .unwrap()
+            .add_denied_host("example.net")
+            .unwrap()
+            .host_acl_default(true)
+            .build();
+
+        let stub = |netloc: &str| -> io::Result<Vec<SocketAddr>> {
+            match netloc {
+                "example.org:443" => Ok(vec![
+                    "10.0.0.2:443".parse().unwrap(),
+                    "1.1.1.2:443".parse().unwrap(),
+                ]),
+                _ => Ok(vec!["10.0.0.3:443".parse().unwrap()]),
+            }
+        };
+        let resolver = HttpAclResolver::with_resolver(acl, Arc::new(stub));
+
+        assert_eq!(
+            resolver.resolve("example.com:443").unwrap(),
+            vec!["1.1.1.1:443".parse::<SocketAddr>().unwrap()]
+        );
+        assert_eq!(
+            resolver.resolve("example.org:443").unwrap(),
+            vec!["1.1.1.2:443".parse::<SocketAddr>().unwrap()]
+        );
+        assert_eq!(
+            resolver.resolve("example.net:443").unwrap_err().to_string(),
+            "Host denied by ACL"
+        );
+        assert_eq!(
+            resolver.resolve("example.edu:443").unwrap_err().to_string(),
+            "Resolved addresses denied by ACL"
+        );
+    }
+
+    #[test]
+    fn test_http_acl_resolver_agent() {
+        let acl = HttpAcl::builder()
+            .add_denied_host("example.com")
+            .unwrap()
+            .build();
+
+        let agent = ureq::AgentBuilder::new()
+            .resolver(HttpAclResolver::new(acl))
+            .build();
+
+        let err = agent.get("http://example.com/").call().unwrap_err();
+        assert!(err.to_string().contains("Host denied by ACL"), "{}", err);
+    }
+}
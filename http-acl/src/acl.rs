@@ -1,18 +1,33 @@
 //! Contains the [`HttpAcl`], [`HttpAclBuilder`],
 //! and related types.
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{hash_map::Entry, BTreeSet, HashMap, HashSet};
+use std::future::Future;
 use std::hash::Hash;
 use std::net::{IpAddr, SocketAddr};
 use std::ops::RangeInclusive;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
 
+use ipnet::IpNet;
 use matchit::Router;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(any(feature = "toml", feature = "json"))]
+use crate::error::LoadError;
 use crate::{
     error::AddError,
-    utils::{self, IntoIpRange},
+    utils::{
+        self,
+        authority::{Authority, AuthorityError, Host},
+        host::HostIndex,
+        method::MethodIndex,
+        range::RangeIndex,
+        IntoIpRange,
+    },
 };
 
 #[derive(Clone)]
@@ -22,72 +37,230 @@ pub struct HttpAcl {
     allow_https: bool,
     allowed_methods: Vec<HttpRequestMethod>,
     denied_methods: Vec<HttpRequestMethod>,
+    allowed_method_index: MethodIndex,
+    denied_method_index: MethodIndex,
     allowed_hosts: Vec<String>,
     denied_hosts: Vec<String>,
+    allowed_authorities: HashSet<Authority>,
+    denied_authorities: HashSet<Authority>,
+    allowed_host_index: HostIndex,
+    denied_host_index: HostIndex,
+    #[cfg(feature = "regex")]
+    allowed_host_regexes: Vec<regex::Regex>,
+    #[cfg(feature = "regex")]
+    denied_host_regexes: Vec<regex::Regex>,
     allowed_port_ranges: Vec<RangeInclusive<u16>>,
     denied_port_ranges: Vec<RangeInclusive<u16>>,
     allowed_ip_ranges: Vec<RangeInclusive<IpAddr>>,
     denied_ip_ranges: Vec<RangeInclusive<IpAddr>>,
-    static_dns_mapping: HashMap<String, SocketAddr>,
+    expiring_denied_ip_ranges: Vec<(RangeInclusive<IpAddr>, Instant)>,
+    allowed_ip_index: RangeIndex<IpAddr>,
+    denied_ip_index: RangeIndex<IpAddr>,
+    static_dns_mapping: HashMap<String, Vec<SocketAddr>>,
+    static_mappings_authoritative: bool,
+    kill_switch: bool,
+    allowed_headers: HashMap<String, Option<String>>,
+    denied_headers: HashMap<String, Option<String>>,
+    denied_header_values: HashMap<String, Vec<String>>,
+    allowed_header_matchers: HashMap<String, Vec<HeaderMatcher>>,
+    denied_header_matchers: HashMap<String, Vec<HeaderMatcher>>,
+    required_headers: Vec<String>,
+    max_header_count: Option<usize>,
+    max_total_header_bytes: Option<usize>,
+    allowed_rules: Vec<CompoundRule>,
+    denied_query_substrings: Vec<String>,
+    max_query_length: Option<usize>,
     allowed_url_paths: Vec<String>,
-    allowed_url_paths_router: Router<()>,
+    allowed_url_paths_router: Router<Box<str>>,
     denied_url_paths: Vec<String>,
-    denied_url_paths_router: Router<()>,
+    denied_url_paths_router: Router<Box<str>>,
+    allowed_method_url_paths: HashMap<HttpRequestMethod, Vec<String>>,
+    allowed_method_url_paths_routers: HashMap<HttpRequestMethod, Router<Box<str>>>,
+    denied_method_url_paths: HashMap<HttpRequestMethod, Vec<String>>,
+    denied_method_url_paths_routers: HashMap<HttpRequestMethod, Router<Box<str>>>,
+    decode_url_paths: bool,
+    normalize_url_paths: bool,
+    strip_trailing_slashes: bool,
     allow_private_ip_ranges: bool,
+    allow_loopback_ip_addresses: bool,
+    allow_link_local_ip_addresses: bool,
+    allow_unspecified_ip_addresses: bool,
+    ip_family_mode: IpFamilyMode,
+    wildcard_includes_apex: bool,
+    precedence: Precedence,
     method_acl_default: bool,
     host_acl_default: bool,
     port_acl_default: bool,
     ip_acl_default: bool,
     url_path_acl_default: bool,
+    header_acl_default: bool,
+    scheme_acl_default: bool,
+    validate_fn: Option<ValidateFn>,
+    async_validate_fn: Option<AsyncValidateFn>,
+    on_decision: Option<DecisionFn>,
+    clock: Option<ClockFn>,
 }
 
+/// A function that validates a request given its scheme, authority, method, port, headers and body.
+pub type ValidateFn = Arc<
+    dyn for<'h> Fn(
+            &str,
+            &Authority,
+            &HttpRequestMethod,
+            u16,
+            Box<dyn Iterator<Item = (&'h str, &'h str)> + Send + Sync + 'h>,
+            Option<&[u8]>,
+        ) -> AclClassification
+        + Send
+        + Sync,
+>;
+
+/// A function called with the dimension name and the classification of every decision of the ACL.
+pub type DecisionFn = Arc<dyn Fn(&str, &AclClassification) + Send + Sync>;
+
+/// A function that returns the current time, used to expire the expiring rules.
+pub type ClockFn = Arc<dyn Fn() -> Instant + Send + Sync>;
+
+/// A function that asynchronously validates a request given its scheme, authority, method, port, headers and body.
+pub type AsyncValidateFn = Arc<
+    dyn Fn(
+            String,
+            Authority,
+            HttpRequestMethod,
+            u16,
+            Vec<(String, String)>,
+            Option<Vec<u8>>,
+        ) -> Pin<Box<dyn Future<Output = AclClassification> + Send>>
+        + Send
+        + Sync,
+>;
+
 impl std::fmt::Debug for HttpAcl {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("HttpAcl")
+        let mut debug = f.debug_struct("HttpAcl");
+        debug
             .field("allow_http", &self.allow_http)
             .field("allow_https", &self.allow_https)
             .field("allowed_methods", &self.allowed_methods)
             .field("denied_methods", &self.denied_methods)
             .field("allowed_hosts", &self.allowed_hosts)
             .field("denied_hosts", &self.denied_hosts)
+            .field("allowed_authorities", &self.allowed_authorities)
+            .field("denied_authorities", &self.denied_authorities)
             .field("allowed_port_ranges", &self.allowed_port_ranges)
             .field("denied_port_ranges", &self.denied_port_ranges)
             .field("allowed_ip_ranges", &self.allowed_ip_ranges)
             .field("denied_ip_ranges", &self.denied_ip_ranges)
+            .field("expiring_denied_ip_ranges", &self.expiring_denied_ip_ranges)
             .field("static_dns_mapping", &self.static_dns_mapping)
-            .field("alllowed_url_paths", &self.allowed_url_paths)
+            .field(
+                "static_mappings_authoritative",
+                &self.static_mappings_authoritative,
+            )
+            .field("kill_switch", &self.kill_switch)
+            .field("allowed_headers", &self.allowed_headers)
+            .field("denied_headers", &self.denied_headers)
+            .field("denied_header_values", &self.denied_header_values)
+            .field("allowed_header_matchers", &self.allowed_header_matchers)
+            .field("denied_header_matchers", &self.denied_header_matchers)
+            .field("required_headers", &self.required_headers)
+            .field("max_header_count", &self.max_header_count)
+            .field("max_total_header_bytes", &self.max_total_header_bytes)
+            .field("allowed_rules", &self.allowed_rules)
+            .field("denied_query_substrings", &self.denied_query_substrings)
+            .field("max_query_length", &self.max_query_length)
+            .field("allowed_url_paths", &self.allowed_url_paths)
             .field("denied_url_paths", &self.denied_url_paths)
+            .field("allowed_method_url_paths", &self.allowed_method_url_paths)
+            .field("denied_method_url_paths", &self.denied_method_url_paths)
+            .field("decode_url_paths", &self.decode_url_paths)
+            .field("normalize_url_paths", &self.normalize_url_paths)
+            .field("strip_trailing_slashes", &self.strip_trailing_slashes)
             .field("allow_private_ip_ranges", &self.allow_private_ip_ranges)
+            .field(
+                "allow_loopback_ip_addresses",
+                &self.allow_loopback_ip_addresses,
+            )
+            .field(
+                "allow_link_local_ip_addresses",
+                &self.allow_link_local_ip_addresses,
+            )
+            .field(
+                "allow_unspecified_ip_addresses",
+                &self.allow_unspecified_ip_addresses,
+            )
+            .field("ip_family_mode", &self.ip_family_mode)
+            .field("wildcard_includes_apex", &self.wildcard_includes_apex)
+            .field("precedence", &self.precedence)
             .field("method_acl_default", &self.method_acl_default)
             .field("host_acl_default", &self.host_acl_default)
             .field("port_acl_default", &self.port_acl_default)
             .field("ip_acl_default", &self.ip_acl_default)
             .field("url_path_acl_default", &self.url_path_acl_default)
-            .finish()
+            .field("header_acl_default", &self.header_acl_default)
+            .field("scheme_acl_default", &self.scheme_acl_default);
+        #[cfg(feature = "regex")]
+        debug
+            .field("allowed_host_regexes", &self.allowed_host_regexes)
+            .field("denied_host_regexes", &self.denied_host_regexes);
+        debug.finish()
     }
 }
 
 impl PartialEq for HttpAcl {
     fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "regex")]
+        if self.host_regex_patterns() != other.host_regex_patterns() {
+            return false;
+        }
         self.allow_http == other.allow_http
             && self.allow_https == other.allow_https
             && self.allowed_methods == other.allowed_methods
             && self.denied_methods == other.denied_methods
             && self.allowed_hosts == other.allowed_hosts
             && self.denied_hosts == other.denied_hosts
+            && self.allowed_authorities == other.allowed_authorities
+            && self.denied_authorities == other.denied_authorities
             && self.allowed_port_ranges == other.allowed_port_ranges
             && self.denied_port_ranges == other.denied_port_ranges
             && self.allowed_ip_ranges == other.allowed_ip_ranges
             && self.denied_ip_ranges == other.denied_ip_ranges
+            && self.expiring_denied_ip_ranges == other.expiring_denied_ip_ranges
             && self.static_dns_mapping == other.static_dns_mapping
+            && self.static_mappings_authoritative == other.static_mappings_authoritative
+            && self.kill_switch == other.kill_switch
+            && self.allowed_headers == other.allowed_headers
+            && self.denied_headers == other.denied_headers
+            && self.denied_header_values == other.denied_header_values
+            && self.allowed_header_matchers == other.allowed_header_matchers
+            && self.denied_header_matchers == other.denied_header_matchers
+            && self.required_headers == other.required_headers
+            && self.max_header_count == other.max_header_count
+            && self.max_total_header_bytes == other.max_total_header_bytes
+            && self.allowed_rules == other.allowed_rules
+            && self.denied_query_substrings == other.denied_query_substrings
+            && self.max_query_length == other.max_query_length
             && self.allowed_url_paths == other.allowed_url_paths
             && self.denied_url_paths == other.denied_url_paths
+            && self.allowed_method_url_paths == other.allowed_method_url_paths
+            && self.denied_method_url_paths == other.denied_method_url_paths
+            && self.decode_url_paths == other.decode_url_paths
+            && self.normalize_url_paths == other.normalize_url_paths
+            && self.strip_trailing_slashes == other.strip_trailing_slashes
             && self.allow_private_ip_ranges == other.allow_private_ip_ranges
+            && self.allow_loopback_ip_addresses == other.allow_loopback_ip_addresses
+            && self.allow_link_local_ip_addresses == other.allow_link_local_ip_addresses
+            && self.allow_unspecified_ip_addresses == other.allow_unspecified_ip_addresses
+            && self.ip_family_mode == other.ip_family_mode
+            && self.wildcard_includes_apex == other.wildcard_includes_apex
+            && self.precedence == other.precedence
             && self.method_acl_default == other.method_acl_default
             && self.host_acl_default == other.host_acl_default
             && self.port_acl_default == other.port_acl_default
             && self.ip_acl_default == other.ip_acl_default
             && self.url_path_acl_default == other.url_path_acl_default
+            && self.header_acl_default == other.header_acl_default
+            && self.scheme_acl_default == other.scheme_acl_default
     }
 }
 
@@ -108,23 +281,68 @@ impl std::default::Default for HttpAcl {
                 HttpRequestMethod::TRACE,
             ],
             denied_methods: Vec::new(),
+            allowed_method_index: MethodIndex::standard(),
+            denied_method_index: MethodIndex::default(),
             allowed_hosts: Vec::new(),
             denied_hosts: Vec::new(),
+            allowed_authorities: HashSet::new(),
+            denied_authorities: HashSet::new(),
+            allowed_host_index: HostIndex::default(),
+            denied_host_index: HostIndex::default(),
+            #[cfg(feature = "regex")]
+            allowed_host_regexes: Vec::new(),
+            #[cfg(feature = "regex")]
+            denied_host_regexes: Vec::new(),
             allowed_port_ranges: vec![80..=80, 443..=443],
             denied_port_ranges: Vec::new(),
             allowed_ip_ranges: Vec::new(),
             denied_ip_ranges: Vec::new(),
+            expiring_denied_ip_ranges: Vec::new(),
+            allowed_ip_index: RangeIndex::default(),
+            denied_ip_index: RangeIndex::default(),
             static_dns_mapping: HashMap::new(),
+            static_mappings_authoritative: false,
+            kill_switch: false,
+            allowed_headers: HashMap::new(),
+            denied_headers: HashMap::new(),
+            denied_header_values: HashMap::new(),
+            allowed_header_matchers: HashMap::new(),
+            denied_header_matchers: HashMap::new(),
+            required_headers: Vec::new(),
+            max_header_count: None,
+            max_total_header_bytes: None,
+            allowed_rules: Vec::new(),
+            denied_query_substrings: Vec::new(),
+            max_query_length: None,
             allowed_url_paths: Vec::new(),
             allowed_url_paths_router: Router::new(),
             denied_url_paths: Vec::new(),
             denied_url_paths_router: Router::new(),
+            allowed_method_url_paths: HashMap::new(),
+            allowed_method_url_paths_routers: HashMap::new(),
+            denied_method_url_paths: HashMap::new(),
+            denied_method_url_paths_routers: HashMap::new(),
+            decode_url_paths: true,
+            normalize_url_paths: true,
+            strip_trailing_slashes: true,
             allow_private_ip_ranges: false,
+            allow_loopback_ip_addresses: false,
+            allow_link_local_ip_addresses: false,
+            allow_unspecified_ip_addresses: false,
+            ip_family_mode: IpFamilyMode::DualStack,
+            wildcard_includes_apex: false,
+            precedence: Precedence::DenyWins,
             method_acl_default: false,
             host_acl_default: false,
             port_acl_default: false,
             ip_acl_default: false,
             url_path_acl_default: true,
+            header_acl_default: true,
+            scheme_acl_default: false,
+            validate_fn: None,
+            async_validate_fn: None,
+            on_decision: None,
+            clock: None,
         }
     }
 }
@@ -150,6 +368,51 @@ impl HttpAcl {
         self.allow_private_ip_ranges
     }
 
+    /// Returns whether loopback IP addresses are allowed.
+    pub fn allow_loopback_ip_addresses(&self) -> bool {
+        self.allow_loopback_ip_addresses
+    }
+
+    /// Returns whether link-local IP addresses are allowed.
+    pub fn allow_link_local_ip_addresses(&self) -> bool {
+        self.allow_link_local_ip_addresses
+    }
+
+    /// Returns whether the unspecified IP addresses (`0.0.0.0` and `::`) are allowed.
+    pub fn allow_unspecified_ip_addresses(&self) -> bool {
+        self.allow_unspecified_ip_addresses
+    }
+
+    /// Returns the IP versions that are allowed.
+    pub fn ip_family_mode(&self) -> IpFamilyMode {
+        self.ip_family_mode
+    }
+
+    /// Returns whether wildcard hosts also match their apex domain.
+    pub fn wildcard_includes_apex(&self) -> bool {
+        self.wildcard_includes_apex
+    }
+
+    /// Returns the precedence between allowed and denied rules that both match.
+    pub fn precedence(&self) -> Precedence {
+        self.precedence
+    }
+
+    /// Returns whether URL paths are percent-decoded before matching.
+    pub fn decode_url_paths(&self) -> bool {
+        self.decode_url_paths
+    }
+
+    /// Returns whether URL paths are normalized before matching.
+    pub fn normalize_url_paths(&self) -> bool {
+        self.normalize_url_paths
+    }
+
+    /// Returns whether trailing slashes are stripped from URL paths before matching.
+    pub fn strip_trailing_slashes(&self) -> bool {
+        self.strip_trailing_slashes
+    }
+
     /// Returns the default action for HTTP methods if no ACL match is found.
     pub fn method_acl_default(&self) -> bool {
         self.method_acl_default
@@ -170,6 +433,71 @@ impl HttpAcl {
         self.ip_acl_default
     }
 
+    /// Returns the default action for headers if no ACL match is found.
+    pub fn header_acl_default(&self) -> bool {
+        self.header_acl_default
+    }
+
+    /// Returns the default action for schemes other than HTTP and HTTPS.
+    pub fn scheme_acl_default(&self) -> bool {
+        self.scheme_acl_default
+    }
+
+    /// Returns the allowed headers.
+    pub fn allowed_headers(&self) -> &HashMap<String, Option<String>> {
+        &self.allowed_headers
+    }
+
+    /// Returns the denied headers.
+    pub fn denied_headers(&self) -> &HashMap<String, Option<String>> {
+        &self.denied_headers
+    }
+
+    /// Returns the required headers.
+    pub fn required_headers(&self) -> &[String] {
+        &self.required_headers
+    }
+
+    /// Returns the maximum number of headers.
+    pub fn max_header_count(&self) -> Option<usize> {
+        self.max_header_count
+    }
+
+    /// Returns the maximum total size of header names and values in bytes.
+    pub fn max_total_header_bytes(&self) -> Option<usize> {
+        self.max_total_header_bytes
+    }
+
+    /// Returns the denied value sets of headers.
+    pub fn denied_header_values(&self) -> &HashMap<String, Vec<String>> {
+        &self.denied_header_values
+    }
+
+    /// Returns the allowed header matchers.
+    pub fn allowed_header_matchers(&self) -> &HashMap<String, Vec<HeaderMatcher>> {
+        &self.allowed_header_matchers
+    }
+
+    /// Returns the denied header matchers.
+    pub fn denied_header_matchers(&self) -> &HashMap<String, Vec<HeaderMatcher>> {
+        &self.denied_header_matchers
+    }
+
+    /// Returns the allowed compound rules.
+    pub fn allowed_rules(&self) -> &[CompoundRule] {
+        &self.allowed_rules
+    }
+
+    /// Returns the denied query substrings.
+    pub fn denied_query_substrings(&self) -> &[String] {
+        &self.denied_query_substrings
+    }
+
+    /// Returns the maximum length of a URL query in bytes.
+    pub fn max_query_length(&self) -> Option<usize> {
+        self.max_query_length
+    }
+
     /// Returns the allowed methods.
     pub fn allowed_methods(&self) -> &[HttpRequestMethod] {
         &self.allowed_methods
@@ -180,9 +508,51 @@ impl HttpAcl {
         &self.denied_methods
     }
 
+    /// Calls the decision callback, if any, with a classification of a dimension.
+    ///
+    /// With the `tracing` feature, denials are also logged with the `http_acl::decision` target.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn decide(
+        &self,
+        dimension: Dimension,
+        entity: &dyn std::fmt::Display,
+        classification: AclClassification,
+    ) -> AclClassification {
+        let classification = self.kill_switch_denial().unwrap_or(classification);
+        #[cfg(feature = "tracing")]
+        if classification.is_denied() {
+            tracing::warn!(
+                target: "http_acl::decision",
+                category = dimension.as_str(),
+                entity = %entity,
+                reason_code = classification.reason_code(),
+                "{}",
+                classification
+            );
+        }
+        if let Some(on_decision) = &self.on_decision {
+            on_decision(dimension.as_str(), &classification);
+        }
+        classification
+    }
+
     /// Returns whether the scheme is allowed.
+    ///
+    /// HTTP and HTTPS are governed by their own toggles,
+    /// and any other scheme falls back to the scheme default.
     pub fn is_scheme_allowed(&self, scheme: &str) -> AclClassification {
-        if scheme == "http" && self.allow_http || scheme == "https" && self.allow_https {
+        self.decide(Dimension::Scheme, &scheme, self.classify_scheme(scheme))
+    }
+
+    /// Classifies a scheme without calling the decision callback.
+    fn classify_scheme(&self, scheme: &str) -> AclClassification {
+        let allowed = match scheme {
+            "http" => self.allow_http,
+            "https" => self.allow_https,
+            _ if self.scheme_acl_default => return AclClassification::AllowedDefault,
+            _ => return AclClassification::DeniedDefault,
+        };
+        if allowed {
             AclClassification::AllowedUserAcl
         } else {
             AclClassification::DeniedUserAcl
@@ -192,9 +562,18 @@ impl HttpAcl {
     /// Returns whether the method is allowed.
     pub fn is_method_allowed(&self, method: impl Into<HttpRequestMethod>) -> AclClassification {
         let method = method.into();
-        if self.allowed_methods.contains(&method) {
+        self.decide(
+            Dimension::Method,
+            &method.as_str(),
+            self.classify_method(&method),
+        )
+    }
+
+    /// Classifies a method without calling the decision callback.
+    fn classify_method(&self, method: &HttpRequestMethod) -> AclClassification {
+        if self.allowed_method_index.contains(method) {
             AclClassification::AllowedUserAcl
-        } else if self.denied_methods.contains(&method) {
+        } else if self.denied_method_index.contains(method) {
             AclClassification::DeniedUserAcl
         } else if self.method_acl_default {
             AclClassification::AllowedDefault
@@ -205,10 +584,25 @@ impl HttpAcl {
 
     /// Returns whether the host is allowed.
     pub fn is_host_allowed(&self, host: &str) -> AclClassification {
-        if self.denied_hosts.contains(&host.to_string()) {
+        self.decide(Dimension::Host, &host, self.classify_host(host))
+    }
+
+    /// Classifies a host without calling the decision callback.
+    fn classify_host(&self, host: &str) -> AclClassification {
+        if self
+            .denied_host_index
+            .find(host, self.wildcard_includes_apex)
+            .is_some()
+        {
             AclClassification::DeniedUserAcl
-        } else if self.allowed_hosts.contains(&host.to_string()) {
+        } else if self
+            .allowed_host_index
+            .find(host, self.wildcard_includes_apex)
+            .is_some()
+        {
             AclClassification::AllowedUserAcl
+        } else if let Some(classification) = self.classify_host_regexes(host) {
+            classification
         } else if self.host_acl_default {
             AclClassification::AllowedDefault
         } else {
@@ -216,8 +610,743 @@ impl HttpAcl {
         }
     }
 
+    /// Classifies a host against the host regular expressions, if any matches.
+    #[cfg(feature = "regex")]
+    fn classify_host_regexes(&self, host: &str) -> Option<AclClassification> {
+        if self.denied_host_regexes.iter().any(|re| re.is_match(host)) {
+            Some(AclClassification::DeniedUserAcl)
+        } else if self.allowed_host_regexes.iter().any(|re| re.is_match(host)) {
+            Some(AclClassification::AllowedUserAcl)
+        } else {
+            None
+        }
+    }
+
+    /// Classifies a host against the host regular expressions, if any matches.
+    #[cfg(not(feature = "regex"))]
+    fn classify_host_regexes(&self, _host: &str) -> Option<AclClassification> {
+        None
+    }
+
+    /// Returns the patterns of the allowed and denied host regular expressions.
+    #[cfg(feature = "regex")]
+    fn host_regex_patterns(&self) -> (Vec<String>, Vec<String>) {
+        let patterns = |regexes: &[regex::Regex]| {
+            regexes
+                .iter()
+                .map(|re| re.as_str().to_string())
+                .collect::<Vec<_>>()
+        };
+        (
+            patterns(&self.allowed_host_regexes),
+            patterns(&self.denied_host_regexes),
+        )
+    }
+
+    /// Returns whether a header is allowed.
+    ///
+    /// Header names are compared case-insensitively. A header that matches both
+    /// an allowed and a denied rule is resolved by the [`Precedence`].
+    pub fn is_header_allowed(&self, header_name: &str, header_value: &str) -> AclClassification {
+        self.decide(
+            Dimension::Header,
+            &header_name,
+            self.classify_header(header_name, header_value),
+        )
+    }
+
+    /// Classifies a header without calling the decision callback.
+    fn classify_header(&self, header_name: &str, header_value: &str) -> AclClassification {
+        let header_name = header_name.to_ascii_lowercase();
+        let allowed = Self::is_header_in_map(&header_name, header_value, &self.allowed_headers)
+            || Self::find_header_matcher(&header_name, header_value, &self.allowed_header_matchers)
+                .is_some();
+        let denied = Self::is_header_in_map(&header_name, header_value, &self.denied_headers)
+            || self
+                .denied_header_values
+                .get(&header_name)
+                .is_some_and(|values| values.iter().any(|value| value == header_value))
+            || Self::find_header_matcher(&header_name, header_value, &self.denied_header_matchers)
+                .is_some();
+        match (allowed, denied, self.precedence) {
+            (true, false, _) | (true, true, Precedence::AllowWins) => {
+                AclClassification::AllowedUserAcl
+            }
+            (_, true, _) => AclClassification::DeniedUserAcl,
+            (false, false, _) if self.header_acl_default => AclClassification::AllowedDefault,
+            (false, false, _) => AclClassification::DeniedDefault,
+        }
+    }
+
+    /// Returns whether all of the required headers are present.
+    ///
+    /// Header names are compared case-insensitively.
+    /// Returns [`AclClassification::AllowedDefault`] if no headers are required.
+    pub fn are_required_headers_present<'h>(
+        &self,
+        headers: impl Iterator<Item = (&'h str, &'h str)>,
+    ) -> AclClassification {
+        if let Some(denial) = self.kill_switch_denial() {
+            return denial;
+        }
+        if self.required_headers.is_empty() {
+            return AclClassification::AllowedDefault;
+        }
+        let present = headers
+            .map(|(name, _)| name.to_ascii_lowercase())
+            .collect::<Vec<_>>();
+        match self
+            .required_headers
+            .iter()
+            .find(|required| !present.contains(required))
+        {
+            Some(missing) => {
+                AclClassification::Denied(format!("missing required header {}", missing))
+            }
+            None => AclClassification::AllowedUserAcl,
+        }
+    }
+
+    /// Returns whether the headers are within the header count and total size limits.
+    ///
+    /// The total size is the sum of the lengths of the header names and values.
+    /// Returns [`AclClassification::AllowedDefault`] if no limits are set.
+    pub fn are_headers_within_limits<N: AsRef<[u8]>, V: AsRef<[u8]>>(
+        &self,
+        headers: impl IntoIterator<Item = (N, V)>,
+    ) -> AclClassification {
+        if let Some(denial) = self.kill_switch_denial() {
+            return denial;
+        }
+        if self.max_header_count.is_none() && self.max_total_header_bytes.is_none() {
+            return AclClassification::AllowedDefault;
+        }
+        let (count, bytes) = headers
+            .into_iter()
+            .fold((0, 0), |(count, bytes), (name, value)| {
+                (
+                    count + 1,
+                    bytes + name.as_ref().len() + value.as_ref().len(),
+                )
+            });
+        if let Some(max) = self.max_header_count.filter(|max| count > *max) {
+            AclClassification::Denied(format!(
+                "{} headers exceed the maximum header count of {}",
+                count, max
+            ))
+        } else if let Some(max) = self.max_total_header_bytes.filter(|max| bytes > *max) {
+            AclClassification::Denied(format!(
+                "{} header bytes exceed the maximum total header size of {} bytes",
+                bytes, max
+            ))
+        } else {
+            AclClassification::AllowedUserAcl
+        }
+    }
+
+    /// Checks if a header matches a header map.
+    ///
+    /// A header rule without a value matches any value.
+    fn is_header_in_map(
+        header_name: &str,
+        header_value: &str,
+        headers: &HashMap<String, Option<String>>,
+    ) -> bool {
+        match headers.get(header_name) {
+            Some(Some(value)) => value == header_value,
+            Some(None) => true,
+            None => false,
+        }
+    }
+
+    /// Returns the first matcher of a header matcher map that matches a header.
+    fn find_header_matcher<'m>(
+        header_name: &str,
+        header_value: &str,
+        matchers: &'m HashMap<String, Vec<HeaderMatcher>>,
+    ) -> Option<&'m HeaderMatcher> {
+        matchers
+            .get(header_name)?
+            .iter()
+            .find(|matcher| matcher.matches(header_value))
+    }
+
+    /// Returns a builder with the rules and settings of the ACL.
+    ///
+    /// The validate functions are not part of the builder, use [`HttpAcl::rebuild`] to keep them.
+    pub fn to_builder(&self) -> HttpAclBuilder {
+        HttpAclBuilder {
+            allow_http: self.allow_http,
+            allow_https: self.allow_https,
+            allowed_methods: self.allowed_methods.clone(),
+            denied_methods: self.denied_methods.clone(),
+            allowed_hosts: self.allowed_hosts.clone(),
+            denied_hosts: self.denied_hosts.clone(),
+            allowed_authorities: self
+                .allowed_authorities
+                .iter()
+                .map(Authority::to_string)
+                .collect(),
+            denied_authorities: self
+                .denied_authorities
+                .iter()
+                .map(Authority::to_string)
+                .collect(),
+            #[cfg(feature = "regex")]
+            allowed_host_regexes: self.host_regex_patterns().0,
+            #[cfg(feature = "regex")]
+            denied_host_regexes: self.host_regex_patterns().1,
+            allowed_port_ranges: self.allowed_port_ranges.clone(),
+            denied_port_ranges: self.denied_port_ranges.clone(),
+            allowed_ip_ranges: self.allowed_ip_ranges.clone(),
+            denied_ip_ranges: self.denied_ip_ranges.clone(),
+            expiring_denied_ip_ranges: self.expiring_denied_ip_ranges.clone(),
+            static_dns_mapping: self.static_dns_mapping.clone(),
+            static_mappings_authoritative: self.static_mappings_authoritative,
+            kill_switch: self.kill_switch,
+            allowed_headers: self.allowed_headers.clone(),
+            denied_headers: self.denied_headers.clone(),
+            denied_header_values: self.denied_header_values.clone(),
+            allowed_header_matchers: self.allowed_header_matchers.clone(),
+            denied_header_matchers: self.denied_header_matchers.clone(),
+            required_headers: self.required_headers.clone(),
+            max_header_count: self.max_header_count,
+            max_total_header_bytes: self.max_total_header_bytes,
+            allowed_rules: self.allowed_rules.clone(),
+            denied_query_substrings: self.denied_query_substrings.clone(),
+            max_query_length: self.max_query_length,
+            allowed_url_paths: self.allowed_url_paths.clone(),
+            allowed_url_paths_router: self.allowed_url_paths_router.clone(),
+            denied_url_paths: self.denied_url_paths.clone(),
+            denied_url_paths_router: self.denied_url_paths_router.clone(),
+            allowed_method_url_paths: self.allowed_method_url_paths.clone(),
+            allowed_method_url_paths_routers: self.allowed_method_url_paths_routers.clone(),
+            denied_method_url_paths: self.denied_method_url_paths.clone(),
+            denied_method_url_paths_routers: self.denied_method_url_paths_routers.clone(),
+            decode_url_paths: self.decode_url_paths,
+            normalize_url_paths: self.normalize_url_paths,
+            strip_trailing_slashes: self.strip_trailing_slashes,
+            allow_private_ip_ranges: self.allow_private_ip_ranges,
+            allow_loopback_ip_addresses: self.allow_loopback_ip_addresses,
+            allow_link_local_ip_addresses: self.allow_link_local_ip_addresses,
+            allow_unspecified_ip_addresses: self.allow_unspecified_ip_addresses,
+            ip_family_mode: self.ip_family_mode,
+            wildcard_includes_apex: self.wildcard_includes_apex,
+            precedence: self.precedence,
+            method_acl_default: self.method_acl_default,
+            host_acl_default: self.host_acl_default,
+            port_acl_default: self.port_acl_default,
+            ip_acl_default: self.ip_acl_default,
+            url_path_acl_default: self.url_path_acl_default,
+            header_acl_default: self.header_acl_default,
+            scheme_acl_default: self.scheme_acl_default,
+            on_decision: self.on_decision.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+
+    /// Rebuilds the ACL with the rules changed by `f`, keeping the validate functions.
+    pub fn rebuild(
+        &self,
+        f: impl FnOnce(HttpAclBuilder) -> Result<HttpAclBuilder, AddError>,
+    ) -> Result<HttpAcl, AddError> {
+        Ok(HttpAcl {
+            validate_fn: self.validate_fn.clone(),
+            async_validate_fn: self.async_validate_fn.clone(),
+            ..f(self.to_builder())?.try_build()?
+        })
+    }
+
+    /// Combines two ACLs into one that allows what either of them allows, at the rule level.
+    ///
+    /// The allowed rules of both ACLs are merged, only the denied rules present in both ACLs are kept,
+    /// and a default, scheme or precedence is allowing if it is allowing in either ACL. Rules are compared exactly,
+    /// so a rule denied by only one of the ACLs falls back to the combined default.
+    /// An ACL without compound rules allows any combination, so the union only keeps compound rules
+    /// if both ACLs have them.
+    ///
+    /// The static DNS mappings are merged, preferring the mappings of `self`,
+    /// and the validate functions, decision callbacks and clocks are not carried over.
+    pub fn union(&self, other: &HttpAcl) -> HttpAcl {
+        self.combine(other, true)
+    }
+
+    /// Combines two ACLs into one that only allows what both of them allow, at the rule level.
+    ///
+    /// Only the allowed rules present in both ACLs are kept, the denied rules of both ACLs are merged,
+    /// and a default, scheme or precedence is allowing only if it is allowing in both ACLs. Rules are compared exactly,
+    /// so a rule allowed by only one of the ACLs falls back to the combined default.
+    /// An ACL without compound rules allows any combination, so the compound rules of the other ACL
+    /// are kept as is.
+    ///
+    /// The static DNS mappings are merged, preferring the mappings of `self`,
+    /// and the validate functions, decision callbacks and clocks are not carried over.
+    pub fn intersect(&self, other: &HttpAcl) -> HttpAcl {
+        self.combine(other, false)
+    }
+
+    /// Combines two ACLs, as a union if `union` is set and as an intersection otherwise.
+    fn combine(&self, other: &HttpAcl, union: bool) -> HttpAcl {
+        fn merged<T: Clone + PartialEq>(a: &[T], b: &[T]) -> Vec<T> {
+            let mut merged = a.to_vec();
+            merged.extend(b.iter().filter(|item| !a.contains(item)).cloned());
+            merged
+        }
+        fn common<T: Clone + PartialEq>(a: &[T], b: &[T]) -> Vec<T> {
+            a.iter().filter(|item| b.contains(item)).cloned().collect()
+        }
+        fn merged_map<K: Clone + Eq + Hash, V: Clone>(
+            a: &HashMap<K, V>,
+            b: &HashMap<K, V>,
+        ) -> HashMap<K, V> {
+            let mut merged = b.clone();
+            merged.extend(a.iter().map(|(k, v)| (k.clone(), v.clone())));
+            merged
+        }
+        fn common_map<K: Clone + Eq + Hash, V: Clone + PartialEq>(
+            a: &HashMap<K, V>,
+            b: &HashMap<K, V>,
+        ) -> HashMap<K, V> {
+            a.iter()
+                .filter(|(k, v)| b.get(k) == Some(v))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        }
+        fn merged_lists<K: Clone + Eq + Hash, V: Clone + PartialEq>(
+            a: &HashMap<K, Vec<V>>,
+            b: &HashMap<K, Vec<V>>,
+        ) -> HashMap<K, Vec<V>> {
+            let mut lists = a.clone();
+            for (key, list) in b {
+                let entry = lists.entry(key.clone()).or_default();
+                *entry = merged(entry, list);
+            }
+            lists
+        }
+        fn common_lists<K: Clone + Eq + Hash, V: Clone + PartialEq>(
+            a: &HashMap<K, Vec<V>>,
+            b: &HashMap<K, Vec<V>>,
+        ) -> HashMap<K, Vec<V>> {
+            a.iter()
+                .filter_map(|(key, list)| {
+                    let list = common(list, b.get(key)?);
+                    (!list.is_empty()).then(|| (key.clone(), list))
+                })
+                .collect()
+        }
+        fn authorities(authorities: HashSet<Authority>) -> Vec<String> {
+            authorities
+                .into_iter()
+                .collect::<BTreeSet<_>>()
+                .iter()
+                .map(Authority::to_string)
+                .collect()
+        }
+
+        let (a, b) = (self, other);
+        let either = |a: bool, b: bool| if union { a || b } else { a && b };
+        macro_rules! allowed {
+            ($field:ident) => {
+                if union {
+                    merged(&a.$field, &b.$field)
+                } else {
+                    common(&a.$field, &b.$field)
+                }
+            };
+        }
+        macro_rules! denied {
+            ($field:ident) => {
+                if union {
+                    common(&a.$field, &b.$field)
+                } else {
+                    merged(&a.$field, &b.$field)
+                }
+            };
+        }
+
+        let allowed_rules = match (a.allowed_rules.is_empty(), b.allowed_rules.is_empty()) {
+            (true, _) | (_, true) if union => Vec::new(),
+            (true, _) => b.allowed_rules.clone(),
+            (_, true) => a.allowed_rules.clone(),
+            _ => allowed!(allowed_rules),
+        };
+        let (allowed_headers, denied_headers, denied_header_values) = if union {
+            (
+                merged_map(&a.allowed_headers, &b.allowed_headers),
+                common_map(&a.denied_headers, &b.denied_headers),
+                common_lists(&a.denied_header_values, &b.denied_header_values),
+            )
+        } else {
+            (
+                common_map(&a.allowed_headers, &b.allowed_headers),
+                merged_map(&a.denied_headers, &b.denied_headers),
+                merged_lists(&a.denied_header_values, &b.denied_header_values),
+            )
+        };
+        let (allowed_header_matchers, denied_header_matchers) = if union {
+            (
+                merged_lists(&a.allowed_header_matchers, &b.allowed_header_matchers),
+                common_lists(&a.denied_header_matchers, &b.denied_header_matchers),
+            )
+        } else {
+            (
+                common_lists(&a.allowed_header_matchers, &b.allowed_header_matchers),
+                merged_lists(&a.denied_header_matchers, &b.denied_header_matchers),
+            )
+        };
+        let (allowed_method_url_paths, denied_method_url_paths) = if union {
+            (
+                merged_lists(&a.allowed_method_url_paths, &b.allowed_method_url_paths),
+                common_lists(&a.denied_method_url_paths, &b.denied_method_url_paths),
+            )
+        } else {
+            (
+                common_lists(&a.allowed_method_url_paths, &b.allowed_method_url_paths),
+                merged_lists(&a.denied_method_url_paths, &b.denied_method_url_paths),
+            )
+        };
+        let limit = |a: Option<usize>, b: Option<usize>| match (a, b) {
+            (Some(a), Some(b)) if union => Some(a.max(b)),
+            (Some(a), Some(b)) => Some(a.min(b)),
+            _ if union => None,
+            (a, b) => a.or(b),
+        };
+        let ip_family_mode = match (a.ip_family_mode, b.ip_family_mode) {
+            (a, b) if a == b => a,
+            (IpFamilyMode::DualStack, mode) | (mode, IpFamilyMode::DualStack) if !union => mode,
+            _ => IpFamilyMode::DualStack,
+        };
+
+        HttpAclBuilder {
+            allow_http: either(a.allow_http, b.allow_http),
+            allow_https: either(a.allow_https, b.allow_https),
+            allowed_methods: allowed!(allowed_methods),
+            denied_methods: denied!(denied_methods),
+            allowed_hosts: allowed!(allowed_hosts),
+            denied_hosts: denied!(denied_hosts),
+            allowed_authorities: authorities(if union {
+                &a.allowed_authorities | &b.allowed_authorities
+            } else {
+                &a.allowed_authorities & &b.allowed_authorities
+            }),
+            denied_authorities: authorities(if union {
+                &a.denied_authorities & &b.denied_authorities
+            } else {
+                &a.denied_authorities | &b.denied_authorities
+            }),
+            #[cfg(feature = "regex")]
+            allowed_host_regexes: {
+                let (a, b) = (a.host_regex_patterns().0, b.host_regex_patterns().0);
+                if union {
+                    merged(&a, &b)
+                } else {
+                    common(&a, &b)
+                }
+            },
+            #[cfg(feature = "regex")]
+            denied_host_regexes: {
+                let (a, b) = (a.host_regex_patterns().1, b.host_regex_patterns().1);
+                if union {
+                    common(&a, &b)
+                } else {
+                    merged(&a, &b)
+                }
+            },
+            allowed_port_ranges: allowed!(allowed_port_ranges),
+            denied_port_ranges: denied!(denied_port_ranges),
+            allowed_ip_ranges: allowed!(allowed_ip_ranges),
+            denied_ip_ranges: denied!(denied_ip_ranges),
+            expiring_denied_ip_ranges: denied!(expiring_denied_ip_ranges),
+            static_dns_mapping: merged_map(&a.static_dns_mapping, &b.static_dns_mapping),
+            static_mappings_authoritative: a.static_mappings_authoritative
+                || b.static_mappings_authoritative,
+            kill_switch: a.kill_switch || b.kill_switch,
+            allowed_headers,
+            denied_headers,
+            denied_header_values,
+            allowed_header_matchers,
+            denied_header_matchers,
+            required_headers: denied!(required_headers),
+            max_header_count: limit(a.max_header_count, b.max_header_count),
+            max_total_header_bytes: limit(a.max_total_header_bytes, b.max_total_header_bytes),
+            allowed_rules,
+            denied_query_substrings: denied!(denied_query_substrings),
+            max_query_length: limit(a.max_query_length, b.max_query_length),
+            allowed_url_paths: allowed!(allowed_url_paths),
+            allowed_url_paths_router: Router::new(),
+            denied_url_paths: denied!(denied_url_paths),
+            denied_url_paths_router: Router::new(),
+            allowed_method_url_paths,
+            allowed_method_url_paths_routers: HashMap::new(),
+            denied_method_url_paths,
+            denied_method_url_paths_routers: HashMap::new(),
+            decode_url_paths: a.decode_url_paths || b.decode_url_paths,
+            normalize_url_paths: a.normalize_url_paths || b.normalize_url_paths,
+            strip_trailing_slashes: a.strip_trailing_slashes || b.strip_trailing_slashes,
+            allow_private_ip_ranges: either(a.allow_private_ip_ranges, b.allow_private_ip_ranges),
+            allow_loopback_ip_addresses: either(
+                a.allow_loopback_ip_addresses,
+                b.allow_loopback_ip_addresses,
+            ),
+            allow_link_local_ip_addresses: either(
+                a.allow_link_local_ip_addresses,
+                b.allow_link_local_ip_addresses,
+            ),
+            allow_unspecified_ip_addresses: either(
+                a.allow_unspecified_ip_addresses,
+                b.allow_unspecified_ip_addresses,
+            ),
+            ip_family_mode,
+            wildcard_includes_apex: either(a.wildcard_includes_apex, b.wildcard_includes_apex),
+            precedence: if either(
+                a.precedence == Precedence::AllowWins,
+                b.precedence == Precedence::AllowWins,
+            ) {
+                Precedence::AllowWins
+            } else {
+                Precedence::DenyWins
+            },
+            method_acl_default: either(a.method_acl_default, b.method_acl_default),
+            host_acl_default: either(a.host_acl_default, b.host_acl_default),
+            port_acl_default: either(a.port_acl_default, b.port_acl_default),
+            ip_acl_default: either(a.ip_acl_default, b.ip_acl_default),
+            url_path_acl_default: either(a.url_path_acl_default, b.url_path_acl_default),
+            header_acl_default: either(a.header_acl_default, b.header_acl_default),
+            scheme_acl_default: either(a.scheme_acl_default, b.scheme_acl_default),
+            on_decision: None,
+            clock: None,
+        }
+        .build()
+    }
+
+    /// Exports the allowed and denied IP ranges as CIDR networks.
+    ///
+    /// Ranges that are not aligned to a CIDR network are split into several networks.
+    pub fn export_ip_rules(&self) -> (Vec<IpNet>, Vec<IpNet>) {
+        (
+            self.allowed_ip_ranges
+                .iter()
+                .flat_map(utils::ip_range_to_ip_nets)
+                .collect(),
+            self.denied_ip_ranges
+                .iter()
+                .flat_map(utils::ip_range_to_ip_nets)
+                .collect(),
+        )
+    }
+
+    /// Returns whether an authority is allowed.
+    ///
+    /// An authority matching an allowed or denied authority exactly, including its port,
+    /// is classified by that rule. Otherwise an IP host is checked against the IP ranges
+    /// and a domain host against the hosts, and the port is also checked, unless it is unspecified.
+    pub fn is_authority_allowed(&self, authority: &Authority) -> AclClassification {
+        if let Some(classification) = self.classify_authority(authority) {
+            return self.decide(Dimension::Host, authority, classification);
+        }
+        let classification = match &authority.host {
+            Host::Ip(ip) => self.is_ip_allowed(ip),
+            Host::Domain(domain) => self.is_host_allowed(domain),
+        };
+        let Some(port) = authority.port else {
+            return classification;
+        };
+        if classification.is_denied() {
+            return classification;
+        }
+        let port_classification = self.is_port_allowed(port);
+        if port_classification.is_denied() {
+            port_classification
+        } else {
+            classification
+        }
+    }
+
+    /// Evaluates an authority against the ACL, along with the IP address its domain resolved to.
+    ///
+    /// Unlike [`HttpAcl::is_authority_allowed`], the host, IP and port decisions are recorded
+    /// separately, so [`ValidationReport::combined_reason`] can tell which of them governed.
+    pub fn evaluate_authority(
+        &self,
+        authority: &Authority,
+        resolved_ip: Option<IpAddr>,
+    ) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        if let Some(classification) = self.classify_authority(authority) {
+            report.push(Dimension::Host, classification);
+            return report;
+        }
+        match &authority.host {
+            Host::Ip(ip) => report.push(Dimension::Ip, self.is_ip_allowed(ip)),
+            Host::Domain(domain) => {
+                report.push(Dimension::Host, self.is_host_allowed(domain));
+                if let Some(ip) = resolved_ip {
+                    report.push(Dimension::Ip, self.is_ip_allowed(&ip));
+                }
+            }
+        }
+        if let Some(port) = authority.port {
+            report.push(Dimension::Port, self.is_port_allowed(port));
+        }
+        report
+    }
+
+    /// Returns the classification of an authority if it matches an authority rule exactly.
+    ///
+    /// A matching authority rule takes precedence over the separate host, IP and port rules.
+    pub fn authority_rule_classification(
+        &self,
+        authority: &Authority,
+    ) -> Option<AclClassification> {
+        let classification = self.classify_authority(authority)?;
+        Some(self.decide(Dimension::Host, authority, classification))
+    }
+
+    /// Returns a lazy iterator over the classification of the authority of a request,
+    /// either of the authority rule it matches or of its host or IP and its port.
+    ///
+    /// The authority rules are matched with the port of the request, falling back to
+    /// the default port of the scheme.
+    fn authority_classifications<'r>(
+        &'r self,
+        scheme: &'r str,
+        host: &'r str,
+        port: Option<u16>,
+    ) -> impl Iterator<Item = (Dimension, AclClassification)> + 'r {
+        std::iter::once_with(move || {
+            let port = port.or(Self::default_port(scheme))?;
+            let host = match Self::parse_ip_host(host) {
+                Some(ip) => Host::Ip(ip),
+                None => Host::Domain(host.to_string()),
+            };
+            self.authority_rule_classification(&Authority {
+                host,
+                port: Some(port),
+            })
+        })
+        .flat_map(
+            move |rule| -> Box<dyn Iterator<Item = (Dimension, AclClassification)> + 'r> {
+                match rule {
+                    Some(classification) => {
+                        Box::new(std::iter::once((Dimension::Host, classification)))
+                    }
+                    None => Box::new(
+                        std::iter::once_with(move || self.host_classification(host)).chain(
+                            std::iter::once_with(move || {
+                                (Dimension::Port, self.port_classification(scheme, port))
+                            }),
+                        ),
+                    ),
+                }
+            },
+        )
+    }
+
+    /// Classifies an authority against the authorities, if it matches one exactly.
+    fn classify_authority(&self, authority: &Authority) -> Option<AclClassification> {
+        if self.denied_authorities.contains(authority) {
+            Some(AclClassification::DeniedUserAcl)
+        } else if self.allowed_authorities.contains(authority) {
+            Some(AclClassification::AllowedUserAcl)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether a host, either a domain or an IP address, is reachable on any scheme and port.
+    ///
+    /// The host is only reachable if it is allowed and at least one scheme and one port are allowed.
+    /// A scheme other than HTTP and HTTPS counts as allowed when the scheme default allows it.
+    pub fn is_host_reachable(&self, host: &str) -> AclClassification {
+        let (_, classification) = self.host_classification(host);
+        if classification.is_denied() {
+            classification
+        } else if !self.allow_http && !self.allow_https && !self.scheme_acl_default {
+            AclClassification::Denied("no scheme is allowed".to_string())
+        } else if self.min_allowed_port().is_none() {
+            AclClassification::Denied("no port is allowed".to_string())
+        } else {
+            classification
+        }
+    }
+
+    /// Returns whether a combination of scheme, method and host is allowed by a single compound rule.
+    ///
+    /// Once any compound rule is added, only the combinations matching a compound rule are allowed.
+    pub fn is_compound_allowed(
+        &self,
+        scheme: &str,
+        method: impl Into<HttpRequestMethod>,
+        host: &str,
+    ) -> AclClassification {
+        let method = method.into();
+        self.decide(
+            Dimension::Compound,
+            &format_args!("{} {} {}", scheme, method.as_str(), host),
+            self.classify_compound(scheme, &method, host),
+        )
+    }
+
+    /// Classifies a combination of scheme, method and host without calling the decision callback.
+    fn classify_compound(
+        &self,
+        scheme: &str,
+        method: &HttpRequestMethod,
+        host: &str,
+    ) -> AclClassification {
+        if self.allowed_rules.iter().any(|rule| {
+            rule.scheme.eq_ignore_ascii_case(scheme)
+                && rule.method == *method
+                && utils::host::matches_host(&rule.host, host, self.wildcard_includes_apex)
+        }) {
+            AclClassification::AllowedUserAcl
+        } else if self.allowed_rules.is_empty() {
+            AclClassification::AllowedDefault
+        } else {
+            AclClassification::DeniedDefault
+        }
+    }
+
+    /// Returns whether a URL query is allowed.
+    ///
+    /// A query longer than the maximum query length is denied before it is decoded.
+    /// Otherwise, the query is percent-decoded and denied if it contains any of the denied
+    /// query substrings, compared case-insensitively.
+    pub fn is_url_query_allowed(&self, query: impl AsRef<str>) -> AclClassification {
+        let query = query.as_ref();
+        self.decide(Dimension::Query, &query, self.classify_url_query(query))
+    }
+
+    /// Classifies a URL query without calling the decision callback.
+    fn classify_url_query(&self, query: impl AsRef<str>) -> AclClassification {
+        let query = query.as_ref();
+        if self.max_query_length.is_some_and(|max| query.len() > max) {
+            AclClassification::Denied("query too long".to_string())
+        } else if self.matching_query_substring(query).is_some() {
+            AclClassification::DeniedUserAcl
+        } else {
+            AclClassification::AllowedDefault
+        }
+    }
+
+    /// Returns the denied query substring contained in a URL query.
+    fn matching_query_substring(&self, query: &str) -> Option<&String> {
+        if self.denied_query_substrings.is_empty() {
+            return None;
+        }
+        let query = utils::url::decode_url_query(query).to_lowercase();
+        self.denied_query_substrings
+            .iter()
+            .find(|substring| query.contains(substring.as_str()))
+    }
+
     /// Returns whether the port is allowed.
     pub fn is_port_allowed(&self, port: u16) -> AclClassification {
+        self.decide(Dimension::Port, &port, self.classify_port(port))
+    }
+
+    /// Classifies a port without calling the decision callback.
+    fn classify_port(&self, port: u16) -> AclClassification {
         if Self::is_port_in_ranges(port, &self.denied_port_ranges) {
             AclClassification::DeniedUserAcl
         } else if Self::is_port_in_ranges(port, &self.allowed_port_ranges) {
@@ -231,17 +1360,52 @@ impl HttpAcl {
 
     /// Returns whether an IP is allowed.
     pub fn is_ip_allowed(&self, ip: &IpAddr) -> AclClassification {
-        if (!utils::ip::is_global_ip(ip) || ip.is_loopback()) && !utils::ip::is_private_ip(ip) {
-            if Self::is_ip_in_ranges(ip, &self.allowed_ip_ranges) {
+        self.decide(Dimension::Ip, ip, self.classify_ip(ip))
+    }
+
+    /// Partitions IPs into the allowed and the denied ones, in their original order.
+    pub fn filter_allowed_ips<'a>(
+        &self,
+        ips: impl IntoIterator<Item = &'a IpAddr>,
+    ) -> (Vec<IpAddr>, Vec<IpAddr>) {
+        ips.into_iter()
+            .partition(|ip| self.is_ip_allowed(ip).is_allowed())
+    }
+
+    /// Classifies an IP without calling the decision callback.
+    fn classify_ip(&self, ip: &IpAddr) -> AclClassification {
+        match (self.ip_family_mode, ip) {
+            (IpFamilyMode::V4Only, IpAddr::V6(_)) => {
+                return AclClassification::Denied("IPv6 addresses are disabled".to_string());
+            }
+            (IpFamilyMode::V6Only, IpAddr::V4(_)) => {
+                return AclClassification::Denied("IPv4 addresses are disabled".to_string());
+            }
+            _ => {}
+        }
+
+        if ip.is_unspecified() && !self.allow_unspecified_ip_addresses {
+            return AclClassification::DeniedNotGlobal;
+        }
+
+        let denied = self.denied_ip_index.contains(ip) || self.is_ip_in_expiring_denied_ranges(ip);
+        let allowed = self.allowed_ip_index.contains(ip)
+            && (!denied || self.precedence == Precedence::AllowWins);
+
+        if !utils::ip::is_global_ip(ip)
+            && !utils::ip::is_private_ip(ip)
+            && !self.is_non_global_ip_allowed(ip)
+        {
+            if allowed {
                 return AclClassification::AllowedUserAcl;
             } else {
                 return AclClassification::DeniedNotGlobal;
             }
         }
 
-        if Self::is_ip_in_ranges(ip, &self.allowed_ip_ranges) {
+        if allowed {
             AclClassification::AllowedUserAcl
-        } else if Self::is_ip_in_ranges(ip, &self.denied_ip_ranges) {
+        } else if denied {
             AclClassification::DeniedUserAcl
         } else if utils::ip::is_private_ip(ip) && !self.allow_private_ip_ranges {
             AclClassification::DeniedPrivateRange
@@ -252,51 +1416,1211 @@ impl HttpAcl {
         }
     }
 
-    /// Resolve static DNS mapping.
-    pub fn resolve_static_dns_mapping(&self, host: &str) -> Option<SocketAddr> {
-        self.static_dns_mapping.get(host).copied()
+    /// Returns whether an IP is in an expiring denied IP range that has not expired yet.
+    fn is_ip_in_expiring_denied_ranges(&self, ip: &IpAddr) -> bool {
+        if self.expiring_denied_ip_ranges.is_empty() {
+            return false;
+        }
+        let now = self.now();
+        self.expiring_denied_ip_ranges
+            .iter()
+            .any(|(ip_range, expiry)| *expiry > now && ip_range.contains(ip))
     }
 
-    /// Returns whether a URL path is allowed.
-    pub fn is_url_path_allowed(&self, url_path: &str) -> AclClassification {
-        if self.allowed_url_paths_router.at(url_path).is_ok() {
-            AclClassification::AllowedUserAcl
-        } else if self.denied_url_paths_router.at(url_path).is_ok() {
-            AclClassification::DeniedUserAcl
-        } else if self.url_path_acl_default {
-            AclClassification::AllowedDefault
-        } else {
-            AclClassification::DeniedDefault
-        }
+    /// Returns the current time of the clock of the ACL.
+    fn now(&self) -> Instant {
+        self.clock
+            .as_ref()
+            .map_or_else(Instant::now, |clock| clock())
     }
 
-    /// Checks if an ip is in a list of ip ranges.
-    fn is_ip_in_ranges(ip: &IpAddr, ranges: &[RangeInclusive<IpAddr>]) -> bool {
-        ranges.iter().any(|range| range.contains(ip))
+    /// Removes the expired IP ranges from the expiring denied IP ranges.
+    pub fn prune_expired(&mut self) {
+        let now = self.now();
+        self.expiring_denied_ip_ranges
+            .retain(|(_, expiry)| *expiry > now);
     }
 
-    /// Checks if a port is in a list of port ranges.
-    fn is_port_in_ranges(port: u16, ranges: &[RangeInclusive<u16>]) -> bool {
-        ranges.iter().any(|range| range.contains(&port))
+    /// Returns the expiring denied IP ranges with their expiry, including the expired ones
+    /// until [`HttpAcl::prune_expired`] is called.
+    pub fn expiring_denied_ip_ranges(&self) -> &[(RangeInclusive<IpAddr>, Instant)] {
+        &self.expiring_denied_ip_ranges
     }
-}
 
-/// Represents an ACL Classification.
-#[non_exhaustive]
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub enum AclClassification {
-    /// The entiy is allowed according to the allowed ACL.
-    AllowedUserAcl,
-    /// The entity is allowed because the default is to allow if no ACL match is found.
-    AllowedDefault,
-    /// The entiy is denied according to the denied ACL.
-    DeniedUserAcl,
-    /// The entity is denied because the default is to deny if no ACL match is found.
-    DeniedDefault,
-    /// The entiy is denied.
-    Denied(String),
-    /// The IP is denied because it is not global.
-    DeniedNotGlobal,
+    /// Returns whether an IP range overlaps any of the allowed IP ranges.
+    pub fn overlaps_allowed_ip_ranges<Ip: IntoIpRange>(&self, ip_range: Ip) -> bool {
+        ip_range
+            .into_range()
+            .is_some_and(|ip_range| self.allowed_ip_index.overlaps(&ip_range))
+    }
+
+    /// Returns whether an IP network is entirely contained in one of the allowed IP ranges.
+    ///
+    /// Unlike [`HttpAcl::is_ip_allowed`], the denied IP ranges and the defaults are ignored.
+    pub fn is_ip_range_subset_of_allowed(&self, net: &IpNet) -> bool {
+        self.allowed_ip_index
+            .covers(&(net.network()..=net.broadcast()))
+    }
+
+    /// Returns whether an IP range overlaps any of the denied IP ranges.
+    pub fn overlaps_denied_ip_ranges<Ip: IntoIpRange>(&self, ip_range: Ip) -> bool {
+        ip_range
+            .into_range()
+            .is_some_and(|ip_range| self.denied_ip_index.overlaps(&ip_range))
+    }
+
+    /// Returns the allowed IP ranges, sorted by their start.
+    pub fn sorted_allowed_ip_ranges(&self) -> &[RangeInclusive<IpAddr>] {
+        &self.allowed_ip_ranges
+    }
+
+    /// Returns the denied IP ranges, sorted by their start.
+    pub fn sorted_denied_ip_ranges(&self) -> &[RangeInclusive<IpAddr>] {
+        &self.denied_ip_ranges
+    }
+
+    /// Returns whether a non-global IP address is allowed by the loopback or link-local toggles.
+    fn is_non_global_ip_allowed(&self, ip: &IpAddr) -> bool {
+        (self.allow_loopback_ip_addresses && ip.is_loopback())
+            || (self.allow_link_local_ip_addresses && utils::ip::is_link_local_ip(ip))
+            || (self.allow_unspecified_ip_addresses && ip.is_unspecified())
+    }
+
+    /// Resolve static DNS mapping.
+    ///
+    /// Returns an empty slice if the host has no static DNS mapping.
+    pub fn resolve_static_dns_mapping(&self, host: &str) -> &[SocketAddr] {
+        self.static_dns_mapping
+            .get(host)
+            .map(|sock_addrs| sock_addrs.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns whether static DNS mappings are authoritative.
+    ///
+    /// When enabled, a host with a static DNS mapping is never resolved via DNS,
+    /// even if none of its mapped addresses are allowed.
+    pub fn static_mappings_authoritative(&self) -> bool {
+        self.static_mappings_authoritative
+    }
+
+    /// Returns whether the kill switch is engaged.
+    pub fn kill_switch_engaged(&self) -> bool {
+        self.kill_switch
+    }
+
+    /// Engages or releases the kill switch.
+    ///
+    /// While engaged, every check is denied with [`AclClassification::Denied`],
+    /// regardless of the rules of the ACL, which are kept to be enforced again once released.
+    pub fn set_kill_switch(&mut self, engaged: bool) {
+        self.kill_switch = engaged;
+    }
+
+    /// Returns whether a validate function or an async validate function is set.
+    fn has_validate_fn(&self) -> bool {
+        self.validate_fn.is_some() || self.async_validate_fn.is_some()
+    }
+
+    /// Returns the denial of the kill switch if it is engaged.
+    fn kill_switch_denial(&self) -> Option<AclClassification> {
+        self.kill_switch
+            .then(|| AclClassification::Denied("acl kill switch engaged".to_string()))
+    }
+
+    /// Returns whether a URL path is allowed.
+    pub fn is_url_path_allowed(&self, url_path: &str) -> AclClassification {
+        self.decide(
+            Dimension::UrlPath,
+            &url_path,
+            self.classify_url_path(url_path),
+        )
+    }
+
+    /// Classifies a URL path without calling the decision callback.
+    fn classify_url_path(&self, url_path: &str) -> AclClassification {
+        let url_path = self.prepare_url_path(url_path);
+        self.url_path_classification(&url_path)
+    }
+
+    /// Prepares a URL path for matching.
+    fn prepare_url_path<'a>(&self, url_path: &'a str) -> Cow<'a, str> {
+        let mut url_path = Cow::Borrowed(url_path);
+        if self.decode_url_paths {
+            url_path = Cow::Owned(utils::url::decode_url_path(&url_path));
+        }
+        if self.normalize_url_paths {
+            url_path = Cow::Owned(utils::url::normalize_url_path(&url_path));
+        }
+        if self.strip_trailing_slashes {
+            url_path = match url_path {
+                Cow::Borrowed(url_path) => {
+                    Cow::Borrowed(utils::url::strip_trailing_slash(url_path))
+                }
+                Cow::Owned(url_path) => {
+                    Cow::Owned(utils::url::strip_trailing_slash(&url_path).to_string())
+                }
+            };
+        }
+        url_path
+    }
+
+    /// Classifies a prepared URL path against the URL path routers.
+    fn url_path_classification(&self, url_path: &str) -> AclClassification {
+        if self.allowed_url_paths_router.at(url_path).is_ok() {
+            AclClassification::AllowedUserAcl
+        } else if self.denied_url_paths_router.at(url_path).is_ok() {
+            AclClassification::DeniedUserAcl
+        } else if self.url_path_acl_default {
+            AclClassification::AllowedDefault
+        } else {
+            AclClassification::DeniedDefault
+        }
+    }
+
+    /// Returns the lowest port that is allowed, if any.
+    pub fn min_allowed_port(&self) -> Option<u16> {
+        // The lowest allowed port is either the start of an allowed range,
+        // the port right after a denied range or zero when allowed by default.
+        self.allowed_port_ranges
+            .iter()
+            .map(|range| *range.start())
+            .chain(
+                self.denied_port_ranges
+                    .iter()
+                    .filter_map(|range| range.end().checked_add(1)),
+            )
+            .chain(self.port_acl_default.then_some(u16::MIN))
+            .filter(|port| self.classify_port(*port).is_allowed())
+            .min()
+    }
+
+    /// Returns the highest port that is allowed, if any.
+    pub fn max_allowed_port(&self) -> Option<u16> {
+        // The highest allowed port is either the end of an allowed range,
+        // the port right before a denied range or the maximum port when allowed by default.
+        self.allowed_port_ranges
+            .iter()
+            .map(|range| *range.end())
+            .chain(
+                self.denied_port_ranges
+                    .iter()
+                    .filter_map(|range| range.start().checked_sub(1)),
+            )
+            .chain(self.port_acl_default.then_some(u16::MAX))
+            .filter(|port| self.classify_port(*port).is_allowed())
+            .max()
+    }
+
+    /// Returns whether a URL path is allowed for the given method.
+    ///
+    /// The URL paths specific to the method are checked first,
+    /// falling back to the URL paths that apply to every method.
+    pub fn is_url_path_allowed_for_method(
+        &self,
+        method: impl Into<HttpRequestMethod>,
+        url_path: &str,
+    ) -> AclClassification {
+        let method = method.into();
+        self.decide(
+            Dimension::UrlPath,
+            &url_path,
+            self.classify_url_path_for_method(&method, url_path),
+        )
+    }
+
+    /// Classifies a URL path for a method without calling the decision callback.
+    fn classify_url_path_for_method(
+        &self,
+        method: &HttpRequestMethod,
+        url_path: &str,
+    ) -> AclClassification {
+        let url_path = self.prepare_url_path(url_path);
+        if self
+            .allowed_method_url_paths_routers
+            .get(method)
+            .is_some_and(|router| router.at(&url_path).is_ok())
+        {
+            AclClassification::AllowedUserAcl
+        } else if self
+            .denied_method_url_paths_routers
+            .get(method)
+            .is_some_and(|router| router.at(&url_path).is_ok())
+        {
+            AclClassification::DeniedUserAcl
+        } else {
+            self.url_path_classification(&url_path)
+        }
+    }
+
+    /// Returns the rules that can never govern a decision, as the rules that take precedence
+    /// over them always match first.
+    ///
+    /// Denied hosts and port ranges win over allowed ones, so an allowed host or port range
+    /// covered by a denied rule is shadowed. IP ranges follow the [`Precedence`], so with
+    /// [`Precedence::DenyWins`] an allowed IP range covered by denied IP ranges is shadowed,
+    /// and with [`Precedence::AllowWins`] a denied IP range covered by allowed IP ranges is.
+    pub fn shadowed_rules(&self) -> Vec<ShadowedRule> {
+        let denied_port_index = RangeIndex::new(&self.denied_port_ranges);
+        let ports = self
+            .allowed_port_ranges
+            .iter()
+            .filter(|range| denied_port_index.covers(range))
+            .map(|range| ShadowedRule {
+                dimension: Dimension::Port,
+                rule: format!("{}-{}", range.start(), range.end()),
+            });
+        let (ip_ranges, winning_ip_index) = match self.precedence {
+            Precedence::AllowWins => (&self.denied_ip_ranges, &self.allowed_ip_index),
+            Precedence::DenyWins => (&self.allowed_ip_ranges, &self.denied_ip_index),
+        };
+        let ips = ip_ranges
+            .iter()
+            .filter(|range| winning_ip_index.covers(range))
+            .map(|range| ShadowedRule {
+                dimension: Dimension::Ip,
+                rule: format!("{}-{}", range.start(), range.end()),
+            });
+        let hosts = self
+            .allowed_hosts
+            .iter()
+            .filter(|host| self.is_host_shadowed(host))
+            .map(|host| ShadowedRule {
+                dimension: Dimension::Host,
+                rule: host.clone(),
+            });
+        hosts.chain(ports).chain(ips).collect()
+    }
+
+    /// Returns the number of allowed and denied rules of each dimension.
+    ///
+    /// The URL path counts include the URL paths of specific methods.
+    pub fn rule_counts(&self) -> RuleCounts {
+        let method_url_paths = |paths: &HashMap<HttpRequestMethod, Vec<String>>| {
+            paths.values().map(Vec::len).sum::<usize>()
+        };
+        RuleCounts {
+            allowed_methods: self.allowed_methods.len(),
+            denied_methods: self.denied_methods.len(),
+            allowed_hosts: self.allowed_hosts.len(),
+            denied_hosts: self.denied_hosts.len(),
+            allowed_port_ranges: self.allowed_port_ranges.len(),
+            denied_port_ranges: self.denied_port_ranges.len(),
+            allowed_ip_ranges: self.allowed_ip_ranges.len(),
+            denied_ip_ranges: self.denied_ip_ranges.len(),
+            allowed_headers: self.allowed_headers.len(),
+            denied_headers: self.denied_headers.len(),
+            allowed_url_paths: self.allowed_url_paths.len()
+                + method_url_paths(&self.allowed_method_url_paths),
+            denied_url_paths: self.denied_url_paths.len()
+                + method_url_paths(&self.denied_method_url_paths),
+        }
+    }
+
+    /// Returns the differences between this ACL and another ACL.
+    ///
+    /// Rules of the other ACL missing from this ACL are added, rules of this ACL missing
+    /// from the other ACL are removed, and the boolean flags and other settings with
+    /// different values are changed.
+    pub fn diff(&self, other: &HttpAcl) -> AclDiff {
+        let (rules, other_rules) = (self.rules(), other.rules());
+        AclDiff {
+            added: other_rules.difference(&rules).cloned().collect(),
+            removed: rules.difference(&other_rules).cloned().collect(),
+            changed_flags: self
+                .flags()
+                .into_iter()
+                .zip(other.flags())
+                .filter(|((_, value), (_, other_value))| value != other_value)
+                .map(|((flag, value), (_, other_value))| (flag, value, other_value))
+                .collect(),
+            changed_settings: self
+                .settings()
+                .into_iter()
+                .zip(other.settings())
+                .filter(|((_, value), (_, other_value))| value != other_value)
+                .map(|((setting, value), (_, other_value))| (setting, value, other_value))
+                .collect(),
+        }
+    }
+
+    /// Returns every allowed and denied rule, sorted by dimension.
+    fn rules(&self) -> BTreeSet<AclRule> {
+        fn rules<'a, T>(
+            dimension: Dimension,
+            allowed: bool,
+            entries: &'a [T],
+            format: impl Fn(&T) -> String + 'a,
+        ) -> impl Iterator<Item = AclRule> + 'a {
+            entries.iter().map(move |entry| AclRule {
+                dimension,
+                allowed,
+                rule: format(entry),
+            })
+        }
+        fn format_range<T: std::fmt::Display>(range: &RangeInclusive<T>) -> String {
+            format!("{}-{}", range.start(), range.end())
+        }
+        fn format_header((name, value): (&String, &Option<String>)) -> String {
+            match value {
+                Some(value) => format!("{}: {}", name, value),
+                None => name.clone(),
+            }
+        }
+        fn method_url_paths(
+            paths: &HashMap<HttpRequestMethod, Vec<String>>,
+        ) -> impl Iterator<Item = String> + '_ {
+            paths.iter().flat_map(|(method, paths)| {
+                paths
+                    .iter()
+                    .map(move |path| format!("{} {}", method.as_str(), path))
+            })
+        }
+
+        rules(Dimension::Method, true, &self.allowed_methods, |m| {
+            m.as_str().to_string()
+        })
+        .chain(rules(Dimension::Method, false, &self.denied_methods, |m| {
+            m.as_str().to_string()
+        }))
+        .chain(rules(
+            Dimension::Host,
+            true,
+            &self.allowed_hosts,
+            String::clone,
+        ))
+        .chain(rules(
+            Dimension::Host,
+            false,
+            &self.denied_hosts,
+            String::clone,
+        ))
+        .chain(
+            [
+                (true, &self.allowed_authorities),
+                (false, &self.denied_authorities),
+            ]
+            .into_iter()
+            .flat_map(|(allowed, authorities)| {
+                authorities.iter().map(move |authority| AclRule {
+                    dimension: Dimension::Host,
+                    allowed,
+                    rule: authority.to_string(),
+                })
+            }),
+        )
+        .chain(rules(
+            Dimension::Port,
+            true,
+            &self.allowed_port_ranges,
+            format_range,
+        ))
+        .chain(rules(
+            Dimension::Port,
+            false,
+            &self.denied_port_ranges,
+            format_range,
+        ))
+        .chain(rules(
+            Dimension::Ip,
+            true,
+            &self.allowed_ip_ranges,
+            format_range,
+        ))
+        .chain(rules(
+            Dimension::Ip,
+            false,
+            &self.denied_ip_ranges,
+            format_range,
+        ))
+        .chain(rules(
+            Dimension::UrlPath,
+            true,
+            &self.allowed_url_paths,
+            String::clone,
+        ))
+        .chain(rules(
+            Dimension::UrlPath,
+            false,
+            &self.denied_url_paths,
+            String::clone,
+        ))
+        .chain(rules(
+            Dimension::UrlPath,
+            true,
+            &method_url_paths(&self.allowed_method_url_paths).collect::<Vec<_>>(),
+            String::clone,
+        ))
+        .chain(rules(
+            Dimension::UrlPath,
+            false,
+            &method_url_paths(&self.denied_method_url_paths).collect::<Vec<_>>(),
+            String::clone,
+        ))
+        .chain(self.allowed_headers.iter().map(|header| AclRule {
+            dimension: Dimension::Header,
+            allowed: true,
+            rule: format_header(header),
+        }))
+        .chain(self.denied_headers.iter().map(|header| AclRule {
+            dimension: Dimension::Header,
+            allowed: false,
+            rule: format_header(header),
+        }))
+        .chain(
+            self.denied_header_values
+                .iter()
+                .flat_map(|(name, values)| values.iter().map(move |value| (name, value)))
+                .map(|(name, value)| AclRule {
+                    dimension: Dimension::Header,
+                    allowed: false,
+                    rule: format!("{}: {}", name, value),
+                }),
+        )
+        .chain(
+            [
+                (true, &self.allowed_header_matchers),
+                (false, &self.denied_header_matchers),
+            ]
+            .into_iter()
+            .flat_map(|(allowed, matchers)| {
+                matchers.iter().flat_map(move |(name, matchers)| {
+                    matchers.iter().map(move |matcher| AclRule {
+                        dimension: Dimension::Header,
+                        allowed,
+                        rule: format!("{}: {}", name, matcher),
+                    })
+                })
+            }),
+        )
+        .chain(self.host_regex_rules())
+        .chain(rules(
+            Dimension::Query,
+            false,
+            &self.denied_query_substrings,
+            String::clone,
+        ))
+        .chain(rules(
+            Dimension::Compound,
+            true,
+            &self.allowed_rules,
+            |rule| format!("{} {}://{}", rule.method.as_str(), rule.scheme, rule.host),
+        ))
+        .collect()
+    }
+
+    /// Returns the host regular expressions of the ACL as rules, formatted as `/pattern/`.
+    #[cfg(feature = "regex")]
+    fn host_regex_rules(&self) -> impl Iterator<Item = AclRule> + '_ {
+        [
+            (true, &self.allowed_host_regexes),
+            (false, &self.denied_host_regexes),
+        ]
+        .into_iter()
+        .flat_map(|(allowed, regexes)| {
+            regexes.iter().map(move |re| AclRule {
+                dimension: Dimension::Host,
+                allowed,
+                rule: format!("/{}/", re.as_str()),
+            })
+        })
+    }
+
+    /// Returns the host regular expressions of the ACL as rules, which are only available
+    /// with the `regex` feature.
+    #[cfg(not(feature = "regex"))]
+    fn host_regex_rules(&self) -> impl Iterator<Item = AclRule> + '_ {
+        std::iter::empty()
+    }
+
+    /// Returns the settings of the ACL that are not boolean flags by name, formatted as strings.
+    fn settings(&self) -> [(&'static str, String); 7] {
+        fn limit(limit: Option<usize>) -> String {
+            limit.map_or_else(|| "none".to_string(), |limit| limit.to_string())
+        }
+        let mut static_dns_mapping = self
+            .static_dns_mapping
+            .iter()
+            .map(|(host, addresses)| {
+                let addresses = addresses
+                    .iter()
+                    .map(SocketAddr::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{} => {}", host, addresses)
+            })
+            .collect::<Vec<_>>();
+        static_dns_mapping.sort();
+        [
+            ("ip_family_mode", format!("{:?}", self.ip_family_mode)),
+            ("precedence", format!("{:?}", self.precedence)),
+            ("required_headers", self.required_headers.join(", ")),
+            ("max_header_count", limit(self.max_header_count)),
+            ("max_total_header_bytes", limit(self.max_total_header_bytes)),
+            ("max_query_length", limit(self.max_query_length)),
+            ("static_dns_mapping", static_dns_mapping.join("; ")),
+        ]
+    }
+
+    /// Returns the boolean flags of the ACL by name.
+    fn flags(&self) -> [(&'static str, bool); 19] {
+        [
+            ("allow_http", self.allow_http),
+            ("allow_https", self.allow_https),
+            ("allow_private_ip_ranges", self.allow_private_ip_ranges),
+            (
+                "allow_loopback_ip_addresses",
+                self.allow_loopback_ip_addresses,
+            ),
+            (
+                "allow_link_local_ip_addresses",
+                self.allow_link_local_ip_addresses,
+            ),
+            (
+                "allow_unspecified_ip_addresses",
+                self.allow_unspecified_ip_addresses,
+            ),
+            (
+                "static_mappings_authoritative",
+                self.static_mappings_authoritative,
+            ),
+            ("kill_switch", self.kill_switch),
+            ("wildcard_includes_apex", self.wildcard_includes_apex),
+            ("decode_url_paths", self.decode_url_paths),
+            ("normalize_url_paths", self.normalize_url_paths),
+            ("strip_trailing_slashes", self.strip_trailing_slashes),
+            ("method_acl_default", self.method_acl_default),
+            ("host_acl_default", self.host_acl_default),
+            ("port_acl_default", self.port_acl_default),
+            ("ip_acl_default", self.ip_acl_default),
+            ("url_path_acl_default", self.url_path_acl_default),
+            ("header_acl_default", self.header_acl_default),
+            ("scheme_acl_default", self.scheme_acl_default),
+        ]
+    }
+
+    /// Returns whether every host matched by an allowed host rule is matched by a denied host rule.
+    fn is_host_shadowed(&self, host: &str) -> bool {
+        let Some(apex) = host.strip_prefix("*.") else {
+            return self
+                .denied_host_index
+                .find(host, self.wildcard_includes_apex)
+                .is_some();
+        };
+        let subdomains_denied = self.denied_hosts.iter().any(|denied| {
+            denied.strip_prefix("*.").is_some_and(|denied_apex| {
+                apex == denied_apex
+                    || apex
+                        .strip_suffix(denied_apex)
+                        .is_some_and(|subdomain| subdomain.ends_with('.'))
+            })
+        });
+        subdomains_denied
+            && (!self.wildcard_includes_apex
+                || self
+                    .denied_host_index
+                    .find(apex, self.wildcard_includes_apex)
+                    .is_some())
+    }
+
+    /// Returns a human-readable explanation of the decision for an input
+    /// in the given dimension, naming the rule (or default) that governed it.
+    ///
+    /// The decision callback is not called and no decision is traced.
+    pub fn explain(&self, dimension: Dimension, input: &str) -> String {
+        let (classification, rule) = match dimension {
+            Dimension::Scheme => (self.classify_scheme(input), None),
+            Dimension::Method => {
+                let method = HttpRequestMethod::from(input);
+                let classification = self.classify_method(&method);
+                (classification, Some(method.as_str().to_string()))
+            }
+            Dimension::Host => {
+                let classification = self.classify_host(input);
+                let hosts = if classification.is_allowed() {
+                    &self.allowed_host_index
+                } else {
+                    &self.denied_host_index
+                };
+                let rule = hosts.find(input, self.wildcard_includes_apex).cloned();
+                (classification, rule)
+            }
+            Dimension::Port => {
+                let Ok(port) = input.parse::<u16>() else {
+                    return format!("{} {} is invalid.", dimension, input);
+                };
+                let classification = self.classify_port(port);
+                let ranges = if classification.is_allowed() {
+                    &self.allowed_port_ranges
+                } else {
+                    &self.denied_port_ranges
+                };
+                let rule = ranges
+                    .iter()
+                    .find(|range| range.contains(&port))
+                    .map(|range| format!("{}-{}", range.start(), range.end()));
+                (classification, rule)
+            }
+            Dimension::Ip => {
+                let Ok(ip) = input.parse::<IpAddr>() else {
+                    return format!("{} {} is invalid.", dimension, input);
+                };
+                let classification = self.classify_ip(&ip);
+                let ranges = if classification.is_allowed() {
+                    &self.allowed_ip_ranges
+                } else {
+                    &self.denied_ip_ranges
+                };
+                let rule = ranges
+                    .iter()
+                    .find(|range| range.contains(&ip))
+                    .map(|range| format!("{}-{}", range.start(), range.end()));
+                (classification, rule)
+            }
+            Dimension::UrlPath => {
+                let classification = self.classify_url_path(input);
+                let rule = self.matching_url_path(input).map(|(rule, _)| rule);
+                (classification, rule)
+            }
+            Dimension::Query => (
+                self.classify_url_query(input),
+                self.matching_query_substring(input).cloned(),
+            ),
+            Dimension::Request => {
+                return format!(
+                    "{} {} can only be decided by the validate function.",
+                    dimension, input
+                );
+            }
+            Dimension::Compound => {
+                let Some((method, (scheme, host))) = input
+                    .split_once(' ')
+                    .and_then(|(method, url)| Some((method, url.split_once("://")?)))
+                else {
+                    return format!("{} {} is invalid.", dimension, input);
+                };
+                (
+                    self.classify_compound(scheme, &HttpRequestMethod::from(method), host),
+                    None,
+                )
+            }
+            Dimension::Header => {
+                let (name, value) = input.split_once(':').unwrap_or((input, ""));
+                let (name, value) = (name.trim().to_ascii_lowercase(), value.trim());
+                let classification = self.classify_header(&name, value);
+                let headers = if classification.is_allowed() {
+                    &self.allowed_headers
+                } else {
+                    &self.denied_headers
+                };
+                let rule = headers
+                    .get(&name)
+                    .map(|rule| match rule {
+                        Some(value) => format!("{}: {}", name, value),
+                        None => name.clone(),
+                    })
+                    .or_else(|| {
+                        let values = self.denied_header_values.get(&name)?;
+                        (classification.is_denied() && values.iter().any(|v| v == value))
+                            .then(|| format!("{}: {}", name, values.join(" | ")))
+                    })
+                    .or_else(|| {
+                        let matchers = if classification.is_allowed() {
+                            &self.allowed_header_matchers
+                        } else {
+                            &self.denied_header_matchers
+                        };
+                        Self::find_header_matcher(&name, value, matchers)
+                            .map(|matcher| format!("{}: {}", name, matcher))
+                    });
+                (classification, rule)
+            }
+        };
+
+        let (classification, rule) = match self.kill_switch_denial() {
+            Some(denial) => (denial, None),
+            None => (classification, rule),
+        };
+
+        match (classification, rule) {
+            (AclClassification::AllowedUserAcl, Some(rule)) => {
+                format!(
+                    "{} {} is allowed by the {} rule `{}`.",
+                    dimension, input, dimension, rule
+                )
+            }
+            (AclClassification::AllowedUserAcl, None) => {
+                format!("{} {} is allowed by the allowed ACL.", dimension, input)
+            }
+            (AclClassification::DeniedUserAcl, Some(rule)) => {
+                format!(
+                    "{} {} is denied by the {} rule `{}`.",
+                    dimension, input, dimension, rule
+                )
+            }
+            (AclClassification::DeniedUserAcl, None) => {
+                format!("{} {} is denied by the denied ACL.", dimension, input)
+            }
+            (AclClassification::AllowedDefault, _) => format!(
+                "{} {} is allowed by default as no {} rule matched.",
+                dimension, input, dimension
+            ),
+            (AclClassification::DeniedDefault, _) => format!(
+                "{} {} is denied by default as no {} rule matched.",
+                dimension, input, dimension
+            ),
+            (AclClassification::DeniedNotGlobal, _) => {
+                format!("{} {} is denied as it is not global.", dimension, input)
+            }
+            (AclClassification::DeniedPrivateRange, _) => {
+                format!(
+                    "{} {} is denied as it is in a private range.",
+                    dimension, input
+                )
+            }
+            (AclClassification::Denied(reason), _) => {
+                format!("{} {} is denied because {}.", dimension, input, reason)
+            }
+        }
+    }
+
+    /// Returns the URL path rule that matches a URL path,
+    /// along with whether the rule allows or denies it.
+    pub fn matching_url_path(&self, url_path: &str) -> Option<(String, bool)> {
+        let url_path = self.prepare_url_path(url_path);
+        if let Ok(matched) = self.allowed_url_paths_router.at(&url_path) {
+            Some((matched.value.to_string(), true))
+        } else if let Ok(matched) = self.denied_url_paths_router.at(&url_path) {
+            Some((matched.value.to_string(), false))
+        } else {
+            None
+        }
+    }
+
+    /// Evaluates a URI and method against the ACL, returning the classification of each dimension.
+    ///
+    /// Authority-form URIs used by `CONNECT` requests are evaluated without a scheme or URL path.
+    #[cfg(feature = "http")]
+    pub fn evaluate_uri(&self, uri: &http::Uri, method: &http::Method) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        report.push(Dimension::Method, self.is_method_allowed(method));
+
+        let authority_form = method == http::Method::CONNECT
+            && uri.scheme().is_none()
+            && uri.authority().is_some()
+            && uri.path_and_query().is_none();
+
+        if !authority_form {
+            match uri.scheme_str() {
+                Some(scheme) => report.push(Dimension::Scheme, self.is_scheme_allowed(scheme)),
+                None => report.push(
+                    Dimension::Scheme,
+                    AclClassification::Denied("missing scheme".to_string()),
+                ),
+            }
+        }
+
+        match uri.host() {
+            Some(host) => {
+                let scheme = uri.scheme_str().unwrap_or_default();
+                for (dimension, classification) in
+                    self.authority_classifications(scheme, host, uri.port_u16())
+                {
+                    report.push(dimension, classification);
+                }
+            }
+            None => {
+                report.push(
+                    Dimension::Host,
+                    AclClassification::Denied("missing host".to_string()),
+                );
+                report.push(
+                    Dimension::Port,
+                    self.port_classification(uri.scheme_str().unwrap_or_default(), uri.port_u16()),
+                );
+            }
+        }
+
+        if !authority_form {
+            report.push(
+                Dimension::UrlPath,
+                self.is_url_path_allowed_for_method(method, uri.path()),
+            );
+            if let Some(query) = uri.query() {
+                report.push(Dimension::Query, self.is_url_query_allowed(query));
+            }
+        }
+
+        report.push(
+            Dimension::Compound,
+            self.is_compound_allowed(
+                uri.scheme_str().unwrap_or_default(),
+                method.as_str(),
+                uri.host().unwrap_or_default(),
+            ),
+        );
+
+        report
+    }
+
+    /// Evaluates the parts of a request against the ACL, returning the classification of each dimension.
+    ///
+    /// Every dimension is evaluated, so all of the denials of a request can be reported at once.
+    /// The dimensions are evaluated in the order documented on [`HttpAcl::first_denial`].
+    pub fn evaluate(&self, request: &RequestParts) -> ValidationReport {
+        ValidationReport {
+            classifications: self.classifications(request).collect(),
+        }
+    }
+
+    /// Returns the first denied dimension of a request, without evaluating the dimensions after it.
+    ///
+    /// The dimensions are evaluated in a stable order:
+    /// scheme, method, host or IP, port, URL path, query, compound rules, headers and finally
+    /// the validate function. This is the same order the reqwest middleware enforces.
+    pub fn first_denial(&self, request: &RequestParts) -> Option<(Dimension, AclClassification)> {
+        self.classifications(request)
+            .find(|(_, classification)| classification.is_denied())
+    }
+
+    /// Checks a method and URL against the ACL, returning the classification of the first denied dimension.
+    ///
+    /// The dimensions are evaluated in the same order as the reqwest middleware:
+    /// scheme, method, host or IP, port, URL path, query and compound rules.
+    /// Returns [`AclClassification::AllowedDefault`] if no dimension is denied.
+    pub fn check_url(&self, method: &str, url: &str) -> AclClassification {
+        let url = match url::Url::parse(url) {
+            Ok(url) => url,
+            Err(e) => return AclClassification::Denied(format!("invalid URL: {}", e)),
+        };
+        let scheme = url.scheme();
+        let Some(host) = url.host_str() else {
+            return AclClassification::Denied("missing host".to_string());
+        };
+        let path = url.path();
+
+        let denial = std::iter::once_with(|| self.is_scheme_allowed(scheme))
+            .chain(std::iter::once_with(|| self.is_method_allowed(method)))
+            .chain(
+                self.authority_classifications(scheme, host, url.port())
+                    .map(|(_, classification)| classification),
+            )
+            .chain(std::iter::once_with(|| {
+                self.is_url_path_allowed_for_method(method, path)
+            }))
+            .chain(url.query().map(|query| self.is_url_query_allowed(query)))
+            .chain(std::iter::once_with(|| {
+                self.is_compound_allowed(scheme, method, host)
+            }))
+            .find(AclClassification::is_denied);
+        denial.unwrap_or(AclClassification::AllowedDefault)
+    }
+
+    /// Evaluates the requests recorded in a HAR (HTTP Archive) against the ACL.
+    ///
+    /// Returns the `METHOD url` of each recorded request along with its [`ValidationReport`].
+    /// Entries without a valid method or URL are skipped, and an invalid HAR returns no reports.
+    #[cfg(feature = "har")]
+    pub fn evaluate_har(&self, json: &str) -> Vec<(String, ValidationReport)> {
+        let Ok(har) = serde_json::from_str::<serde_json::Value>(json) else {
+            return Vec::new();
+        };
+        let Some(entries) = har["log"]["entries"].as_array() else {
+            return Vec::new();
+        };
+
+        entries
+            .iter()
+            .filter_map(|entry| {
+                let request = &entry["request"];
+                let method = request["method"].as_str()?;
+                let url = url::Url::parse(request["url"].as_str()?).ok()?;
+                let headers = request["headers"]
+                    .as_array()
+                    .map(|headers| {
+                        headers
+                            .iter()
+                            .filter_map(|header| {
+                                Some((header["name"].as_str()?, header["value"].as_str()?))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let report = self.evaluate(&RequestParts {
+                    scheme: url.scheme(),
+                    method,
+                    host: url.host_str().unwrap_or_default(),
+                    port: url.port(),
+                    path: url.path(),
+                    query: url.query(),
+                    headers: &headers,
+                });
+                Some((format!("{} {}", method, url), report))
+            })
+            .collect()
+    }
+
+    /// Returns a lazy iterator over the classification of each dimension of a request.
+    fn classifications<'r>(
+        &'r self,
+        request: &'r RequestParts<'r>,
+    ) -> impl Iterator<Item = (Dimension, AclClassification)> + 'r {
+        std::iter::once_with(|| (Dimension::Scheme, self.is_scheme_allowed(request.scheme)))
+            .chain(std::iter::once_with(|| {
+                (Dimension::Method, self.is_method_allowed(request.method))
+            }))
+            .chain(self.authority_classifications(request.scheme, request.host, request.port))
+            .chain(std::iter::once_with(|| {
+                (
+                    Dimension::UrlPath,
+                    self.is_url_path_allowed_for_method(request.method, request.path),
+                )
+            }))
+            .chain(
+                request
+                    .query
+                    .map(|query| (Dimension::Query, self.is_url_query_allowed(query))),
+            )
+            .chain(std::iter::once_with(|| {
+                (
+                    Dimension::Compound,
+                    self.is_compound_allowed(request.scheme, request.method, request.host),
+                )
+            }))
+            .chain(
+                request
+                    .headers
+                    .iter()
+                    .map(|(name, value)| (Dimension::Header, self.is_header_allowed(name, value))),
+            )
+            .chain(
+                (self.max_header_count.is_some() || self.max_total_header_bytes.is_some()).then(
+                    || {
+                        (
+                            Dimension::Header,
+                            self.are_headers_within_limits(request.headers.iter().copied()),
+                        )
+                    },
+                ),
+            )
+            .chain(self.required_headers.first().into_iter().map(|_| {
+                (
+                    Dimension::Header,
+                    self.are_required_headers_present(request.headers.iter().copied()),
+                )
+            }))
+            .chain(self.has_validate_fn().then_some(()).into_iter().map(|()| {
+                let port = request.port.or(Self::default_port(request.scheme));
+                let authority = Authority {
+                    host: match Self::parse_ip_host(request.host) {
+                        Some(ip) => Host::Ip(ip),
+                        None => Host::Domain(request.host.to_string()),
+                    },
+                    port,
+                };
+                (
+                    Dimension::Request,
+                    self.is_valid(
+                        request.scheme,
+                        &authority,
+                        &HttpRequestMethod::from(request.method),
+                        port.unwrap_or_default(),
+                        Box::new(request.headers.iter().copied()),
+                        None,
+                    ),
+                )
+            }))
+    }
+
+    /// Returns the classification of a host, which is either a domain or an IP address.
+    fn host_classification(&self, host: &str) -> (Dimension, AclClassification) {
+        match Self::parse_ip_host(host) {
+            Some(ip) => (Dimension::Ip, self.is_ip_allowed(&ip)),
+            None => (Dimension::Host, self.is_host_allowed(host)),
+        }
+    }
+
+    /// Parses a host as an IP address, allowing IPv6 addresses in brackets
+    /// and alternate IPv4 encodings.
+    fn parse_ip_host(host: &str) -> Option<IpAddr> {
+        match utils::authority::normalize_host(host) {
+            Some(Host::Ip(ip)) => Some(ip),
+            _ => None,
+        }
+    }
+
+    /// Returns the default port of a scheme.
+    pub(crate) fn default_port(scheme: &str) -> Option<u16> {
+        match scheme {
+            "http" => Some(80),
+            "https" => Some(443),
+            _ => None,
+        }
+    }
+
+    /// Returns the classification of a port, falling back to the default port of the scheme.
+    fn port_classification(&self, scheme: &str, port: Option<u16>) -> AclClassification {
+        match port.or(Self::default_port(scheme)) {
+            Some(port) => self.is_port_allowed(port),
+            None => AclClassification::Denied("missing port".to_string()),
+        }
+    }
+
+    /// Returns whether a request is valid according to the validate function.
+    ///
+    /// The port is the resolved port of the request, as the authority port is `None` when unspecified.
+    ///
+    /// Returns [`AclClassification::AllowedDefault`] if no validate function is set.
+    /// An async validate function can only be called by [`HttpAcl::is_valid_async`],
+    /// so every request is denied if the ACL was built with one.
+    pub fn is_valid<'h>(
+        &self,
+        scheme: &str,
+        authority: &Authority,
+        method: &HttpRequestMethod,
+        port: u16,
+        headers: Box<dyn Iterator<Item = (&'h str, &'h str)> + Send + Sync + 'h>,
+        body: Option<&[u8]>,
+    ) -> AclClassification {
+        if let Some(denial) = self.kill_switch_denial() {
+            return denial;
+        }
+        if let Some(validate_fn) = &self.validate_fn {
+            validate_fn(scheme, authority, method, port, headers, body)
+        } else if self.async_validate_fn.is_some() {
+            AclClassification::Denied("async validate function requires is_valid_async".to_string())
+        } else {
+            AclClassification::AllowedDefault
+        }
+    }
+
+    /// Returns whether a `GET` request without headers or body is valid according to
+    /// the validate function.
+    ///
+    /// The port is the port of the authority, falling back to the default port of the scheme.
+    ///
+    /// Returns [`AclClassification::AllowedDefault`] if no validate function is set.
+    pub fn is_valid_simple(&self, scheme: &str, authority: &Authority) -> AclClassification {
+        let port = authority
+            .port
+            .or(Self::default_port(scheme))
+            .unwrap_or_default();
+        self.is_valid(
+            scheme,
+            authority,
+            &HttpRequestMethod::GET,
+            port,
+            Box::new(std::iter::empty()),
+            None,
+        )
+    }
+
+    /// Returns whether a request is valid according to the async validate function,
+    /// falling back to the validate function if no async validate function is set.
+    ///
+    /// Returns [`AclClassification::AllowedDefault`] if neither is set.
+    pub async fn is_valid_async<'h>(
+        &self,
+        scheme: &str,
+        authority: &Authority,
+        method: &HttpRequestMethod,
+        port: u16,
+        headers: Box<dyn Iterator<Item = (&'h str, &'h str)> + Send + Sync + 'h>,
+        body: Option<&[u8]>,
+    ) -> AclClassification {
+        if let Some(denial) = self.kill_switch_denial() {
+            return denial;
+        }
+        if let Some(async_validate_fn) = &self.async_validate_fn {
+            let headers = headers
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            async_validate_fn(
+                scheme.to_string(),
+                authority.clone(),
+                method.clone(),
+                port,
+                headers,
+                body.map(|body| body.to_vec()),
+            )
+            .await
+        } else {
+            self.is_valid(scheme, authority, method, port, headers, body)
+        }
+    }
+
+    /// Checks if a port is in a list of port ranges.
+    fn is_port_in_ranges(port: u16, ranges: &[RangeInclusive<u16>]) -> bool {
+        ranges.iter().any(|range| range.contains(&port))
+    }
+}
+
+/// Represents a dimension of a request that the ACL makes decisions on.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Dimension {
+    /// The scheme of the request.
+    Scheme,
+    /// The HTTP method of the request.
+    Method,
+    /// The host of the request.
+    Host,
+    /// The port of the request.
+    Port,
+    /// The IP address of the request.
+    Ip,
+    /// The URL path of the request.
+    UrlPath,
+    /// The URL query of the request.
+    Query,
+    /// A header of the request.
+    Header,
+    /// The request as a whole, as decided by the validate function.
+    Request,
+    /// The combination of scheme, method and host, as decided by the compound rules.
+    Compound,
+}
+
+impl Dimension {
+    /// Return the dimension as a `&str`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dimension::Scheme => "scheme",
+            Dimension::Method => "method",
+            Dimension::Host => "host",
+            Dimension::Port => "port",
+            Dimension::Ip => "ip",
+            Dimension::UrlPath => "path",
+            Dimension::Query => "query",
+            Dimension::Header => "header",
+            Dimension::Request => "request",
+            Dimension::Compound => "compound",
+        }
+    }
+}
+
+impl std::fmt::Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Represents an ACL Classification.
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AclClassification {
+    /// The entity is allowed according to the allowed ACL.
+    AllowedUserAcl,
+    /// The entity is allowed because the default is to allow if no ACL match is found.
+    AllowedDefault,
+    /// The entity is denied according to the denied ACL.
+    DeniedUserAcl,
+    /// The entity is denied because the default is to deny if no ACL match is found.
+    DeniedDefault,
+    /// The entity is denied.
+    Denied(String),
+    /// The IP is denied because it is not global.
+    DeniedNotGlobal,
     /// The IP is denied because it is in a private range.
     DeniedPrivateRange,
 }
@@ -305,14 +2629,14 @@ impl std::fmt::Display for AclClassification {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AclClassification::AllowedUserAcl => {
-                write!(f, "The entiy is allowed according to the allowed ACL.")
+                write!(f, "The entity is allowed according to the allowed ACL.")
             }
             AclClassification::AllowedDefault => write!(
                 f,
                 "The entity is allowed because the default is to allow if no ACL match is found."
             ),
             AclClassification::DeniedUserAcl => {
-                write!(f, "The entiy is denied according to the denied ACL.")
+                write!(f, "The entity is denied according to the denied ACL.")
             }
             AclClassification::DeniedNotGlobal => {
                 write!(f, "The ip is denied because it is not global.")
@@ -325,12 +2649,18 @@ impl std::fmt::Display for AclClassification {
                 "The entity is denied because the default is to deny if no ACL match is found."
             ),
             AclClassification::Denied(reason) => {
-                write!(f, "The entiy is denied because {}.", reason)
+                write!(f, "The entity is denied because {}.", reason)
             }
         }
     }
 }
 
+impl From<AuthorityError> for AclClassification {
+    fn from(err: AuthorityError) -> Self {
+        AclClassification::Denied(format!("invalid authority: {}", err))
+    }
+}
+
 impl AclClassification {
     /// Returns whether the classification is allowed.
     pub fn is_allowed(&self) -> bool {
@@ -351,14 +2681,343 @@ impl AclClassification {
                 | AclClassification::DeniedPrivateRange
         )
     }
+
+    /// Returns a short machine-readable code for the classification.
+    ///
+    /// Unlike the [`Display`](std::fmt::Display) text, these codes are stable across releases.
+    pub fn reason_code(&self) -> &'static str {
+        match self {
+            AclClassification::AllowedUserAcl => "allowed_user",
+            AclClassification::AllowedDefault => "allowed_default",
+            AclClassification::DeniedUserAcl => "denied_user",
+            AclClassification::DeniedDefault => "denied_default",
+            AclClassification::Denied(_) => "denied_custom",
+            AclClassification::DeniedNotGlobal => "denied_not_global",
+            AclClassification::DeniedPrivateRange => "denied_private_range",
+        }
+    }
 }
 
-/// Represents an HTTP request method.
+/// Represents a warning about an ACL configuration that is valid but likely a mistake.
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum AclWarning {
+    /// The rule has no effect as it matches the default action of its dimension,
+    /// and there are no opposite rules for it to override.
+    RedundantRule {
+        /// The dimension of the rule.
+        dimension: Dimension,
+        /// The rule.
+        rule: String,
+    },
+}
+
+impl std::fmt::Display for AclWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AclWarning::RedundantRule { dimension, rule } => write!(
+                f,
+                "The {} rule `{}` has no effect as it matches the default action.",
+                dimension, rule
+            ),
+        }
+    }
+}
+
+/// Represents a rule that can never govern a decision, see [`HttpAcl::shadowed_rules`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ShadowedRule {
+    /// The dimension of the rule.
+    pub dimension: Dimension,
+    /// The rule.
+    pub rule: String,
+}
+
+impl std::fmt::Display for ShadowedRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The {} rule `{}` is shadowed by a rule that takes precedence.",
+            self.dimension, self.rule
+        )
+    }
+}
+
+/// Represents an allowed or denied rule of an ACL, see [`HttpAcl::diff`].
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub enum HttpRequestMethod {
-    /// The CONNECT method.
-    CONNECT,
+pub struct AclRule {
+    /// The dimension of the rule.
+    pub dimension: Dimension,
+    /// Whether the rule allows or denies.
+    pub allowed: bool,
+    /// The rule.
+    pub rule: String,
+}
+
+impl std::fmt::Display for AclRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = if self.allowed { "allowed" } else { "denied" };
+        write!(f, "{} {} `{}`", action, self.dimension, self.rule)
+    }
+}
+
+/// Represents the number of allowed and denied rules of each dimension, see [`HttpAcl::rule_counts`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RuleCounts {
+    /// The number of allowed methods.
+    pub allowed_methods: usize,
+    /// The number of denied methods.
+    pub denied_methods: usize,
+    /// The number of allowed hosts.
+    pub allowed_hosts: usize,
+    /// The number of denied hosts.
+    pub denied_hosts: usize,
+    /// The number of allowed port ranges.
+    pub allowed_port_ranges: usize,
+    /// The number of denied port ranges.
+    pub denied_port_ranges: usize,
+    /// The number of allowed IP ranges.
+    pub allowed_ip_ranges: usize,
+    /// The number of denied IP ranges.
+    pub denied_ip_ranges: usize,
+    /// The number of allowed headers.
+    pub allowed_headers: usize,
+    /// The number of denied headers.
+    pub denied_headers: usize,
+    /// The number of allowed URL paths.
+    pub allowed_url_paths: usize,
+    /// The number of denied URL paths.
+    pub denied_url_paths: usize,
+}
+
+/// Represents the differences between two ACLs, see [`HttpAcl::diff`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AclDiff {
+    /// The rules of the other ACL missing from this ACL.
+    pub added: Vec<AclRule>,
+    /// The rules of this ACL missing from the other ACL.
+    pub removed: Vec<AclRule>,
+    /// The boolean flags with a different value, along with their value in this ACL and in the other ACL.
+    pub changed_flags: Vec<(&'static str, bool, bool)>,
+    /// The other settings with a different value, along with their value in this ACL and in the other ACL.
+    pub changed_settings: Vec<(&'static str, String, String)>,
+}
+
+impl AclDiff {
+    /// Returns whether there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed_flags.is_empty()
+            && self.changed_settings.is_empty()
+    }
+}
+
+/// Represents a rule that allows a scheme, method and host only in combination.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CompoundRule {
+    /// The scheme of the rule.
+    pub scheme: String,
+    /// The HTTP method of the rule.
+    pub method: HttpRequestMethod,
+    /// The host of the rule, a host prefixed with `*.` is a wildcard matching any subdomain.
+    pub host: String,
+}
+
+impl CompoundRule {
+    /// Create a new [`CompoundRule`].
+    pub fn new(
+        scheme: impl AsRef<str>,
+        method: impl Into<HttpRequestMethod>,
+        host: impl AsRef<str>,
+    ) -> Self {
+        Self {
+            scheme: scheme.as_ref().to_string(),
+            method: method.into(),
+            host: host.as_ref().to_string(),
+        }
+    }
+}
+
+/// Matches the value of a header.
+///
+/// Values are compared case-sensitively.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HeaderMatcher {
+    /// Matches any value.
+    Any,
+    /// Matches a value exactly.
+    Exact(String),
+    /// Matches a value starting with a prefix.
+    Prefix(String),
+    /// Matches a value ending with a suffix.
+    Suffix(String),
+    /// Matches a value containing a substring.
+    Contains(String),
+}
+
+impl HeaderMatcher {
+    /// Returns whether a header value matches.
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(exact) => value == exact,
+            Self::Prefix(prefix) => value.starts_with(prefix.as_str()),
+            Self::Suffix(suffix) => value.ends_with(suffix.as_str()),
+            Self::Contains(substring) => value.contains(substring.as_str()),
+        }
+    }
+}
+
+impl std::fmt::Display for HeaderMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => write!(f, "*"),
+            Self::Exact(exact) => write!(f, "{}", exact),
+            Self::Prefix(prefix) => write!(f, "{}*", prefix),
+            Self::Suffix(suffix) => write!(f, "*{}", suffix),
+            Self::Contains(substring) => write!(f, "*{}*", substring),
+        }
+    }
+}
+
+/// A set of declarative header rules, added to an ACL with [`HttpAclBuilder::header_policy`].
+///
+/// Header names are compared case-insensitively and the rules are indexed by name,
+/// so most header checks do not need a validate function.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HeaderPolicy {
+    allowed: Vec<(String, HeaderMatcher)>,
+    denied: Vec<(String, HeaderMatcher)>,
+}
+
+impl HeaderPolicy {
+    /// Create a new empty [`HeaderPolicy`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows a header whose value matches the matcher.
+    pub fn allow(mut self, header: impl AsRef<str>, matcher: HeaderMatcher) -> Self {
+        self.allowed
+            .push((header.as_ref().to_ascii_lowercase(), matcher));
+        self
+    }
+
+    /// Denies a header whose value matches the matcher.
+    pub fn deny(mut self, header: impl AsRef<str>, matcher: HeaderMatcher) -> Self {
+        self.denied
+            .push((header.as_ref().to_ascii_lowercase(), matcher));
+        self
+    }
+}
+
+/// Represents the parts of a request that are evaluated by [`HttpAcl::evaluate`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RequestParts<'a> {
+    /// The scheme of the request.
+    pub scheme: &'a str,
+    /// The HTTP method of the request.
+    pub method: &'a str,
+    /// The host of the request, either a domain or an IP address.
+    pub host: &'a str,
+    /// The port of the request, defaults to the port of the scheme if not set.
+    pub port: Option<u16>,
+    /// The URL path of the request.
+    pub path: &'a str,
+    /// The URL query of the request, without the leading `?`.
+    pub query: Option<&'a str>,
+    /// The headers of the request.
+    pub headers: &'a [(&'a str, &'a str)],
+}
+
+/// Represents the classification of each dimension of a request.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidationReport {
+    classifications: Vec<(Dimension, AclClassification)>,
+}
+
+impl ValidationReport {
+    /// Adds the classification of a dimension to the report.
+    pub fn push(&mut self, dimension: Dimension, classification: AclClassification) {
+        self.classifications.push((dimension, classification));
+    }
+
+    /// Returns the classification of a dimension, if it was evaluated.
+    pub fn get(&self, dimension: Dimension) -> Option<&AclClassification> {
+        self.classifications
+            .iter()
+            .find(|(d, _)| *d == dimension)
+            .map(|(_, classification)| classification)
+    }
+
+    /// Returns an iterator over the classification of each evaluated dimension.
+    pub fn iter(&self) -> impl Iterator<Item = &(Dimension, AclClassification)> {
+        self.classifications.iter()
+    }
+
+    /// Returns an iterator over the denied dimensions.
+    pub fn denials(&self) -> impl Iterator<Item = &(Dimension, AclClassification)> {
+        self.iter()
+            .filter(|(_, classification)| classification.is_denied())
+    }
+
+    /// Returns whether every evaluated dimension is allowed.
+    pub fn is_allowed(&self) -> bool {
+        self.denials().next().is_none()
+    }
+
+    /// Returns whether any evaluated dimension is denied.
+    pub fn is_denied(&self) -> bool {
+        !self.is_allowed()
+    }
+
+    /// Returns a description of the combined decision, naming the dimension that governed it
+    /// along with the reason code of every evaluated dimension.
+    ///
+    /// The first denied dimension governs a denial, while an allow requires every dimension to be allowed.
+    pub fn combined_reason(&self) -> String {
+        let reasons = self
+            .iter()
+            .map(|(dimension, classification)| {
+                format!("{}: {}", dimension, classification.reason_code())
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.denials().next() {
+            Some((dimension, _)) => {
+                format!(
+                    "The {} decision governed the denial ({}).",
+                    dimension, reasons
+                )
+            }
+            None => format!("Every decision allowed the request ({}).", reasons),
+        }
+    }
+}
+
+impl IntoIterator for ValidationReport {
+    type Item = (Dimension, AclClassification);
+    type IntoIter = std::vec::IntoIter<(Dimension, AclClassification)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.classifications.into_iter()
+    }
+}
+
+/// Represents an HTTP request method.
+///
+/// [`HttpRequestMethod::OTHER`] methods are compared case-insensitively.
+///
+/// With the `serde` feature, a method is serialized as its string, so it can be used as a map key.
+/// The `{"OTHER": "..."}` form written by earlier versions for non-standard methods is still
+/// accepted when deserializing.
+#[derive(Clone, Debug)]
+pub enum HttpRequestMethod {
+    /// The CONNECT method.
+    CONNECT,
     /// The DELETE method.
     DELETE,
     /// The GET method.
@@ -380,18 +3039,41 @@ pub enum HttpRequestMethod {
 }
 
 impl From<&str> for HttpRequestMethod {
+    /// Converts a method, comparing it case-insensitively to the standard methods.
+    ///
+    /// Any other method keeps its original casing in [`HttpRequestMethod::OTHER`].
     fn from(method: &str) -> Self {
-        match method {
-            "CONNECT" => HttpRequestMethod::CONNECT,
-            "DELETE" => HttpRequestMethod::DELETE,
-            "GET" => HttpRequestMethod::GET,
-            "HEAD" => HttpRequestMethod::HEAD,
-            "OPTIONS" => HttpRequestMethod::OPTIONS,
-            "PATCH" => HttpRequestMethod::PATCH,
-            "POST" => HttpRequestMethod::POST,
-            "PUT" => HttpRequestMethod::PUT,
-            "TRACE" => HttpRequestMethod::TRACE,
-            _ => HttpRequestMethod::OTHER(method.to_string()),
+        [
+            HttpRequestMethod::CONNECT,
+            HttpRequestMethod::DELETE,
+            HttpRequestMethod::GET,
+            HttpRequestMethod::HEAD,
+            HttpRequestMethod::OPTIONS,
+            HttpRequestMethod::PATCH,
+            HttpRequestMethod::POST,
+            HttpRequestMethod::PUT,
+            HttpRequestMethod::TRACE,
+        ]
+        .into_iter()
+        .find(|standard| standard.as_str().eq_ignore_ascii_case(method))
+        .unwrap_or_else(|| HttpRequestMethod::OTHER(method.to_string()))
+    }
+}
+
+#[cfg(feature = "http")]
+impl From<&http::Method> for HttpRequestMethod {
+    fn from(method: &http::Method) -> Self {
+        match *method {
+            http::Method::CONNECT => HttpRequestMethod::CONNECT,
+            http::Method::DELETE => HttpRequestMethod::DELETE,
+            http::Method::GET => HttpRequestMethod::GET,
+            http::Method::HEAD => HttpRequestMethod::HEAD,
+            http::Method::OPTIONS => HttpRequestMethod::OPTIONS,
+            http::Method::PATCH => HttpRequestMethod::PATCH,
+            http::Method::POST => HttpRequestMethod::POST,
+            http::Method::PUT => HttpRequestMethod::PUT,
+            http::Method::TRACE => HttpRequestMethod::TRACE,
+            _ => HttpRequestMethod::OTHER(method.as_str().to_string()),
         }
     }
 }
@@ -412,11 +3094,144 @@ impl HttpRequestMethod {
             HttpRequestMethod::OTHER(other) => other,
         }
     }
+
+    /// Returns the position of the method in the enum, used for ordering.
+    pub(crate) fn index(&self) -> u8 {
+        match self {
+            HttpRequestMethod::CONNECT => 0,
+            HttpRequestMethod::DELETE => 1,
+            HttpRequestMethod::GET => 2,
+            HttpRequestMethod::HEAD => 3,
+            HttpRequestMethod::OPTIONS => 4,
+            HttpRequestMethod::PATCH => 5,
+            HttpRequestMethod::POST => 6,
+            HttpRequestMethod::PUT => 7,
+            HttpRequestMethod::TRACE => 8,
+            HttpRequestMethod::OTHER(_) => 9,
+        }
+    }
+}
+
+impl PartialEq for HttpRequestMethod {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (HttpRequestMethod::OTHER(a), HttpRequestMethod::OTHER(b)) => a.eq_ignore_ascii_case(b),
+            _ => self.index() == other.index(),
+        }
+    }
+}
+
+impl Eq for HttpRequestMethod {}
+
+impl Hash for HttpRequestMethod {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index().hash(state);
+        if let HttpRequestMethod::OTHER(other) = self {
+            for byte in other.bytes() {
+                byte.to_ascii_uppercase().hash(state);
+            }
+        }
+    }
+}
+
+impl PartialOrd for HttpRequestMethod {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HttpRequestMethod {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self, other) {
+            (HttpRequestMethod::OTHER(a), HttpRequestMethod::OTHER(b)) => a
+                .bytes()
+                .map(|byte| byte.to_ascii_uppercase())
+                .cmp(b.bytes().map(|byte| byte.to_ascii_uppercase())),
+            _ => self.index().cmp(&other.index()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for HttpRequestMethod {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for HttpRequestMethod {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(HttpRequestMethodVisitor)
+    }
+}
+
+/// Deserializes a method from its string, or from the `{"OTHER": "..."}` map
+/// written by earlier versions.
+#[cfg(feature = "serde")]
+struct HttpRequestMethodVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for HttpRequestMethodVisitor {
+    type Value = HttpRequestMethod;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an HTTP method string or an {\"OTHER\": string} map")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, method: &str) -> Result<Self::Value, E> {
+        Ok(HttpRequestMethod::from(method))
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let Some(key) = map.next_key::<String>()? else {
+            return Err(serde::de::Error::invalid_length(0, &self));
+        };
+        if key != "OTHER" {
+            return Err(serde::de::Error::unknown_variant(&key, &["OTHER"]));
+        }
+        let method = map.next_value::<String>()?;
+        if map.next_key::<String>()?.is_some() {
+            return Err(serde::de::Error::invalid_length(2, &self));
+        }
+        Ok(HttpRequestMethod::OTHER(method))
+    }
+}
+
+/// Represents the IP versions an [`HttpAcl`] allows.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IpFamilyMode {
+    /// Only IPv4 addresses are allowed.
+    V4Only,
+    /// Only IPv6 addresses are allowed.
+    V6Only,
+    /// Both IPv4 and IPv6 addresses are allowed.
+    #[default]
+    DualStack,
+}
+
+/// Represents which rule wins when both an allowed and a denied rule match.
+///
+/// This currently applies to headers and IP ranges, the other dimensions keep their fixed order.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Precedence {
+    /// The allowed rule wins.
+    AllowWins,
+    /// The denied rule wins.
+    #[default]
+    DenyWins,
 }
 
 /// A builder for [`HttpAcl`].
-#[derive(Default, Clone)]
+///
+/// The URL path routers are not serialized, they are rebuilt from the URL paths when
+/// the [`HttpAcl`] is built. Use [`HttpAclBuilder::try_build`] to also validate the rules
+/// of a deserialized builder. Missing fields take the defaults of [`HttpAclBuilder::new`].
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct HttpAclBuilder {
     allow_http: bool,
     allow_https: bool,
@@ -424,72 +3239,201 @@ pub struct HttpAclBuilder {
     denied_methods: Vec<HttpRequestMethod>,
     allowed_hosts: Vec<String>,
     denied_hosts: Vec<String>,
+    allowed_authorities: Vec<String>,
+    denied_authorities: Vec<String>,
+    #[cfg(feature = "regex")]
+    allowed_host_regexes: Vec<String>,
+    #[cfg(feature = "regex")]
+    denied_host_regexes: Vec<String>,
     allowed_port_ranges: Vec<RangeInclusive<u16>>,
     denied_port_ranges: Vec<RangeInclusive<u16>>,
     allowed_ip_ranges: Vec<RangeInclusive<IpAddr>>,
     denied_ip_ranges: Vec<RangeInclusive<IpAddr>>,
-    static_dns_mapping: HashMap<String, SocketAddr>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    expiring_denied_ip_ranges: Vec<(RangeInclusive<IpAddr>, Instant)>,
+    static_dns_mapping: HashMap<String, Vec<SocketAddr>>,
+    static_mappings_authoritative: bool,
+    kill_switch: bool,
+    allowed_headers: HashMap<String, Option<String>>,
+    denied_headers: HashMap<String, Option<String>>,
+    denied_header_values: HashMap<String, Vec<String>>,
+    allowed_header_matchers: HashMap<String, Vec<HeaderMatcher>>,
+    denied_header_matchers: HashMap<String, Vec<HeaderMatcher>>,
+    required_headers: Vec<String>,
+    max_header_count: Option<usize>,
+    max_total_header_bytes: Option<usize>,
+    allowed_rules: Vec<CompoundRule>,
+    denied_query_substrings: Vec<String>,
+    max_query_length: Option<usize>,
     allowed_url_paths: Vec<String>,
     #[cfg_attr(feature = "serde", serde(skip))]
-    allowed_url_paths_router: Router<()>,
+    allowed_url_paths_router: Router<Box<str>>,
     denied_url_paths: Vec<String>,
     #[cfg_attr(feature = "serde", serde(skip))]
-    denied_url_paths_router: Router<()>,
+    denied_url_paths_router: Router<Box<str>>,
+    allowed_method_url_paths: HashMap<HttpRequestMethod, Vec<String>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    allowed_method_url_paths_routers: HashMap<HttpRequestMethod, Router<Box<str>>>,
+    denied_method_url_paths: HashMap<HttpRequestMethod, Vec<String>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    denied_method_url_paths_routers: HashMap<HttpRequestMethod, Router<Box<str>>>,
+    decode_url_paths: bool,
+    normalize_url_paths: bool,
+    strip_trailing_slashes: bool,
     allow_private_ip_ranges: bool,
+    allow_loopback_ip_addresses: bool,
+    allow_link_local_ip_addresses: bool,
+    allow_unspecified_ip_addresses: bool,
+    ip_family_mode: IpFamilyMode,
+    wildcard_includes_apex: bool,
+    precedence: Precedence,
     method_acl_default: bool,
     host_acl_default: bool,
     port_acl_default: bool,
     ip_acl_default: bool,
     url_path_acl_default: bool,
+    header_acl_default: bool,
+    scheme_acl_default: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    on_decision: Option<DecisionFn>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    clock: Option<ClockFn>,
 }
 
 impl std::fmt::Debug for HttpAclBuilder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("HttpAclBuilder")
+        let mut debug = f.debug_struct("HttpAclBuilder");
+        debug
             .field("allow_http", &self.allow_http)
             .field("allow_https", &self.allow_https)
             .field("allowed_methods", &self.allowed_methods)
             .field("denied_methods", &self.denied_methods)
             .field("allowed_hosts", &self.allowed_hosts)
             .field("denied_hosts", &self.denied_hosts)
+            .field("allowed_authorities", &self.allowed_authorities)
+            .field("denied_authorities", &self.denied_authorities)
             .field("allowed_port_ranges", &self.allowed_port_ranges)
             .field("denied_port_ranges", &self.denied_port_ranges)
             .field("allowed_ip_ranges", &self.allowed_ip_ranges)
             .field("denied_ip_ranges", &self.denied_ip_ranges)
+            .field("expiring_denied_ip_ranges", &self.expiring_denied_ip_ranges)
             .field("static_dns_mapping", &self.static_dns_mapping)
+            .field(
+                "static_mappings_authoritative",
+                &self.static_mappings_authoritative,
+            )
+            .field("kill_switch", &self.kill_switch)
+            .field("allowed_headers", &self.allowed_headers)
+            .field("denied_headers", &self.denied_headers)
+            .field("denied_header_values", &self.denied_header_values)
+            .field("allowed_header_matchers", &self.allowed_header_matchers)
+            .field("denied_header_matchers", &self.denied_header_matchers)
+            .field("required_headers", &self.required_headers)
+            .field("max_header_count", &self.max_header_count)
+            .field("max_total_header_bytes", &self.max_total_header_bytes)
+            .field("allowed_rules", &self.allowed_rules)
+            .field("denied_query_substrings", &self.denied_query_substrings)
+            .field("max_query_length", &self.max_query_length)
             .field("allowed_url_paths", &self.allowed_url_paths)
             .field("denied_url_paths", &self.denied_url_paths)
+            .field("allowed_method_url_paths", &self.allowed_method_url_paths)
+            .field("denied_method_url_paths", &self.denied_method_url_paths)
+            .field("decode_url_paths", &self.decode_url_paths)
+            .field("normalize_url_paths", &self.normalize_url_paths)
+            .field("strip_trailing_slashes", &self.strip_trailing_slashes)
             .field("allow_private_ip_ranges", &self.allow_private_ip_ranges)
+            .field(
+                "allow_loopback_ip_addresses",
+                &self.allow_loopback_ip_addresses,
+            )
+            .field(
+                "allow_link_local_ip_addresses",
+                &self.allow_link_local_ip_addresses,
+            )
+            .field(
+                "allow_unspecified_ip_addresses",
+                &self.allow_unspecified_ip_addresses,
+            )
+            .field("ip_family_mode", &self.ip_family_mode)
+            .field("wildcard_includes_apex", &self.wildcard_includes_apex)
+            .field("precedence", &self.precedence)
             .field("method_acl_default", &self.method_acl_default)
             .field("host_acl_default", &self.host_acl_default)
             .field("port_acl_default", &self.port_acl_default)
             .field("ip_acl_default", &self.ip_acl_default)
             .field("url_path_acl_default", &self.url_path_acl_default)
-            .finish()
+            .field("header_acl_default", &self.header_acl_default)
+            .field("scheme_acl_default", &self.scheme_acl_default);
+        #[cfg(feature = "regex")]
+        debug
+            .field("allowed_host_regexes", &self.allowed_host_regexes)
+            .field("denied_host_regexes", &self.denied_host_regexes);
+        debug.finish()
     }
 }
 
 impl PartialEq for HttpAclBuilder {
     fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "regex")]
+        if self.allowed_host_regexes != other.allowed_host_regexes
+            || self.denied_host_regexes != other.denied_host_regexes
+        {
+            return false;
+        }
         self.allow_http == other.allow_http
             && self.allow_https == other.allow_https
             && self.allowed_methods == other.allowed_methods
             && self.denied_methods == other.denied_methods
             && self.allowed_hosts == other.allowed_hosts
             && self.denied_hosts == other.denied_hosts
+            && self.allowed_authorities == other.allowed_authorities
+            && self.denied_authorities == other.denied_authorities
             && self.allowed_port_ranges == other.allowed_port_ranges
             && self.denied_port_ranges == other.denied_port_ranges
             && self.allowed_ip_ranges == other.allowed_ip_ranges
             && self.denied_ip_ranges == other.denied_ip_ranges
+            && self.expiring_denied_ip_ranges == other.expiring_denied_ip_ranges
             && self.static_dns_mapping == other.static_dns_mapping
+            && self.static_mappings_authoritative == other.static_mappings_authoritative
+            && self.kill_switch == other.kill_switch
+            && self.allowed_headers == other.allowed_headers
+            && self.denied_headers == other.denied_headers
+            && self.denied_header_values == other.denied_header_values
+            && self.allowed_header_matchers == other.allowed_header_matchers
+            && self.denied_header_matchers == other.denied_header_matchers
+            && self.required_headers == other.required_headers
+            && self.max_header_count == other.max_header_count
+            && self.max_total_header_bytes == other.max_total_header_bytes
+            && self.allowed_rules == other.allowed_rules
+            && self.denied_query_substrings == other.denied_query_substrings
+            && self.max_query_length == other.max_query_length
             && self.allowed_url_paths == other.allowed_url_paths
             && self.denied_url_paths == other.denied_url_paths
+            && self.allowed_method_url_paths == other.allowed_method_url_paths
+            && self.denied_method_url_paths == other.denied_method_url_paths
+            && self.decode_url_paths == other.decode_url_paths
+            && self.normalize_url_paths == other.normalize_url_paths
+            && self.strip_trailing_slashes == other.strip_trailing_slashes
             && self.allow_private_ip_ranges == other.allow_private_ip_ranges
+            && self.allow_loopback_ip_addresses == other.allow_loopback_ip_addresses
+            && self.allow_link_local_ip_addresses == other.allow_link_local_ip_addresses
+            && self.allow_unspecified_ip_addresses == other.allow_unspecified_ip_addresses
+            && self.ip_family_mode == other.ip_family_mode
+            && self.wildcard_includes_apex == other.wildcard_includes_apex
+            && self.precedence == other.precedence
             && self.method_acl_default == other.method_acl_default
             && self.host_acl_default == other.host_acl_default
             && self.port_acl_default == other.port_acl_default
             && self.ip_acl_default == other.ip_acl_default
             && self.url_path_acl_default == other.url_path_acl_default
+            && self.header_acl_default == other.header_acl_default
+            && self.scheme_acl_default == other.scheme_acl_default
+    }
+}
+
+impl Default for HttpAclBuilder {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -513,21 +3457,234 @@ impl HttpAclBuilder {
             denied_methods: Vec::new(),
             allowed_hosts: Vec::new(),
             denied_hosts: Vec::new(),
+            allowed_authorities: Vec::new(),
+            denied_authorities: Vec::new(),
+            #[cfg(feature = "regex")]
+            allowed_host_regexes: Vec::new(),
+            #[cfg(feature = "regex")]
+            denied_host_regexes: Vec::new(),
             allowed_port_ranges: vec![80..=80, 443..=443],
             denied_port_ranges: Vec::new(),
             allowed_ip_ranges: Vec::new(),
             denied_ip_ranges: Vec::new(),
+            expiring_denied_ip_ranges: Vec::new(),
             allowed_url_paths: Vec::new(),
             allowed_url_paths_router: Router::new(),
             denied_url_paths: Vec::new(),
             denied_url_paths_router: Router::new(),
+            allowed_method_url_paths: HashMap::new(),
+            allowed_method_url_paths_routers: HashMap::new(),
+            denied_method_url_paths: HashMap::new(),
+            denied_method_url_paths_routers: HashMap::new(),
+            decode_url_paths: true,
+            normalize_url_paths: true,
+            strip_trailing_slashes: true,
             allow_private_ip_ranges: false,
+            allow_loopback_ip_addresses: false,
+            allow_link_local_ip_addresses: false,
+            allow_unspecified_ip_addresses: false,
+            ip_family_mode: IpFamilyMode::DualStack,
+            wildcard_includes_apex: false,
+            precedence: Precedence::DenyWins,
             static_dns_mapping: HashMap::new(),
+            static_mappings_authoritative: false,
+            kill_switch: false,
+            allowed_headers: HashMap::new(),
+            denied_headers: HashMap::new(),
+            denied_header_values: HashMap::new(),
+            allowed_header_matchers: HashMap::new(),
+            denied_header_matchers: HashMap::new(),
+            required_headers: Vec::new(),
+            max_header_count: None,
+            max_total_header_bytes: None,
+            allowed_rules: Vec::new(),
+            denied_query_substrings: Vec::new(),
+            max_query_length: None,
             method_acl_default: false,
             host_acl_default: false,
             port_acl_default: false,
             ip_acl_default: false,
             url_path_acl_default: true,
+            header_acl_default: true,
+            scheme_acl_default: false,
+            on_decision: None,
+            clock: None,
+        }
+    }
+
+    /// Create a new [`HttpAclBuilder`] that denies everything.
+    ///
+    /// Unlike [`HttpAclBuilder::new`], no methods, schemes or ports are allowed
+    /// and every default denies, so only the rules added afterwards are allowed.
+    pub fn deny_all() -> Self {
+        Self {
+            allow_http: false,
+            allow_https: false,
+            allowed_methods: Vec::new(),
+            allowed_port_ranges: Vec::new(),
+            method_acl_default: false,
+            host_acl_default: false,
+            port_acl_default: false,
+            ip_acl_default: false,
+            url_path_acl_default: false,
+            header_acl_default: false,
+            scheme_acl_default: false,
+            ..Self::new()
+        }
+    }
+
+    /// Resets every rule, flag and default of the builder to those of [`HttpAclBuilder::new`].
+    pub fn clear_all(self) -> Self {
+        Self::new()
+    }
+
+    /// Create a new [`HttpAclBuilder`] from environment variables named `{prefix}_{NAME}`.
+    ///
+    /// List variables are comma-separated and replace the default entries of their list:
+    ///
+    /// - `ALLOWED_HOSTS` and `DENIED_HOSTS`: hosts, such as `example.com`.
+    /// - `ALLOWED_IP_RANGES` and `DENIED_IP_RANGES`: IP addresses or CIDR networks.
+    /// - `ALLOWED_PORTS` and `DENIED_PORTS`: ports or port ranges, such as `8000-8999`.
+    /// - `ALLOWED_METHODS` and `DENIED_METHODS`: HTTP methods.
+    /// - `ALLOWED_URL_PATHS` and `DENIED_URL_PATHS`: URL paths.
+    ///
+    /// Without `ALLOWED_METHODS` or `ALLOWED_PORTS`, a denied method or port range is removed
+    /// from the default allowed methods or port ranges instead of conflicting with them.
+    ///
+    /// Boolean variables are `true` or `false`: `HTTP`, `HTTPS`, `METHOD_ACL_DEFAULT`,
+    /// `HOST_ACL_DEFAULT`, `PORT_ACL_DEFAULT`, `IP_ACL_DEFAULT`, `URL_PATH_ACL_DEFAULT`,
+    /// `HEADER_ACL_DEFAULT` and `SCHEME_ACL_DEFAULT`.
+    ///
+    /// Unset variables keep the defaults of [`HttpAclBuilder::new`]. An entry that cannot be
+    /// parsed returns [`AddError::InvalidEntity`].
+    pub fn from_env(prefix: &str) -> Result<Self, AddError> {
+        let mut builder = Self::new();
+
+        for (name, setter) in [
+            ("HTTP", Self::http as fn(Self, bool) -> Self),
+            ("HTTPS", Self::https),
+            ("METHOD_ACL_DEFAULT", Self::method_acl_default),
+            ("HOST_ACL_DEFAULT", Self::host_acl_default),
+            ("PORT_ACL_DEFAULT", Self::port_acl_default),
+            ("IP_ACL_DEFAULT", Self::ip_acl_default),
+            ("URL_PATH_ACL_DEFAULT", Self::url_path_acl_default),
+            ("HEADER_ACL_DEFAULT", Self::header_acl_default),
+            ("SCHEME_ACL_DEFAULT", Self::scheme_acl_default),
+        ] {
+            if let Some(value) = Self::env_var(prefix, name)? {
+                let value = value
+                    .trim()
+                    .parse::<bool>()
+                    .map_err(|_| AddError::InvalidEntity(value.clone()))?;
+                builder = setter(builder, value);
+            }
+        }
+
+        let allowed_methods = Self::env_list(prefix, "ALLOWED_METHODS")?;
+        let default_allowed_methods = allowed_methods.is_none();
+        if let Some(methods) = allowed_methods {
+            builder = builder.clear_allowed_methods();
+            for method in methods {
+                builder = builder.add_allowed_method(method.as_str())?;
+            }
+        }
+        if let Some(methods) = Self::env_list(prefix, "DENIED_METHODS")? {
+            for method in methods {
+                if default_allowed_methods {
+                    builder = builder.remove_allowed_method(method.as_str());
+                }
+                builder = builder.add_denied_method(method.as_str())?;
+            }
+        }
+
+        if let Some(hosts) = Self::env_list(prefix, "ALLOWED_HOSTS")? {
+            builder = builder.extend_allowed_hosts(hosts)?;
+        }
+        if let Some(hosts) = Self::env_list(prefix, "DENIED_HOSTS")? {
+            builder = builder.extend_denied_hosts(hosts)?;
+        }
+
+        if let Some(ip_ranges) = Self::env_list(prefix, "ALLOWED_IP_RANGES")? {
+            for ip_range in ip_ranges {
+                let parsed = Self::parse_ip_range_entry(&ip_range)
+                    .ok_or(AddError::InvalidEntity(ip_range))?;
+                builder = builder.add_allowed_ip_range(parsed)?;
+            }
+        }
+        if let Some(ip_ranges) = Self::env_list(prefix, "DENIED_IP_RANGES")? {
+            for ip_range in ip_ranges {
+                let parsed = Self::parse_ip_range_entry(&ip_range)
+                    .ok_or(AddError::InvalidEntity(ip_range))?;
+                builder = builder.add_denied_ip_range(parsed)?;
+            }
+        }
+
+        let allowed_port_ranges = Self::env_list(prefix, "ALLOWED_PORTS")?;
+        let default_allowed_port_ranges = allowed_port_ranges.is_none();
+        if let Some(port_ranges) = allowed_port_ranges {
+            builder = builder.clear_allowed_port_ranges();
+            for port_range in port_ranges {
+                let parsed = Self::parse_port_range_entry(&port_range)
+                    .ok_or(AddError::InvalidEntity(port_range))?;
+                builder = builder.add_allowed_port_range(parsed)?;
+            }
+        }
+        if let Some(port_ranges) = Self::env_list(prefix, "DENIED_PORTS")? {
+            for port_range in port_ranges {
+                let parsed = Self::parse_port_range_entry(&port_range)
+                    .ok_or(AddError::InvalidEntity(port_range))?;
+                if default_allowed_port_ranges {
+                    builder = builder.remove_allowed_port_range(parsed.clone());
+                }
+                builder = builder.add_denied_port_range(parsed)?;
+            }
+        }
+
+        if let Some(url_paths) = Self::env_list(prefix, "ALLOWED_URL_PATHS")? {
+            for url_path in url_paths {
+                builder = builder.add_allowed_url_path(url_path)?;
+            }
+        }
+        if let Some(url_paths) = Self::env_list(prefix, "DENIED_URL_PATHS")? {
+            for url_path in url_paths {
+                builder = builder.add_denied_url_path(url_path)?;
+            }
+        }
+
+        Ok(builder)
+    }
+
+    /// Reads the environment variable `{prefix}_{name}`, if it is set.
+    fn env_var(prefix: &str, name: &str) -> Result<Option<String>, AddError> {
+        let key = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}_{}", prefix.trim_end_matches('_'), name)
+        };
+        match std::env::var(&key) {
+            Ok(value) => Ok(Some(value)),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(std::env::VarError::NotUnicode(_)) => Err(AddError::InvalidEntity(key)),
+        }
+    }
+
+    /// Reads the comma-separated entries of the environment variable `{prefix}_{name}`, if it is set.
+    fn env_list(prefix: &str, name: &str) -> Result<Option<Vec<String>>, AddError> {
+        Ok(Self::env_var(prefix, name)?.map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(str::to_string)
+                .collect()
+        }))
+    }
+
+    /// Parses a port or a port range entry such as `8000-8999`.
+    fn parse_port_range_entry(entry: &str) -> Option<RangeInclusive<u16>> {
+        match entry.split_once('-') {
+            Some((start, end)) => Some(start.trim().parse().ok()?..=end.trim().parse().ok()?),
+            None => entry.parse().ok().map(|port| port..=port),
         }
     }
 
@@ -543,223 +3700,963 @@ impl HttpAclBuilder {
         self
     }
 
-    /// Sets whether private IP ranges are allowed.
-    pub fn private_ip_ranges(mut self, allow: bool) -> Self {
-        self.allow_private_ip_ranges = allow;
+    /// Sets whether private IP ranges are allowed.
+    pub fn private_ip_ranges(mut self, allow: bool) -> Self {
+        self.allow_private_ip_ranges = allow;
+        self
+    }
+
+    /// Sets whether loopback IP addresses (`127.0.0.0/8` and `::1`) are allowed.
+    pub fn loopback_ip_addresses(mut self, allow: bool) -> Self {
+        self.allow_loopback_ip_addresses = allow;
+        self
+    }
+
+    /// Sets whether link-local IP addresses (`169.254.0.0/16` and `fe80::/10`) are allowed.
+    pub fn link_local_ip_addresses(mut self, allow: bool) -> Self {
+        self.allow_link_local_ip_addresses = allow;
+        self
+    }
+
+    /// Sets whether loopback, link-local and private IP ranges are all allowed.
+    ///
+    /// This is a shortcut for [`HttpAclBuilder::loopback_ip_addresses`],
+    /// [`HttpAclBuilder::link_local_ip_addresses`] and [`HttpAclBuilder::private_ip_ranges`].
+    pub fn non_global_ip_ranges(self, allow: bool) -> Self {
+        self.loopback_ip_addresses(allow)
+            .link_local_ip_addresses(allow)
+            .private_ip_ranges(allow)
+    }
+
+    /// Sets whether the unspecified IP addresses (`0.0.0.0` and `::`) are allowed.
+    ///
+    /// These are denied by default, even if they fall within an allowed IP range,
+    /// as many systems route them to the local host. Once allowed, they are treated
+    /// like the other allowed non-global addresses and go through the IP ACL.
+    pub fn unspecified_ip_addresses(mut self, allow: bool) -> Self {
+        self.allow_unspecified_ip_addresses = allow;
+        self
+    }
+
+    /// Sets whether wildcard hosts such as `*.example.com` also match their apex domain
+    /// (`example.com`). Disabled by default, so the apex needs its own rule.
+    pub fn wildcard_includes_apex(mut self, include: bool) -> Self {
+        self.wildcard_includes_apex = include;
+        self
+    }
+
+    /// Sets the precedence between allowed and denied rules that both match.
+    pub fn precedence(mut self, precedence: Precedence) -> Self {
+        self.precedence = precedence;
+        self
+    }
+
+    /// Sets the IP versions that are allowed.
+    pub fn ip_family_mode(mut self, mode: IpFamilyMode) -> Self {
+        self.ip_family_mode = mode;
+        self
+    }
+
+    /// Sets whether URL paths are percent-decoded before matching.
+    ///
+    /// Encoded slashes (`%2F`) are left encoded so that decoding never
+    /// changes the segment boundaries of the path. Enabled by default.
+    pub fn decode_url_paths(mut self, decode: bool) -> Self {
+        self.decode_url_paths = decode;
+        self
+    }
+
+    /// Sets whether URL paths are normalized before matching.
+    ///
+    /// Normalization resolves `.` and `..` segments and collapses duplicate slashes,
+    /// in both the URL paths of requests and the URL path rules. Enabled by default.
+    pub fn normalize_url_paths(mut self, normalize: bool) -> Self {
+        self.normalize_url_paths = normalize;
+        self.rebuild_url_path_routers();
+        self
+    }
+
+    /// Sets whether trailing slashes are stripped from URL paths before matching,
+    /// in both the URL paths of requests and the URL path rules.
+    ///
+    /// When enabled, `/admin/` and `/admin` are the same URL path. Enabled by default.
+    pub fn strip_trailing_slashes(mut self, strip: bool) -> Self {
+        self.strip_trailing_slashes = strip;
+        self.rebuild_url_path_routers();
+        self
+    }
+
+    /// Set default action for HTTP methods if no ACL match is found.
+    pub fn method_acl_default(mut self, allow: bool) -> Self {
+        self.method_acl_default = allow;
+        self
+    }
+
+    /// Set default action for hosts if no ACL match is found.
+    pub fn host_acl_default(mut self, allow: bool) -> Self {
+        self.host_acl_default = allow;
+        self
+    }
+
+    /// Set default action for ports if no ACL match is found.
+    pub fn port_acl_default(mut self, allow: bool) -> Self {
+        self.port_acl_default = allow;
+        self
+    }
+
+    /// Set default action for IPs if no ACL match is found.
+    pub fn ip_acl_default(mut self, allow: bool) -> Self {
+        self.ip_acl_default = allow;
+        self
+    }
+
+    /// Set default action for URL paths if no ACL match is found.
+    pub fn url_path_acl_default(mut self, allow: bool) -> Self {
+        self.url_path_acl_default = allow;
+        self
+    }
+
+    /// Set default action for headers if no ACL match is found.
+    pub fn header_acl_default(mut self, allow: bool) -> Self {
+        self.header_acl_default = allow;
+        self
+    }
+
+    /// Set default action for schemes other than HTTP and HTTPS,
+    /// which are governed by [`HttpAclBuilder::http`] and [`HttpAclBuilder::https`].
+    pub fn scheme_acl_default(mut self, allow: bool) -> Self {
+        self.scheme_acl_default = allow;
+        self
+    }
+
+    /// Set a callback called with the dimension name and the classification of every decision
+    /// of the `is_*_allowed` methods, such as `host` or `port`.
+    pub fn on_decision(
+        mut self,
+        on_decision: impl Fn(&str, &AclClassification) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_decision = Some(Arc::new(on_decision));
+        self
+    }
+
+    /// Set the clock used to expire the expiring rules, [`Instant::now`] by default.
+    pub fn clock(mut self, clock: impl Fn() -> Instant + Send + Sync + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Adds a method to the allowed methods.
+    pub fn add_allowed_method(
+        mut self,
+        method: impl Into<HttpRequestMethod>,
+    ) -> Result<Self, AddError> {
+        let method = method.into();
+        if self.denied_methods.contains(&method) {
+            Err(AddError::AlreadyDenied)
+        } else if self.allowed_methods.contains(&method) {
+            Err(AddError::AlreadyAllowed)
+        } else {
+            self.allowed_methods.push(method);
+            Ok(self)
+        }
+    }
+
+    /// Removes a method from the allowed methods.
+    pub fn remove_allowed_method(mut self, method: impl Into<HttpRequestMethod>) -> Self {
+        let method = method.into();
+        self.allowed_methods.retain(|m| m != &method);
+        self
+    }
+
+    /// Adds a method to the allowed methods without checking for conflicts.
+    ///
+    /// The last writer wins: a method that is already denied is moved to the allowed methods.
+    pub fn with_allowed_method(mut self, method: impl Into<HttpRequestMethod>) -> Self {
+        let method = method.into();
+        self.denied_methods.retain(|m| m != &method);
+        if !self.allowed_methods.contains(&method) {
+            self.allowed_methods.push(method);
+        }
+        self
+    }
+
+    /// Sets the allowed methods.
+    pub fn allowed_methods(
+        mut self,
+        methods: Vec<impl Into<HttpRequestMethod>>,
+    ) -> Result<Self, AddError> {
+        let methods = methods.into_iter().map(|m| m.into()).collect::<Vec<_>>();
+
+        for method in &methods {
+            if self.denied_methods.contains(method) {
+                return Err(AddError::AlreadyDenied);
+            } else if self.allowed_methods.contains(method) {
+                return Err(AddError::AlreadyAllowed);
+            }
+        }
+        self.allowed_methods = methods;
+        Ok(self)
+    }
+
+    /// Clears the allowed methods.
+    pub fn clear_allowed_methods(mut self) -> Self {
+        self.allowed_methods.clear();
+        self
+    }
+
+    /// Adds a method to the denied methods.
+    pub fn add_denied_method(
+        mut self,
+        method: impl Into<HttpRequestMethod>,
+    ) -> Result<Self, AddError> {
+        let method = method.into();
+        if self.allowed_methods.contains(&method) {
+            Err(AddError::AlreadyAllowed)
+        } else if self.denied_methods.contains(&method) {
+            Err(AddError::AlreadyDenied)
+        } else {
+            self.denied_methods.push(method);
+            Ok(self)
+        }
+    }
+
+    /// Removes a method from the denied methods.
+    pub fn remove_denied_method(mut self, method: impl Into<HttpRequestMethod>) -> Self {
+        let method = method.into();
+        self.denied_methods.retain(|m| m != &method);
+        self
+    }
+
+    /// Adds a method to the denied methods without checking for conflicts.
+    ///
+    /// The last writer wins: a method that is already allowed is moved to the denied methods.
+    pub fn with_denied_method(mut self, method: impl Into<HttpRequestMethod>) -> Self {
+        let method = method.into();
+        self.allowed_methods.retain(|m| m != &method);
+        if !self.denied_methods.contains(&method) {
+            self.denied_methods.push(method);
+        }
+        self
+    }
+
+    /// Sets the denied methods.
+    pub fn denied_methods(
+        mut self,
+        methods: Vec<impl Into<HttpRequestMethod>>,
+    ) -> Result<Self, AddError> {
+        let methods = methods.into_iter().map(|m| m.into()).collect::<Vec<_>>();
+
+        for method in &methods {
+            if self.allowed_methods.contains(method) {
+                return Err(AddError::AlreadyAllowed);
+            } else if self.denied_methods.contains(method) {
+                return Err(AddError::AlreadyDenied);
+            }
+        }
+        self.denied_methods = methods;
+        Ok(self)
+    }
+
+    /// Clears the denied methods.
+    pub fn clear_denied_methods(mut self) -> Self {
+        self.denied_methods.clear();
+        self
+    }
+
+    /// Adds a host to the allowed hosts.
+    ///
+    /// A host prefixed with `*.` is a wildcard matching any subdomain, and a host with a `*`
+    /// within a label such as `api-*.example.com` is a label glob matching one or more characters.
+    pub fn add_allowed_host(mut self, host: impl AsRef<str>) -> Result<Self, AddError> {
+        let host = host.as_ref().to_string();
+        if utils::authority::is_valid_host(&host) {
+            if self.denied_hosts.contains(&host) {
+                Err(AddError::AlreadyDenied)
+            } else if self.allowed_hosts.contains(&host) {
+                Err(AddError::AlreadyAllowed)
+            } else {
+                self.allowed_hosts.push(host);
+                Ok(self)
+            }
+        } else {
+            Err(AddError::Invalid)
+        }
+    }
+
+    /// Removes a host from the allowed hosts.
+    pub fn remove_allowed_host(mut self, host: impl AsRef<str>) -> Self {
+        let host = host.as_ref();
+        self.allowed_hosts.retain(|h| h != host);
+        self
+    }
+
+    /// Adds a host to the allowed hosts without checking for conflicts.
+    ///
+    /// The last writer wins: a host that is already denied is moved to the allowed hosts.
+    /// An invalid host is ignored.
+    pub fn with_allowed_host(mut self, host: impl AsRef<str>) -> Self {
+        let host = host.as_ref();
+        if utils::authority::is_valid_host(host) {
+            self.denied_hosts.retain(|h| h != host);
+            if !self.allowed_hosts.iter().any(|h| h == host) {
+                self.allowed_hosts.push(host.to_string());
+            }
+        }
+        self
+    }
+
+    /// Sets the allowed hosts.
+    pub fn allowed_hosts(mut self, hosts: Vec<String>) -> Result<Self, AddError> {
+        for host in &hosts {
+            if utils::authority::is_valid_host(host) {
+                if self.denied_hosts.contains(host) {
+                    return Err(AddError::AlreadyDenied);
+                } else if self.allowed_hosts.contains(host) {
+                    return Err(AddError::AlreadyAllowed);
+                }
+            } else {
+                return Err(AddError::Invalid);
+            }
+        }
+        self.allowed_hosts = hosts;
+        Ok(self)
+    }
+
+    /// Clears the allowed hosts.
+    pub fn clear_allowed_hosts(mut self) -> Self {
+        self.allowed_hosts.clear();
+        self
+    }
+
+    /// Retains only the allowed hosts for which the predicate returns `true`.
+    pub fn retain_allowed_hosts(mut self, mut f: impl FnMut(&str) -> bool) -> Self {
+        self.allowed_hosts.retain(|host| f(host));
+        self
+    }
+
+    /// Extends the allowed hosts, checking each host as [`HttpAclBuilder::add_allowed_host`] does.
+    ///
+    /// An invalid or conflicting host is returned in [`AddError::InvalidEntity`],
+    /// [`AddError::AlreadyAllowedEntity`] or [`AddError::AlreadyDeniedEntity`].
+    pub fn extend_allowed_hosts(
+        mut self,
+        hosts: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, AddError> {
+        for host in hosts {
+            let host = host.as_ref();
+            self = self
+                .add_allowed_host(host)
+                .map_err(|err| err.with_entity(host))?;
+        }
+        Ok(self)
+    }
+
+    /// Adds a host to the denied hosts.
+    ///
+    /// A host prefixed with `*.` is a wildcard matching any subdomain, and a host with a `*`
+    /// within a label such as `api-*.example.com` is a label glob matching one or more characters.
+    pub fn add_denied_host(mut self, host: impl AsRef<str>) -> Result<Self, AddError> {
+        let host = host.as_ref().to_string();
+        if utils::authority::is_valid_host(&host) {
+            if self.allowed_hosts.contains(&host) {
+                Err(AddError::AlreadyAllowed)
+            } else if self.denied_hosts.contains(&host) {
+                Err(AddError::AlreadyDenied)
+            } else {
+                self.denied_hosts.push(host);
+                Ok(self)
+            }
+        } else {
+            Err(AddError::Invalid)
+        }
+    }
+
+    /// Removes a host from the denied hosts.
+    pub fn remove_denied_host(mut self, host: impl AsRef<str>) -> Self {
+        let host = host.as_ref();
+        self.denied_hosts.retain(|h| h != host);
+        self
+    }
+
+    /// Adds an authority, a host with an optional port such as `example.com:8080`,
+    /// to the allowed authorities.
+    ///
+    /// An authority only matches a request to the same host and port, and takes precedence
+    /// over the independent host and port rules in [`HttpAcl::is_authority_allowed`].
+    pub fn add_allowed_authority(mut self, authority: impl AsRef<str>) -> Result<Self, AddError> {
+        let authority = Self::authority(authority.as_ref())?;
+        if self.denied_authorities.contains(&authority) {
+            Err(AddError::AlreadyDenied)
+        } else if self.allowed_authorities.contains(&authority) {
+            Err(AddError::AlreadyAllowed)
+        } else {
+            self.allowed_authorities.push(authority);
+            Ok(self)
+        }
+    }
+
+    /// Removes an authority from the allowed authorities.
+    pub fn remove_allowed_authority(mut self, authority: impl AsRef<str>) -> Self {
+        if let Ok(authority) = Self::authority(authority.as_ref()) {
+            self.allowed_authorities.retain(|a| *a != authority);
+        }
+        self
+    }
+
+    /// Adds an authority, a host with an optional port such as `example.com:8080`,
+    /// to the denied authorities.
+    ///
+    /// See [`HttpAclBuilder::add_allowed_authority`] for how authorities are matched.
+    pub fn add_denied_authority(mut self, authority: impl AsRef<str>) -> Result<Self, AddError> {
+        let authority = Self::authority(authority.as_ref())?;
+        if self.allowed_authorities.contains(&authority) {
+            Err(AddError::AlreadyAllowed)
+        } else if self.denied_authorities.contains(&authority) {
+            Err(AddError::AlreadyDenied)
+        } else {
+            self.denied_authorities.push(authority);
+            Ok(self)
+        }
+    }
+
+    /// Removes an authority from the denied authorities.
+    pub fn remove_denied_authority(mut self, authority: impl AsRef<str>) -> Self {
+        if let Ok(authority) = Self::authority(authority.as_ref()) {
+            self.denied_authorities.retain(|a| *a != authority);
+        }
+        self
+    }
+
+    /// Parses an authority into its normalized form.
+    fn authority(authority: &str) -> Result<String, AddError> {
+        Authority::parse(authority)
+            .map(|authority| authority.to_string())
+            .map_err(|_| AddError::InvalidEntity(authority.to_string()))
+    }
+
+    /// Parses the authorities, skipping the invalid ones.
+    fn authorities(authorities: &[String]) -> HashSet<Authority> {
+        authorities
+            .iter()
+            .filter_map(|authority| Authority::parse(authority).ok())
+            .collect()
+    }
+
+    /// Adds a regular expression to the allowed host regular expressions.
+    ///
+    /// Host regular expressions are only consulted if no exact, label glob or wildcard host
+    /// matches, denied ones first. An invalid regular expression is returned in
+    /// [`AddError::InvalidEntity`].
+    #[cfg(feature = "regex")]
+    pub fn add_allowed_host_regex(mut self, pattern: impl AsRef<str>) -> Result<Self, AddError> {
+        let pattern = Self::host_regex(pattern.as_ref())?;
+        if self.denied_host_regexes.contains(&pattern) {
+            Err(AddError::AlreadyDenied)
+        } else if self.allowed_host_regexes.contains(&pattern) {
+            Err(AddError::AlreadyAllowed)
+        } else {
+            self.allowed_host_regexes.push(pattern);
+            Ok(self)
+        }
+    }
+
+    /// Adds a regular expression to the denied host regular expressions.
+    ///
+    /// See [`HttpAclBuilder::add_allowed_host_regex`] for when they are consulted.
+    #[cfg(feature = "regex")]
+    pub fn add_denied_host_regex(mut self, pattern: impl AsRef<str>) -> Result<Self, AddError> {
+        let pattern = Self::host_regex(pattern.as_ref())?;
+        if self.allowed_host_regexes.contains(&pattern) {
+            Err(AddError::AlreadyAllowed)
+        } else if self.denied_host_regexes.contains(&pattern) {
+            Err(AddError::AlreadyDenied)
+        } else {
+            self.denied_host_regexes.push(pattern);
+            Ok(self)
+        }
+    }
+
+    /// Checks that a host regular expression compiles.
+    #[cfg(feature = "regex")]
+    fn host_regex(pattern: &str) -> Result<String, AddError> {
+        regex::Regex::new(pattern)
+            .map(|_| pattern.to_string())
+            .map_err(|_| AddError::InvalidEntity(pattern.to_string()))
+    }
+
+    /// Compiles the host regular expressions, skipping the invalid ones.
+    #[cfg(feature = "regex")]
+    fn host_regexes(patterns: &[String]) -> Vec<regex::Regex> {
+        patterns
+            .iter()
+            .filter_map(|pattern| regex::Regex::new(pattern).ok())
+            .collect()
+    }
+
+    /// Sets the denied hosts.
+    pub fn denied_hosts(mut self, hosts: Vec<String>) -> Result<Self, AddError> {
+        for host in &hosts {
+            if utils::authority::is_valid_host(host) {
+                if self.allowed_hosts.contains(host) {
+                    return Err(AddError::AlreadyAllowed);
+                } else if self.denied_hosts.contains(host) {
+                    return Err(AddError::AlreadyDenied);
+                }
+            } else {
+                return Err(AddError::Invalid);
+            }
+        }
+        self.denied_hosts = hosts;
+        Ok(self)
+    }
+
+    /// Clears the denied hosts.
+    pub fn clear_denied_hosts(mut self) -> Self {
+        self.denied_hosts.clear();
+        self
+    }
+
+    /// Retains only the denied hosts for which the predicate returns `true`.
+    pub fn retain_denied_hosts(mut self, mut f: impl FnMut(&str) -> bool) -> Self {
+        self.denied_hosts.retain(|host| f(host));
+        self
+    }
+
+    /// Extends the denied hosts, checking each host as [`HttpAclBuilder::add_denied_host`] does.
+    ///
+    /// An invalid or conflicting host is returned in [`AddError::InvalidEntity`],
+    /// [`AddError::AlreadyAllowedEntity`] or [`AddError::AlreadyDeniedEntity`].
+    pub fn extend_denied_hosts(
+        mut self,
+        hosts: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, AddError> {
+        for host in hosts {
+            let host = host.as_ref();
+            self = self
+                .add_denied_host(host)
+                .map_err(|err| err.with_entity(host))?;
+        }
+        Ok(self)
+    }
+
+    /// Adds a header to the allowed headers.
+    ///
+    /// A header without a value matches any value of that header.
+    pub fn add_allowed_header(
+        mut self,
+        header: impl AsRef<str>,
+        value: Option<String>,
+    ) -> Result<Self, AddError> {
+        let header = header.as_ref().to_ascii_lowercase();
+        if self.denied_headers.get(&header) == Some(&value) {
+            Err(AddError::AlreadyDenied)
+        } else if let Entry::Vacant(entry) = self.allowed_headers.entry(header) {
+            entry.insert(value);
+            Ok(self)
+        } else {
+            Err(AddError::AlreadyAllowed)
+        }
+    }
+
+    /// Removes a header from the allowed headers.
+    pub fn remove_allowed_header(mut self, header: impl AsRef<str>) -> Self {
+        self.allowed_headers
+            .remove(&header.as_ref().to_ascii_lowercase());
+        self
+    }
+
+    /// Sets the allowed headers.
+    pub fn allowed_headers(
+        mut self,
+        headers: HashMap<String, Option<String>>,
+    ) -> Result<Self, AddError> {
+        let headers = headers
+            .into_iter()
+            .map(|(header, value)| (header.to_ascii_lowercase(), value))
+            .collect::<HashMap<_, _>>();
+        for (header, value) in &headers {
+            if self.denied_headers.get(header) == Some(value) {
+                return Err(AddError::AlreadyDenied);
+            }
+        }
+        self.allowed_headers = headers;
+        Ok(self)
+    }
+
+    /// Clears the allowed headers.
+    pub fn clear_allowed_headers(mut self) -> Self {
+        self.allowed_headers.clear();
+        self
+    }
+
+    /// Adds a header to the denied headers.
+    ///
+    /// A header without a value matches any value of that header.
+    pub fn add_denied_header(
+        mut self,
+        header: impl AsRef<str>,
+        value: Option<String>,
+    ) -> Result<Self, AddError> {
+        let header = header.as_ref().to_ascii_lowercase();
+        if self.allowed_headers.get(&header) == Some(&value) {
+            Err(AddError::AlreadyAllowed)
+        } else if let Entry::Vacant(entry) = self.denied_headers.entry(header) {
+            entry.insert(value);
+            Ok(self)
+        } else {
+            Err(AddError::AlreadyDenied)
+        }
+    }
+
+    /// Removes a header from the denied headers.
+    pub fn remove_denied_header(mut self, header: impl AsRef<str>) -> Self {
+        self.denied_headers
+            .remove(&header.as_ref().to_ascii_lowercase());
         self
     }
 
-    /// Set default action for HTTP methods if no ACL match is found.
-    pub fn method_acl_default(mut self, allow: bool) -> Self {
-        self.method_acl_default = allow;
-        self
+    /// Sets the denied headers.
+    pub fn denied_headers(
+        mut self,
+        headers: HashMap<String, Option<String>>,
+    ) -> Result<Self, AddError> {
+        let headers = headers
+            .into_iter()
+            .map(|(header, value)| (header.to_ascii_lowercase(), value))
+            .collect::<HashMap<_, _>>();
+        for (header, value) in &headers {
+            if self.allowed_headers.get(header) == Some(value) {
+                return Err(AddError::AlreadyAllowed);
+            }
+        }
+        self.denied_headers = headers;
+        Ok(self)
     }
 
-    /// Set default action for hosts if no ACL match is found.
-    pub fn host_acl_default(mut self, allow: bool) -> Self {
-        self.host_acl_default = allow;
+    /// Clears the denied headers.
+    pub fn clear_denied_headers(mut self) -> Self {
+        self.denied_headers.clear();
         self
     }
 
-    /// Set default action for ports if no ACL match is found.
-    pub fn port_acl_default(mut self, allow: bool) -> Self {
-        self.port_acl_default = allow;
-        self
+    /// Adds a set of values to the denied values of a header.
+    ///
+    /// A header is denied if its value is any of the denied values,
+    /// the values are appended to any values already denied for the header.
+    pub fn add_denied_header_values(
+        mut self,
+        header: impl AsRef<str>,
+        values: Vec<String>,
+    ) -> Result<Self, AddError> {
+        let header = header.as_ref().to_ascii_lowercase();
+        if values.is_empty() {
+            return Err(AddError::Invalid);
+        }
+        if let Some(Some(allowed)) = self.allowed_headers.get(&header) {
+            if values.contains(allowed) {
+                return Err(AddError::AlreadyAllowed);
+            }
+        }
+        let denied = self.denied_header_values.entry(header).or_default();
+        for value in values {
+            if !denied.contains(&value) {
+                denied.push(value);
+            }
+        }
+        Ok(self)
     }
 
-    /// Set default action for IPs if no ACL match is found.
-    pub fn ip_acl_default(mut self, allow: bool) -> Self {
-        self.ip_acl_default = allow;
+    /// Removes the denied values of a header.
+    pub fn remove_denied_header_values(mut self, header: impl AsRef<str>) -> Self {
+        self.denied_header_values
+            .remove(&header.as_ref().to_ascii_lowercase());
         self
     }
 
-    /// Set default action for URL paths if no ACL match is found.
-    pub fn url_path_acl_default(mut self, allow: bool) -> Self {
-        self.url_path_acl_default = allow;
+    /// Clears the denied values of all headers.
+    pub fn clear_denied_header_values(mut self) -> Self {
+        self.denied_header_values.clear();
         self
     }
 
-    /// Adds a method to the allowed methods.
-    pub fn add_allowed_method(
+    /// Adds a matcher to the allowed header matchers.
+    pub fn add_allowed_header_matcher(
         mut self,
-        method: impl Into<HttpRequestMethod>,
+        header: impl AsRef<str>,
+        matcher: HeaderMatcher,
     ) -> Result<Self, AddError> {
-        let method = method.into();
-        if self.denied_methods.contains(&method) {
-            Err(AddError::AlreadyDenied)
-        } else if self.allowed_methods.contains(&method) {
+        let header = header.as_ref().to_ascii_lowercase();
+        if self
+            .denied_header_matchers
+            .get(&header)
+            .is_some_and(|denied| denied.contains(&matcher))
+        {
+            return Err(AddError::AlreadyDenied);
+        }
+        let allowed = self.allowed_header_matchers.entry(header).or_default();
+        if allowed.contains(&matcher) {
             Err(AddError::AlreadyAllowed)
         } else {
-            self.allowed_methods.push(method);
+            allowed.push(matcher);
             Ok(self)
         }
     }
 
-    /// Removes a method from the allowed methods.
-    pub fn remove_allowed_method(mut self, method: impl Into<HttpRequestMethod>) -> Self {
-        let method = method.into();
-        self.allowed_methods.retain(|m| m != &method);
-        self
-    }
-
-    /// Sets the allowed methods.
-    pub fn allowed_methods(
+    /// Adds a matcher to the denied header matchers.
+    pub fn add_denied_header_matcher(
         mut self,
-        methods: Vec<impl Into<HttpRequestMethod>>,
+        header: impl AsRef<str>,
+        matcher: HeaderMatcher,
     ) -> Result<Self, AddError> {
-        let methods = methods.into_iter().map(|m| m.into()).collect::<Vec<_>>();
+        let header = header.as_ref().to_ascii_lowercase();
+        if self
+            .allowed_header_matchers
+            .get(&header)
+            .is_some_and(|allowed| allowed.contains(&matcher))
+        {
+            return Err(AddError::AlreadyAllowed);
+        }
+        let denied = self.denied_header_matchers.entry(header).or_default();
+        if denied.contains(&matcher) {
+            Err(AddError::AlreadyDenied)
+        } else {
+            denied.push(matcher);
+            Ok(self)
+        }
+    }
 
-        for method in &methods {
-            if self.denied_methods.contains(method) {
-                return Err(AddError::AlreadyDenied);
-            } else if self.allowed_methods.contains(method) {
-                return Err(AddError::AlreadyAllowed);
+    /// Adds the rules of a header policy to the header matchers.
+    ///
+    /// Rules already present are skipped, a rule conflicting with a rule of the
+    /// opposite list is an error.
+    pub fn header_policy(mut self, policy: HeaderPolicy) -> Result<Self, AddError> {
+        fn contains(
+            matchers: &HashMap<String, Vec<HeaderMatcher>>,
+            header: &str,
+            matcher: &HeaderMatcher,
+        ) -> bool {
+            matchers
+                .get(header)
+                .is_some_and(|matchers| matchers.contains(matcher))
+        }
+
+        for (header, matcher) in policy.allowed {
+            if !contains(&self.allowed_header_matchers, &header, &matcher) {
+                self = self.add_allowed_header_matcher(header, matcher)?;
+            }
+        }
+        for (header, matcher) in policy.denied {
+            if !contains(&self.denied_header_matchers, &header, &matcher) {
+                self = self.add_denied_header_matcher(header, matcher)?;
             }
         }
-        self.allowed_methods = methods;
         Ok(self)
     }
 
-    /// Clears the allowed methods.
-    pub fn clear_allowed_methods(mut self) -> Self {
-        self.allowed_methods.clear();
+    /// Clears the allowed and denied header matchers.
+    pub fn clear_header_matchers(mut self) -> Self {
+        self.allowed_header_matchers.clear();
+        self.denied_header_matchers.clear();
         self
     }
 
-    /// Adds a method to the denied methods.
-    pub fn add_denied_method(
-        mut self,
-        method: impl Into<HttpRequestMethod>,
-    ) -> Result<Self, AddError> {
-        let method = method.into();
-        if self.allowed_methods.contains(&method) {
+    /// Adds a header to the required headers.
+    pub fn add_required_header(mut self, header: impl AsRef<str>) -> Result<Self, AddError> {
+        let header = header.as_ref().to_ascii_lowercase();
+        if header.is_empty() {
+            Err(AddError::Invalid)
+        } else if self.required_headers.contains(&header) {
             Err(AddError::AlreadyAllowed)
-        } else if self.denied_methods.contains(&method) {
-            Err(AddError::AlreadyDenied)
         } else {
-            self.denied_methods.push(method);
+            self.required_headers.push(header);
             Ok(self)
         }
     }
 
-    /// Removes a method from the denied methods.
-    pub fn remove_denied_method(mut self, method: impl Into<HttpRequestMethod>) -> Self {
-        let method = method.into();
-        self.denied_methods.retain(|m| m != &method);
+    /// Removes a header from the required headers.
+    pub fn remove_required_header(mut self, header: impl AsRef<str>) -> Self {
+        let header = header.as_ref().to_ascii_lowercase();
+        self.required_headers.retain(|h| *h != header);
         self
     }
 
-    /// Sets the denied methods.
-    pub fn denied_methods(
-        mut self,
-        methods: Vec<impl Into<HttpRequestMethod>>,
-    ) -> Result<Self, AddError> {
-        let methods = methods.into_iter().map(|m| m.into()).collect::<Vec<_>>();
+    /// Clears the required headers.
+    pub fn clear_required_headers(mut self) -> Self {
+        self.required_headers.clear();
+        self
+    }
 
-        for method in &methods {
-            if self.allowed_methods.contains(method) {
-                return Err(AddError::AlreadyAllowed);
-            } else if self.denied_methods.contains(method) {
-                return Err(AddError::AlreadyDenied);
+    /// Sets the maximum number of headers, or removes the limit with `None`.
+    pub fn max_header_count(mut self, max: Option<usize>) -> Self {
+        self.max_header_count = max;
+        self
+    }
+
+    /// Sets the maximum total size of header names and values in bytes, or removes the limit with `None`.
+    pub fn max_total_header_bytes(mut self, max: Option<usize>) -> Self {
+        self.max_total_header_bytes = max;
+        self
+    }
+
+    /// Adds the denied hosts and IP ranges from a newline-delimited list.
+    ///
+    /// Each line is a host, an IP address or a CIDR network, and is detected as such.
+    /// See [`HttpAclBuilder::add_denied_hosts_from_str`] for the format.
+    pub fn add_denied_from_str(mut self, list: &str) -> Result<Self, AddError> {
+        for entry in Self::list_entries(list) {
+            if let Some(ip_range) = Self::parse_ip_range_entry(entry) {
+                if !self.denied_ip_ranges.contains(&ip_range) {
+                    self = self.add_denied_ip_range(ip_range)?;
+                }
+            } else if utils::authority::is_valid_host(entry) {
+                if !self.denied_hosts.iter().any(|host| host == entry) {
+                    self = self.add_denied_host(entry)?;
+                }
+            } else {
+                return Err(AddError::InvalidEntity(entry.to_string()));
             }
         }
-        self.denied_methods = methods;
         Ok(self)
     }
 
-    /// Clears the denied methods.
-    pub fn clear_denied_methods(mut self) -> Self {
-        self.denied_methods.clear();
-        self
+    /// Adds the denied hosts from a newline-delimited list.
+    ///
+    /// Whitespace is trimmed, everything after a `#` is a comment and blank lines are skipped.
+    /// Hosts that are already denied are skipped, an invalid line is returned in [`AddError::InvalidEntity`].
+    pub fn add_denied_hosts_from_str(mut self, list: &str) -> Result<Self, AddError> {
+        for entry in Self::list_entries(list) {
+            if !utils::authority::is_valid_host(entry)
+                || Self::parse_ip_range_entry(entry).is_some()
+            {
+                return Err(AddError::InvalidEntity(entry.to_string()));
+            }
+            if !self.denied_hosts.iter().any(|host| host == entry) {
+                self = self.add_denied_host(entry)?;
+            }
+        }
+        Ok(self)
     }
 
-    /// Sets whether public IP ranges are allowed.
-    pub fn add_allowed_host(mut self, host: String) -> Result<Self, AddError> {
-        if utils::authority::is_valid_host(&host) {
-            if self.denied_hosts.contains(&host) {
-                Err(AddError::AlreadyDenied)
-            } else if self.allowed_hosts.contains(&host) {
-                Err(AddError::AlreadyAllowed)
-            } else {
-                self.allowed_hosts.push(host);
-                Ok(self)
+    /// Adds the denied IP ranges from a newline-delimited list of IP addresses and CIDR networks.
+    ///
+    /// See [`HttpAclBuilder::add_denied_hosts_from_str`] for the format.
+    pub fn add_denied_ip_ranges_from_str(mut self, list: &str) -> Result<Self, AddError> {
+        for entry in Self::list_entries(list) {
+            let ip_range = Self::parse_ip_range_entry(entry)
+                .ok_or_else(|| AddError::InvalidEntity(entry.to_string()))?;
+            if !self.denied_ip_ranges.contains(&ip_range) {
+                self = self.add_denied_ip_range(ip_range)?;
             }
+        }
+        Ok(self)
+    }
+
+    /// Returns the entries of a newline-delimited list, without comments and blank lines.
+    fn list_entries(list: &str) -> impl Iterator<Item = &str> {
+        list.lines()
+            .map(|line| line.split_once('#').map_or(line, |(entry, _)| entry).trim())
+            .filter(|entry| !entry.is_empty())
+    }
+
+    /// Parses an IP address or CIDR network entry into an IP range.
+    fn parse_ip_range_entry(entry: &str) -> Option<RangeInclusive<IpAddr>> {
+        if let Ok(ip_net) = entry.parse::<IpNet>() {
+            ip_net.into_range()
         } else {
+            entry.parse::<IpAddr>().ok().map(|ip| ip..=ip)
+        }
+    }
+
+    /// Adds a compound rule to the allowed compound rules.
+    pub fn add_allowed_rule(mut self, rule: CompoundRule) -> Result<Self, AddError> {
+        if !utils::authority::is_valid_host(&rule.host) {
             Err(AddError::Invalid)
+        } else if self.allowed_rules.contains(&rule) {
+            Err(AddError::AlreadyAllowed)
+        } else {
+            self.allowed_rules.push(rule);
+            Ok(self)
         }
     }
 
-    /// Removes a host from the allowed hosts.
-    pub fn remove_allowed_host(mut self, host: String) -> Self {
-        self.allowed_hosts.retain(|h| h != &host);
+    /// Removes a compound rule from the allowed compound rules.
+    pub fn remove_allowed_rule(mut self, rule: &CompoundRule) -> Self {
+        self.allowed_rules.retain(|r| r != rule);
         self
     }
 
-    /// Sets the allowed hosts.
-    pub fn allowed_hosts(mut self, hosts: Vec<String>) -> Result<Self, AddError> {
-        for host in &hosts {
-            if utils::authority::is_valid_host(host) {
-                if self.denied_hosts.contains(host) {
-                    return Err(AddError::AlreadyDenied);
-                } else if self.allowed_hosts.contains(host) {
-                    return Err(AddError::AlreadyAllowed);
-                }
-            } else {
-                return Err(AddError::Invalid);
-            }
+    /// Sets the allowed compound rules.
+    pub fn allowed_rules(mut self, rules: Vec<CompoundRule>) -> Result<Self, AddError> {
+        if !utils::has_unique_elements(&rules) {
+            return Err(AddError::AlreadyAllowed);
         }
-        self.allowed_hosts = hosts;
+        if rules
+            .iter()
+            .any(|rule| !utils::authority::is_valid_host(&rule.host))
+        {
+            return Err(AddError::Invalid);
+        }
+        self.allowed_rules = rules;
         Ok(self)
     }
 
-    /// Clears the allowed hosts.
-    pub fn clear_allowed_hosts(mut self) -> Self {
-        self.allowed_hosts.clear();
+    /// Clears the allowed compound rules.
+    pub fn clear_allowed_rules(mut self) -> Self {
+        self.allowed_rules.clear();
         self
     }
 
-    /// Adds a host to the denied hosts.
-    pub fn add_denied_host(mut self, host: String) -> Result<Self, AddError> {
-        if utils::authority::is_valid_host(&host) {
-            if self.allowed_hosts.contains(&host) {
-                Err(AddError::AlreadyAllowed)
-            } else if self.denied_hosts.contains(&host) {
-                Err(AddError::AlreadyDenied)
-            } else {
-                self.denied_hosts.push(host);
-                Ok(self)
-            }
-        } else {
+    /// Adds a substring to the denied query substrings.
+    ///
+    /// A URL query containing the substring after percent-decoding is denied,
+    /// the substring is compared case-insensitively.
+    pub fn add_denied_query_substring(
+        mut self,
+        substring: impl AsRef<str>,
+    ) -> Result<Self, AddError> {
+        let substring = substring.as_ref().to_lowercase();
+        if substring.is_empty() {
             Err(AddError::Invalid)
+        } else if self.denied_query_substrings.contains(&substring) {
+            Err(AddError::AlreadyDenied)
+        } else {
+            self.denied_query_substrings.push(substring);
+            Ok(self)
         }
     }
 
-    /// Removes a host from the denied hosts.
-    pub fn remove_denied_host(mut self, host: String) -> Self {
-        self.denied_hosts.retain(|h| h != &host);
+    /// Removes a substring from the denied query substrings.
+    pub fn remove_denied_query_substring(mut self, substring: impl AsRef<str>) -> Self {
+        let substring = substring.as_ref().to_lowercase();
+        self.denied_query_substrings.retain(|s| *s != substring);
         self
     }
 
-    /// Sets the denied hosts.
-    pub fn denied_hosts(mut self, hosts: Vec<String>) -> Result<Self, AddError> {
-        for host in &hosts {
-            if utils::authority::is_valid_host(host) {
-                if self.allowed_hosts.contains(host) {
-                    return Err(AddError::AlreadyAllowed);
-                } else if self.denied_hosts.contains(host) {
-                    return Err(AddError::AlreadyDenied);
-                }
-            } else {
-                return Err(AddError::Invalid);
-            }
+    /// Sets the denied query substrings.
+    pub fn denied_query_substrings(mut self, substrings: Vec<String>) -> Result<Self, AddError> {
+        let substrings = substrings
+            .into_iter()
+            .map(|substring| substring.to_lowercase())
+            .collect::<Vec<_>>();
+        if substrings.iter().any(|substring| substring.is_empty()) {
+            return Err(AddError::Invalid);
         }
-        self.denied_hosts = hosts;
+        if !utils::has_unique_elements(&substrings) {
+            return Err(AddError::AlreadyDenied);
+        }
+        self.denied_query_substrings = substrings;
         Ok(self)
     }
 
-    /// Clears the denied hosts.
-    pub fn clear_denied_hosts(mut self) -> Self {
-        self.denied_hosts.clear();
+    /// Clears the denied query substrings.
+    pub fn clear_denied_query_substrings(mut self) -> Self {
+        self.denied_query_substrings.clear();
+        self
+    }
+
+    /// Sets the maximum length of a URL query in bytes, or removes the limit with `None`.
+    pub fn max_query_length(mut self, max: Option<usize>) -> Self {
+        self.max_query_length = max;
         self
     }
 
@@ -768,7 +4665,9 @@ impl HttpAclBuilder {
         mut self,
         port_range: RangeInclusive<u16>,
     ) -> Result<Self, AddError> {
-        if self.denied_port_ranges.contains(&port_range) {
+        if port_range.is_empty() {
+            Err(AddError::Invalid)
+        } else if self.denied_port_ranges.contains(&port_range) {
             Err(AddError::AlreadyDenied)
         } else if self.allowed_port_ranges.contains(&port_range) {
             Err(AddError::AlreadyAllowed)
@@ -790,7 +4689,9 @@ impl HttpAclBuilder {
         port_ranges: Vec<RangeInclusive<u16>>,
     ) -> Result<Self, AddError> {
         for port_range in &port_ranges {
-            if self.denied_port_ranges.contains(port_range) {
+            if port_range.is_empty() {
+                return Err(AddError::Invalid);
+            } else if self.denied_port_ranges.contains(port_range) {
                 return Err(AddError::AlreadyDenied);
             } else if self.allowed_port_ranges.contains(port_range) {
                 return Err(AddError::AlreadyAllowed);
@@ -806,12 +4707,23 @@ impl HttpAclBuilder {
         self
     }
 
+    /// Retains only the allowed port ranges for which the predicate returns `true`.
+    pub fn retain_allowed_port_ranges(
+        mut self,
+        f: impl FnMut(&RangeInclusive<u16>) -> bool,
+    ) -> Self {
+        self.allowed_port_ranges.retain(f);
+        self
+    }
+
     /// Adds a port range to the denied port ranges.
     pub fn add_denied_port_range(
         mut self,
         port_range: RangeInclusive<u16>,
     ) -> Result<Self, AddError> {
-        if self.allowed_port_ranges.contains(&port_range) {
+        if port_range.is_empty() {
+            Err(AddError::Invalid)
+        } else if self.allowed_port_ranges.contains(&port_range) {
             Err(AddError::AlreadyAllowed)
         } else if self.denied_port_ranges.contains(&port_range) {
             Err(AddError::AlreadyDenied)
@@ -833,7 +4745,9 @@ impl HttpAclBuilder {
         port_ranges: Vec<RangeInclusive<u16>>,
     ) -> Result<Self, AddError> {
         for port_range in &port_ranges {
-            if self.allowed_port_ranges.contains(port_range) {
+            if port_range.is_empty() {
+                return Err(AddError::Invalid);
+            } else if self.allowed_port_ranges.contains(port_range) {
                 return Err(AddError::AlreadyAllowed);
             } else if self.denied_port_ranges.contains(port_range) {
                 return Err(AddError::AlreadyDenied);
@@ -849,7 +4763,31 @@ impl HttpAclBuilder {
         self
     }
 
+    /// Retains only the denied port ranges for which the predicate returns `true`.
+    pub fn retain_denied_port_ranges(
+        mut self,
+        f: impl FnMut(&RangeInclusive<u16>) -> bool,
+    ) -> Self {
+        self.denied_port_ranges.retain(f);
+        self
+    }
+
+    /// Denies the ephemeral port range `49152..=65535` assigned by IANA.
+    pub fn deny_ephemeral_ports(self) -> Result<Self, AddError> {
+        self.add_denied_port_range(49152..=65535)
+    }
+
+    /// Replaces the allowed port ranges with the well-known port range `1..=1023`.
+    pub fn allow_only_well_known_ports(self) -> Result<Self, AddError> {
+        self.clear_allowed_port_ranges()
+            .add_allowed_port_range(1..=1023)
+    }
+
     /// Adds an IP range to the allowed IP ranges.
+    ///
+    /// Only an identical range is rejected, ranges overlapping other allowed or denied
+    /// ranges are accepted. An IP address in both an allowed and a denied range is denied,
+    /// unless the precedence is set to [`Precedence::AllowWins`].
     pub fn add_allowed_ip_range<Ip: IntoIpRange>(mut self, ip_range: Ip) -> Result<Self, AddError> {
         let ip_range = ip_range.into_range().ok_or(AddError::Invalid)?;
         if self.denied_ip_ranges.contains(&ip_range) {
@@ -898,7 +4836,20 @@ impl HttpAclBuilder {
         self
     }
 
+    /// Retains only the allowed IP ranges for which the predicate returns `true`.
+    pub fn retain_allowed_ip_ranges(
+        mut self,
+        f: impl FnMut(&RangeInclusive<IpAddr>) -> bool,
+    ) -> Self {
+        self.allowed_ip_ranges.retain(f);
+        self
+    }
+
     /// Adds an IP range to the denied IP ranges.
+    ///
+    /// Only an identical range is rejected, ranges overlapping other allowed or denied
+    /// ranges are accepted. An IP address in both an allowed and a denied range is denied,
+    /// unless the precedence is set to [`Precedence::AllowWins`].
     pub fn add_denied_ip_range<Ip: IntoIpRange>(mut self, ip_range: Ip) -> Result<Self, AddError> {
         let ip_range = ip_range.into_range().ok_or(AddError::Invalid)?;
         if self.allowed_ip_ranges.contains(&ip_range) {
@@ -910,6 +4861,26 @@ impl HttpAclBuilder {
         Ok(self)
     }
 
+    /// Adds an IP range to the denied IP ranges until an expiry, after which it is ignored.
+    ///
+    /// Adding a range already expiring replaces its expiry. Expired ranges are kept until
+    /// [`HttpAcl::prune_expired`] is called. While any expiring range is present, every IP check
+    /// reads the clock, which is [`Instant::now`] unless set with [`HttpAclBuilder::clock`].
+    pub fn add_denied_ip_range_until<Ip: IntoIpRange>(
+        mut self,
+        ip_range: Ip,
+        expiry: Instant,
+    ) -> Result<Self, AddError> {
+        let ip_range = ip_range.into_range().ok_or(AddError::Invalid)?;
+        if self.allowed_ip_ranges.contains(&ip_range) {
+            return Err(AddError::AlreadyAllowed);
+        }
+        self.expiring_denied_ip_ranges
+            .retain(|(range, _)| *range != ip_range);
+        self.expiring_denied_ip_ranges.push((ip_range, expiry));
+        Ok(self)
+    }
+
     /// Removes an IP range from the denied IP ranges.
     pub fn remove_denied_ip_range<Ip: IntoIpRange>(
         mut self,
@@ -947,14 +4918,55 @@ impl HttpAclBuilder {
         self
     }
 
+    /// Retains only the denied IP ranges for which the predicate returns `true`.
+    pub fn retain_denied_ip_ranges(
+        mut self,
+        f: impl FnMut(&RangeInclusive<IpAddr>) -> bool,
+    ) -> Self {
+        self.denied_ip_ranges.retain(f);
+        self
+    }
+
+    /// Replaces the allowed and denied IP ranges with CIDR networks,
+    /// such as those exported by [`HttpAcl::export_ip_rules`].
+    pub fn import_ip_rules(
+        self,
+        allowed: Vec<IpNet>,
+        denied: Vec<IpNet>,
+    ) -> Result<Self, AddError> {
+        self.clear_allowed_ip_ranges()
+            .clear_denied_ip_ranges()
+            .allowed_ip_ranges(allowed)?
+            .denied_ip_ranges(denied)
+    }
+
     /// Add a static DNS mapping.
+    ///
+    /// The address is appended to any addresses already mapped to the host.
     pub fn add_static_dns_mapping(
-        mut self,
-        host: String,
+        self,
+        host: impl AsRef<str>,
         sock_addr: SocketAddr,
     ) -> Result<Self, AddError> {
-        if utils::authority::is_valid_host(&host) {
-            self.static_dns_mapping.insert(host, sock_addr);
+        self.add_static_dns_mappings(host, vec![sock_addr])
+    }
+
+    /// Add a static DNS mapping to multiple addresses.
+    ///
+    /// The addresses are appended to any addresses already mapped to the host.
+    pub fn add_static_dns_mappings(
+        mut self,
+        host: impl AsRef<str>,
+        sock_addrs: Vec<SocketAddr>,
+    ) -> Result<Self, AddError> {
+        let host = host.as_ref();
+        if utils::authority::is_valid_host(host) {
+            let mapped = self.static_dns_mapping.entry(host.to_string()).or_default();
+            for sock_addr in sock_addrs {
+                if !mapped.contains(&sock_addr) {
+                    mapped.push(sock_addr);
+                }
+            }
             Ok(self)
         } else {
             Err(AddError::Invalid)
@@ -970,11 +4982,12 @@ impl HttpAclBuilder {
     /// Sets the static DNS mappings.
     pub fn static_dns_mappings(
         mut self,
-        mappings: HashMap<String, SocketAddr>,
+        mappings: HashMap<String, Vec<SocketAddr>>,
     ) -> Result<Self, AddError> {
-        for (host, ip) in &mappings {
+        for (host, sock_addrs) in &mappings {
             if utils::authority::is_valid_host(host) {
-                self.static_dns_mapping.insert(host.to_string(), *ip);
+                self.static_dns_mapping
+                    .insert(host.to_string(), sock_addrs.clone());
             } else {
                 return Err(AddError::Invalid);
             }
@@ -988,21 +5001,38 @@ impl HttpAclBuilder {
         self
     }
 
+    /// Sets whether static DNS mappings are authoritative.
+    ///
+    /// When enabled, a host with a static DNS mapping never falls through to DNS,
+    /// even if none of its mapped addresses are allowed by the ACL.
+    pub fn static_mappings_authoritative(mut self, authoritative: bool) -> Self {
+        self.static_mappings_authoritative = authoritative;
+        self
+    }
+
+    /// Sets whether the kill switch is engaged, see [`HttpAcl::set_kill_switch`].
+    pub fn kill_switch(mut self, engaged: bool) -> Self {
+        self.kill_switch = engaged;
+        self
+    }
+
     /// Adds a URL path to the allowed URL paths.
-    pub fn add_allowed_url_path(mut self, url_path: String) -> Result<Self, AddError> {
+    pub fn add_allowed_url_path(mut self, url_path: impl AsRef<str>) -> Result<Self, AddError> {
+        let url_path = url_path.as_ref().to_string();
+        let key = self.url_path_key(&url_path);
         if self.denied_url_paths.contains(&url_path)
-            || self.denied_url_paths_router.at(&url_path).is_ok()
+            || self.denied_url_paths_router.at(&key).is_ok()
         {
             Err(AddError::AlreadyDenied)
         } else if self.allowed_url_paths.contains(&url_path)
-            || self.allowed_url_paths_router.at(&url_path).is_ok()
+            || self.allowed_url_paths_router.at(&key).is_ok()
         {
             Err(AddError::AlreadyAllowed)
         } else {
-            self.allowed_url_paths.push(url_path.clone());
             self.allowed_url_paths_router
-                .insert(url_path, ())
+                .insert(key, url_path.as_str().into())
                 .map_err(|_| AddError::Invalid)?;
+            self.allowed_url_paths.push(url_path);
             Ok(self)
         }
     }
@@ -1010,34 +5040,27 @@ impl HttpAclBuilder {
     /// Removes a URL path from the allowed URL paths.
     pub fn remove_allowed_url_path(mut self, url_path: &str) -> Self {
         self.allowed_url_paths.retain(|p| p != url_path);
-        self.allowed_url_paths_router = {
-            let mut router = Router::new();
-            for url_path in &self.allowed_url_paths {
-                router
-                    .insert(url_path.clone(), ())
-                    .expect("failed to insert url path");
-            }
-            router
-        };
+        self.allowed_url_paths_router = self.url_paths_router(&self.allowed_url_paths);
         self
     }
 
     /// Sets the allowed URL paths.
     pub fn allowed_url_paths(mut self, url_paths: Vec<String>) -> Result<Self, AddError> {
         for url_path in &url_paths {
+            let key = self.url_path_key(url_path);
             if self.denied_url_paths.contains(url_path)
-                || self.denied_url_paths_router.at(url_path).is_ok()
+                || self.denied_url_paths_router.at(&key).is_ok()
             {
                 return Err(AddError::AlreadyDenied);
             } else if self.allowed_url_paths.contains(url_path)
-                || self.allowed_url_paths_router.at(url_path).is_ok()
+                || self.allowed_url_paths_router.at(&key).is_ok()
             {
                 return Err(AddError::AlreadyAllowed);
             }
         }
         for url_path in &url_paths {
             self.allowed_url_paths_router
-                .insert(url_path.clone(), ())
+                .insert(self.url_path_key(url_path), url_path.as_str().into())
                 .map_err(|_| AddError::Invalid)?;
         }
         self.allowed_url_paths = url_paths;
@@ -1052,20 +5075,22 @@ impl HttpAclBuilder {
     }
 
     /// Adds a URL path to the denied URL paths.
-    pub fn add_denied_url_path(mut self, url_path: String) -> Result<Self, AddError> {
+    pub fn add_denied_url_path(mut self, url_path: impl AsRef<str>) -> Result<Self, AddError> {
+        let url_path = url_path.as_ref().to_string();
+        let key = self.url_path_key(&url_path);
         if self.allowed_url_paths.contains(&url_path)
-            || self.allowed_url_paths_router.at(&url_path).is_ok()
+            || self.allowed_url_paths_router.at(&key).is_ok()
         {
             Err(AddError::AlreadyAllowed)
         } else if self.denied_url_paths.contains(&url_path)
-            || self.denied_url_paths_router.at(&url_path).is_ok()
+            || self.denied_url_paths_router.at(&key).is_ok()
         {
             Err(AddError::AlreadyDenied)
         } else {
-            self.denied_url_paths.push(url_path.clone());
             self.denied_url_paths_router
-                .insert(url_path, ())
+                .insert(key, url_path.as_str().into())
                 .map_err(|_| AddError::Invalid)?;
+            self.denied_url_paths.push(url_path);
             Ok(self)
         }
     }
@@ -1073,34 +5098,27 @@ impl HttpAclBuilder {
     /// Removes a URL path from the denied URL paths.
     pub fn remove_denied_url_path(mut self, url_path: &str) -> Self {
         self.denied_url_paths.retain(|p| p != url_path);
-        self.denied_url_paths_router = {
-            let mut router = Router::new();
-            for url_path in &self.denied_url_paths {
-                router
-                    .insert(url_path.clone(), ())
-                    .expect("failed to insert url path");
-            }
-            router
-        };
+        self.denied_url_paths_router = self.url_paths_router(&self.denied_url_paths);
         self
     }
 
     /// Sets the denied URL paths.
     pub fn denied_url_paths(mut self, url_paths: Vec<String>) -> Result<Self, AddError> {
         for url_path in &url_paths {
+            let key = self.url_path_key(url_path);
             if self.allowed_url_paths.contains(url_path)
-                || self.allowed_url_paths_router.at(url_path).is_ok()
+                || self.allowed_url_paths_router.at(&key).is_ok()
             {
                 return Err(AddError::AlreadyAllowed);
             } else if self.denied_url_paths.contains(url_path)
-                || self.denied_url_paths_router.at(url_path).is_ok()
+                || self.denied_url_paths_router.at(&key).is_ok()
             {
                 return Err(AddError::AlreadyDenied);
             }
         }
         for url_path in &url_paths {
             self.denied_url_paths_router
-                .insert(url_path.clone(), ())
+                .insert(self.url_path_key(url_path), url_path.as_str().into())
                 .map_err(|_| AddError::Invalid)?;
         }
         self.denied_url_paths = url_paths;
@@ -1114,146 +5132,1086 @@ impl HttpAclBuilder {
         self
     }
 
+    /// Adds a URL path to the allowed URL paths for a method.
+    pub fn add_allowed_url_path_for_method(
+        mut self,
+        method: impl Into<HttpRequestMethod>,
+        url_path: impl AsRef<str>,
+    ) -> Result<Self, AddError> {
+        let url_path = url_path.as_ref().to_string();
+        let key = self.url_path_key(&url_path);
+        let method = method.into();
+        let denied_router = self.denied_method_url_paths_routers.get(&method);
+        let allowed_router = self.allowed_method_url_paths_routers.get(&method);
+        if denied_router.is_some_and(|router| router.at(&key).is_ok())
+            || self
+                .denied_method_url_paths
+                .get(&method)
+                .is_some_and(|url_paths| url_paths.contains(&url_path))
+        {
+            Err(AddError::AlreadyDenied)
+        } else if allowed_router.is_some_and(|router| router.at(&key).is_ok())
+            || self
+                .allowed_method_url_paths
+                .get(&method)
+                .is_some_and(|url_paths| url_paths.contains(&url_path))
+        {
+            Err(AddError::AlreadyAllowed)
+        } else {
+            self.allowed_method_url_paths_routers
+                .entry(method.clone())
+                .or_default()
+                .insert(key, url_path.as_str().into())
+                .map_err(|_| AddError::Invalid)?;
+            self.allowed_method_url_paths
+                .entry(method)
+                .or_default()
+                .push(url_path);
+            Ok(self)
+        }
+    }
+
+    /// Removes a URL path from the allowed URL paths for a method.
+    pub fn remove_allowed_url_path_for_method(
+        mut self,
+        method: impl Into<HttpRequestMethod>,
+        url_path: &str,
+    ) -> Self {
+        let method = method.into();
+        if let Some(url_paths) = self.allowed_method_url_paths.get_mut(&method) {
+            url_paths.retain(|p| p != url_path);
+        }
+        if let Some(url_paths) = self.allowed_method_url_paths.get(&method) {
+            let router = self.url_paths_router(url_paths);
+            self.allowed_method_url_paths_routers.insert(method, router);
+        }
+        self
+    }
+
+    /// Clears the allowed URL paths for every method.
+    pub fn clear_allowed_url_paths_for_methods(mut self) -> Self {
+        self.allowed_method_url_paths.clear();
+        self.allowed_method_url_paths_routers.clear();
+        self
+    }
+
+    /// Adds a URL path to the denied URL paths for a method.
+    pub fn add_denied_url_path_for_method(
+        mut self,
+        method: impl Into<HttpRequestMethod>,
+        url_path: impl AsRef<str>,
+    ) -> Result<Self, AddError> {
+        let url_path = url_path.as_ref().to_string();
+        let key = self.url_path_key(&url_path);
+        let method = method.into();
+        let allowed_router = self.allowed_method_url_paths_routers.get(&method);
+        let denied_router = self.denied_method_url_paths_routers.get(&method);
+        if allowed_router.is_some_and(|router| router.at(&key).is_ok())
+            || self
+                .allowed_method_url_paths
+                .get(&method)
+                .is_some_and(|url_paths| url_paths.contains(&url_path))
+        {
+            Err(AddError::AlreadyAllowed)
+        } else if denied_router.is_some_and(|router| router.at(&key).is_ok())
+            || self
+                .denied_method_url_paths
+                .get(&method)
+                .is_some_and(|url_paths| url_paths.contains(&url_path))
+        {
+            Err(AddError::AlreadyDenied)
+        } else {
+            self.denied_method_url_paths_routers
+                .entry(method.clone())
+                .or_default()
+                .insert(key, url_path.as_str().into())
+                .map_err(|_| AddError::Invalid)?;
+            self.denied_method_url_paths
+                .entry(method)
+                .or_default()
+                .push(url_path);
+            Ok(self)
+        }
+    }
+
+    /// Removes a URL path from the denied URL paths for a method.
+    pub fn remove_denied_url_path_for_method(
+        mut self,
+        method: impl Into<HttpRequestMethod>,
+        url_path: &str,
+    ) -> Self {
+        let method = method.into();
+        if let Some(url_paths) = self.denied_method_url_paths.get_mut(&method) {
+            url_paths.retain(|p| p != url_path);
+        }
+        if let Some(url_paths) = self.denied_method_url_paths.get(&method) {
+            let router = self.url_paths_router(url_paths);
+            self.denied_method_url_paths_routers.insert(method, router);
+        }
+        self
+    }
+
+    /// Clears the denied URL paths for every method.
+    pub fn clear_denied_url_paths_for_methods(mut self) -> Self {
+        self.denied_method_url_paths.clear();
+        self.denied_method_url_paths_routers.clear();
+        self
+    }
+
+    /// Returns the number of allowed methods.
+    pub fn allowed_method_count(&self) -> usize {
+        self.allowed_methods.len()
+    }
+
+    /// Returns the number of denied methods.
+    pub fn denied_method_count(&self) -> usize {
+        self.denied_methods.len()
+    }
+
+    /// Returns the number of allowed hosts.
+    pub fn allowed_host_count(&self) -> usize {
+        self.allowed_hosts.len()
+    }
+
+    /// Returns the number of denied hosts.
+    pub fn denied_host_count(&self) -> usize {
+        self.denied_hosts.len()
+    }
+
+    /// Returns the number of allowed port ranges.
+    pub fn allowed_port_range_count(&self) -> usize {
+        self.allowed_port_ranges.len()
+    }
+
+    /// Returns the number of denied port ranges.
+    pub fn denied_port_range_count(&self) -> usize {
+        self.denied_port_ranges.len()
+    }
+
+    /// Returns the number of allowed IP ranges.
+    pub fn allowed_ip_range_count(&self) -> usize {
+        self.allowed_ip_ranges.len()
+    }
+
+    /// Returns the number of denied IP ranges.
+    pub fn denied_ip_range_count(&self) -> usize {
+        self.denied_ip_ranges.len()
+    }
+
+    /// Returns the number of allowed URL paths.
+    ///
+    /// URL paths specific to a method are not included.
+    pub fn allowed_url_path_count(&self) -> usize {
+        self.allowed_url_paths.len()
+    }
+
+    /// Returns the number of denied URL paths.
+    ///
+    /// URL paths specific to a method are not included.
+    pub fn denied_url_path_count(&self) -> usize {
+        self.denied_url_paths.len()
+    }
+
+    /// Returns the number of allowed headers.
+    pub fn allowed_header_count(&self) -> usize {
+        self.allowed_headers.len()
+    }
+
+    /// Returns the number of denied headers.
+    pub fn denied_header_count(&self) -> usize {
+        self.denied_headers.len()
+    }
+
+    /// Returns the number of denied query substrings.
+    pub fn denied_query_substring_count(&self) -> usize {
+        self.denied_query_substrings.len()
+    }
+
+    /// Returns the warnings about rules that have no effect given the defaults.
+    ///
+    /// An allowed rule is redundant if its dimension allows by default and has no denied rules,
+    /// and a denied rule is redundant if its dimension denies by default and has no allowed rules.
+    /// Allowed IP ranges are never redundant, as they also allow non-global IP addresses.
+    pub fn warnings(&self) -> Vec<AclWarning> {
+        let mut warnings = Vec::new();
+        let mut redundant =
+            |dimension: Dimension, default: bool, allowed: Vec<String>, denied: Vec<String>| {
+                let rules = match (default, allowed.is_empty(), denied.is_empty()) {
+                    (true, false, true) => allowed,
+                    (false, true, false) => denied,
+                    _ => Vec::new(),
+                };
+                warnings.extend(
+                    rules
+                        .into_iter()
+                        .map(|rule| AclWarning::RedundantRule { dimension, rule }),
+                );
+            };
+
+        redundant(
+            Dimension::Method,
+            self.method_acl_default,
+            self.allowed_methods
+                .iter()
+                .map(|method| method.as_str().to_string())
+                .collect(),
+            self.denied_methods
+                .iter()
+                .map(|method| method.as_str().to_string())
+                .collect(),
+        );
+        redundant(
+            Dimension::Host,
+            self.host_acl_default,
+            self.allowed_hosts.clone(),
+            self.denied_hosts.clone(),
+        );
+        redundant(
+            Dimension::Port,
+            self.port_acl_default,
+            Self::format_ranges(&self.allowed_port_ranges),
+            Self::format_ranges(&self.denied_port_ranges),
+        );
+        redundant(
+            Dimension::Ip,
+            self.ip_acl_default,
+            Vec::new(),
+            if self.allowed_ip_ranges.is_empty() {
+                Self::format_ranges(&self.denied_ip_ranges)
+            } else {
+                Vec::new()
+            },
+        );
+        redundant(
+            Dimension::UrlPath,
+            self.url_path_acl_default,
+            self.allowed_url_paths.clone(),
+            self.denied_url_paths.clone(),
+        );
+        redundant(
+            Dimension::Header,
+            self.header_acl_default,
+            Self::format_headers(&self.allowed_headers),
+            Self::format_headers(&self.denied_headers),
+        );
+
+        warnings
+    }
+
+    /// Normalizes the builder into a canonical form and returns its warnings.
+    ///
+    /// Hosts are lowercased, port and IP ranges are sorted with overlapping ranges merged,
+    /// duplicate entries are removed from the lists and the URL path routers are rebuilt.
+    pub fn normalize(&mut self) -> Vec<AclWarning> {
+        for host in self
+            .allowed_hosts
+            .iter_mut()
+            .chain(self.denied_hosts.iter_mut())
+        {
+            host.make_ascii_lowercase();
+        }
+
+        utils::dedup(&mut self.allowed_methods);
+        utils::dedup(&mut self.denied_methods);
+        utils::dedup(&mut self.allowed_hosts);
+        utils::dedup(&mut self.denied_hosts);
+        utils::dedup(&mut self.required_headers);
+        utils::dedup(&mut self.denied_query_substrings);
+        utils::dedup(&mut self.allowed_url_paths);
+        utils::dedup(&mut self.denied_url_paths);
+        for values in self.denied_header_values.values_mut() {
+            utils::dedup(values);
+        }
+        for matchers in self
+            .allowed_header_matchers
+            .values_mut()
+            .chain(self.denied_header_matchers.values_mut())
+        {
+            utils::dedup(matchers);
+        }
+        for url_paths in self
+            .allowed_method_url_paths
+            .values_mut()
+            .chain(self.denied_method_url_paths.values_mut())
+        {
+            utils::dedup(url_paths);
+        }
+
+        self.allowed_port_ranges = RangeIndex::new(&self.allowed_port_ranges).into_ranges();
+        self.denied_port_ranges = RangeIndex::new(&self.denied_port_ranges).into_ranges();
+        self.allowed_ip_ranges = RangeIndex::new(&self.allowed_ip_ranges).into_ranges();
+        self.denied_ip_ranges = RangeIndex::new(&self.denied_ip_ranges).into_ranges();
+
+        self.rebuild_url_path_routers();
+
+        self.warnings()
+    }
+
+    /// Formats ranges as `start-end` rules.
+    fn format_ranges<T: std::fmt::Display>(ranges: &[RangeInclusive<T>]) -> Vec<String> {
+        ranges
+            .iter()
+            .map(|range| format!("{}-{}", range.start(), range.end()))
+            .collect()
+    }
+
+    /// Formats header rules as `name: value` or `name` rules, sorted by name.
+    fn format_headers(headers: &HashMap<String, Option<String>>) -> Vec<String> {
+        let mut rules = headers
+            .iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("{}: {}", name, value),
+                None => name.clone(),
+            })
+            .collect::<Vec<_>>();
+        rules.sort();
+        rules
+    }
+
     /// Builds the [`HttpAcl`].
     pub fn build(self) -> HttpAcl {
+        self.build_full(None)
+    }
+
+    /// Builds the [`HttpAcl`] with an async validate function.
+    ///
+    /// Only one of the validate function and the async validate function can be set,
+    /// use [`HttpAclBuilder::build_full`] for a synchronous validate function.
+    pub fn build_full_async(self, async_validate_fn: AsyncValidateFn) -> HttpAcl {
+        HttpAcl {
+            async_validate_fn: Some(async_validate_fn),
+            ..self.build_full(None)
+        }
+    }
+
+    /// Builds the [`HttpAcl`] with an optional validate function.
+    ///
+    /// Only one of the validate function and the async validate function can be set,
+    /// use [`HttpAclBuilder::build_full_async`] for an async validate function.
+    ///
+    /// Port and IP ranges are sorted by their start, so they may be added in any order.
+    pub fn build_full(mut self, validate_fn: Option<ValidateFn>) -> HttpAcl {
+        self.allowed_port_ranges.sort_by_key(|range| *range.start());
+        self.denied_port_ranges.sort_by_key(|range| *range.start());
+        self.allowed_ip_ranges.sort_by_key(|range| *range.start());
+        self.denied_ip_ranges.sort_by_key(|range| *range.start());
+        self.rebuild_url_path_routers();
+
         HttpAcl {
             allow_http: self.allow_http,
             allow_https: self.allow_https,
+            allowed_method_index: MethodIndex::new(&self.allowed_methods),
+            denied_method_index: MethodIndex::new(&self.denied_methods),
             allowed_methods: self.allowed_methods,
             denied_methods: self.denied_methods,
+            allowed_host_index: HostIndex::new(&self.allowed_hosts),
+            denied_host_index: HostIndex::new(&self.denied_hosts),
+            #[cfg(feature = "regex")]
+            allowed_host_regexes: Self::host_regexes(&self.allowed_host_regexes),
+            #[cfg(feature = "regex")]
+            denied_host_regexes: Self::host_regexes(&self.denied_host_regexes),
             allowed_hosts: self.allowed_hosts,
             denied_hosts: self.denied_hosts,
+            allowed_authorities: Self::authorities(&self.allowed_authorities),
+            denied_authorities: Self::authorities(&self.denied_authorities),
             allowed_port_ranges: self.allowed_port_ranges,
             denied_port_ranges: self.denied_port_ranges,
+            allowed_ip_index: RangeIndex::new(&self.allowed_ip_ranges),
+            denied_ip_index: RangeIndex::new(&self.denied_ip_ranges),
             allowed_ip_ranges: self.allowed_ip_ranges,
             denied_ip_ranges: self.denied_ip_ranges,
+            expiring_denied_ip_ranges: self.expiring_denied_ip_ranges,
             allowed_url_paths: self.allowed_url_paths,
             allowed_url_paths_router: self.allowed_url_paths_router,
             denied_url_paths: self.denied_url_paths,
             denied_url_paths_router: self.denied_url_paths_router,
+            allowed_method_url_paths: self.allowed_method_url_paths,
+            allowed_method_url_paths_routers: self.allowed_method_url_paths_routers,
+            denied_method_url_paths: self.denied_method_url_paths,
+            denied_method_url_paths_routers: self.denied_method_url_paths_routers,
+            decode_url_paths: self.decode_url_paths,
+            normalize_url_paths: self.normalize_url_paths,
+            strip_trailing_slashes: self.strip_trailing_slashes,
             static_dns_mapping: self.static_dns_mapping,
+            static_mappings_authoritative: self.static_mappings_authoritative,
+            kill_switch: self.kill_switch,
+            allowed_headers: self.allowed_headers,
+            denied_headers: self.denied_headers,
+            denied_header_values: self.denied_header_values,
+            allowed_header_matchers: self.allowed_header_matchers,
+            denied_header_matchers: self.denied_header_matchers,
+            required_headers: self.required_headers,
+            max_header_count: self.max_header_count,
+            max_total_header_bytes: self.max_total_header_bytes,
+            allowed_rules: self.allowed_rules,
+            denied_query_substrings: self.denied_query_substrings,
+            max_query_length: self.max_query_length,
             allow_private_ip_ranges: self.allow_private_ip_ranges,
+            allow_loopback_ip_addresses: self.allow_loopback_ip_addresses,
+            allow_link_local_ip_addresses: self.allow_link_local_ip_addresses,
+            allow_unspecified_ip_addresses: self.allow_unspecified_ip_addresses,
+            ip_family_mode: self.ip_family_mode,
+            wildcard_includes_apex: self.wildcard_includes_apex,
+            precedence: self.precedence,
             method_acl_default: self.method_acl_default,
             host_acl_default: self.host_acl_default,
             port_acl_default: self.port_acl_default,
             ip_acl_default: self.ip_acl_default,
             url_path_acl_default: self.url_path_acl_default,
+            header_acl_default: self.header_acl_default,
+            scheme_acl_default: self.scheme_acl_default,
+            validate_fn,
+            async_validate_fn: None,
+            on_decision: self.on_decision,
+            clock: self.clock,
         }
     }
 
     /// Builds the [`HttpAcl`] and returns an error if the configuration is invalid.
-    /// This is used for deserialized ACLs as the URL Path Routers need to be built.
-    pub fn try_build(mut self) -> Result<HttpAcl, AddError> {
+    /// This is used for deserialized ACLs, whose rules have not been validated when added.
+    pub fn try_build(self) -> Result<HttpAcl, AddError> {
+        self.try_build_full(None)
+    }
+
+    /// Builds the [`HttpAcl`] with an async validate function and returns an error if the configuration is invalid.
+    /// This is used for deserialized ACLs, whose rules have not been validated when added.
+    pub fn try_build_full_async(
+        self,
+        async_validate_fn: AsyncValidateFn,
+    ) -> Result<HttpAcl, AddError> {
+        Ok(HttpAcl {
+            async_validate_fn: Some(async_validate_fn),
+            ..self.try_build_full(None)?
+        })
+    }
+
+    /// Builds the [`HttpAcl`] with an optional validate function and returns an error if the configuration is invalid.
+    /// This is used for deserialized ACLs, whose rules have not been validated when added.
+    pub fn try_build_full(self, validate_fn: Option<ValidateFn>) -> Result<HttpAcl, AddError> {
+        self.validate()?;
+        Ok(self.build_full(validate_fn))
+    }
+
+    /// Checks the configuration without consuming the builder, returning the first error
+    /// [`HttpAclBuilder::try_build`] would return.
+    pub fn validate(&self) -> Result<(), AddError> {
+        match self.validation_errors(true).into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Builds the [`HttpAcl`] and returns every error of the configuration if it is invalid.
+    ///
+    /// Unlike [`HttpAclBuilder::try_build`], which stops at the first error, all the rules
+    /// are checked, which is useful to report every problem of a configuration loaded from a file.
+    pub fn try_build_collect(self) -> Result<HttpAcl, Vec<AddError>> {
+        let errors = self.validation_errors(false);
+        if errors.is_empty() {
+            Ok(self.build_full(None))
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validates the rules, returning the errors found.
+    ///
+    /// If `fail_fast` is set, the validation stops at the first error.
+    fn validation_errors(&self, fail_fast: bool) -> Vec<AddError> {
+        let mut errors = Vec::new();
+        macro_rules! fail {
+            ($err:expr) => {{
+                errors.push($err);
+                if fail_fast {
+                    return errors;
+                }
+            }};
+        }
+
+        let mut allowed_url_paths_router = Router::<Box<str>>::new();
+        let mut denied_url_paths_router = Router::<Box<str>>::new();
+        let mut allowed_method_url_paths_routers = HashMap::new();
+
+        #[cfg(feature = "regex")]
+        for pattern in self
+            .allowed_host_regexes
+            .iter()
+            .chain(&self.denied_host_regexes)
+        {
+            if let Err(err) = Self::host_regex(pattern) {
+                fail!(err);
+            }
+        }
+
         if !utils::has_unique_elements(&self.allowed_methods) {
-            return Err(AddError::AlreadyAllowed);
+            fail!(AddError::AlreadyAllowed);
         }
         for method in &self.allowed_methods {
             if self.denied_methods.contains(method) {
-                return Err(AddError::AlreadyDenied);
+                fail!(AddError::AlreadyDenied);
             }
         }
         if !utils::has_unique_elements(&self.denied_methods) {
-            return Err(AddError::AlreadyDenied);
+            fail!(AddError::AlreadyDenied);
         }
         for method in &self.denied_methods {
             if self.allowed_methods.contains(method) {
-                return Err(AddError::AlreadyAllowed);
+                fail!(AddError::AlreadyAllowed);
             }
         }
         if !utils::has_unique_elements(&self.allowed_hosts) {
-            return Err(AddError::AlreadyAllowed);
+            fail!(AddError::AlreadyAllowed);
         }
         for host in &self.allowed_hosts {
-            if utils::authority::is_valid_host(host) {
-                return Err(AddError::Invalid);
+            if !utils::authority::is_valid_host(host) {
+                fail!(AddError::Invalid);
             }
             if self.denied_hosts.contains(host) {
-                return Err(AddError::AlreadyDenied);
+                fail!(AddError::AlreadyDenied);
             }
         }
         if !utils::has_unique_elements(&self.denied_hosts) {
-            return Err(AddError::AlreadyDenied);
+            fail!(AddError::AlreadyDenied);
         }
         for host in &self.denied_hosts {
-            if utils::authority::is_valid_host(host) {
-                return Err(AddError::Invalid);
+            if !utils::authority::is_valid_host(host) {
+                fail!(AddError::Invalid);
             }
             if self.allowed_hosts.contains(host) {
-                return Err(AddError::AlreadyAllowed);
+                fail!(AddError::AlreadyAllowed);
+            }
+        }
+        let mut authorities = |authorities: &[String]| {
+            authorities
+                .iter()
+                .filter_map(|authority| {
+                    Self::authority(authority)
+                        .map_err(|err| errors.push(err))
+                        .ok()
+                })
+                .collect::<Vec<_>>()
+        };
+        let allowed_authorities = authorities(&self.allowed_authorities);
+        let denied_authorities = authorities(&self.denied_authorities);
+        if fail_fast && !errors.is_empty() {
+            return errors;
+        }
+        if !utils::has_unique_elements(&allowed_authorities) {
+            fail!(AddError::AlreadyAllowed);
+        }
+        if !utils::has_unique_elements(&denied_authorities) {
+            fail!(AddError::AlreadyDenied);
+        }
+        if allowed_authorities
+            .iter()
+            .any(|authority| denied_authorities.contains(authority))
+        {
+            fail!(AddError::AlreadyDenied);
+        }
+        for (header, value) in &self.allowed_headers {
+            if self.denied_headers.get(header) == Some(value) {
+                fail!(AddError::AlreadyDenied);
+            }
+        }
+        if !utils::has_unique_elements(&self.required_headers) {
+            fail!(AddError::AlreadyAllowed);
+        }
+        for (header, values) in &self.denied_header_values {
+            if values.is_empty() {
+                fail!(AddError::Invalid);
+            }
+            if !utils::has_unique_elements(values) {
+                fail!(AddError::AlreadyDenied);
+            }
+            if let Some(Some(allowed)) = self.allowed_headers.get(header) {
+                if values.contains(allowed) {
+                    fail!(AddError::AlreadyAllowed);
+                }
+            }
+        }
+        for (header, matchers) in &self.allowed_header_matchers {
+            if !utils::has_unique_elements(matchers) {
+                fail!(AddError::AlreadyAllowed);
+            }
+            let denied = self.denied_header_matchers.get(header);
+            if matchers
+                .iter()
+                .any(|matcher| denied.is_some_and(|denied| denied.contains(matcher)))
+            {
+                fail!(AddError::AlreadyDenied);
+            }
+        }
+        for matchers in self.denied_header_matchers.values() {
+            if !utils::has_unique_elements(matchers) {
+                fail!(AddError::AlreadyDenied);
+            }
+        }
+        if !utils::has_unique_elements(&self.allowed_rules) {
+            fail!(AddError::AlreadyAllowed);
+        }
+        for rule in &self.allowed_rules {
+            if !utils::authority::is_valid_host(&rule.host) {
+                fail!(AddError::Invalid);
             }
         }
         if !utils::has_unique_elements(&self.allowed_port_ranges) {
-            return Err(AddError::AlreadyAllowed);
+            fail!(AddError::AlreadyAllowed);
         }
         for port_range in &self.allowed_port_ranges {
+            if port_range.is_empty() {
+                fail!(AddError::Invalid);
+            }
             if self.denied_port_ranges.contains(port_range) {
-                return Err(AddError::AlreadyDenied);
+                fail!(AddError::AlreadyDenied);
             }
         }
         if !utils::has_unique_elements(&self.denied_port_ranges) {
-            return Err(AddError::AlreadyDenied);
+            fail!(AddError::AlreadyDenied);
         }
         for port_range in &self.denied_port_ranges {
+            if port_range.is_empty() {
+                fail!(AddError::Invalid);
+            }
             if self.allowed_port_ranges.contains(port_range) {
-                return Err(AddError::AlreadyAllowed);
+                fail!(AddError::AlreadyAllowed);
             }
         }
         if !utils::has_unique_elements(&self.allowed_ip_ranges) {
-            return Err(AddError::AlreadyAllowed);
+            fail!(AddError::AlreadyAllowed);
         }
         for ip_range in &self.allowed_ip_ranges {
             if self.denied_ip_ranges.contains(ip_range) {
-                return Err(AddError::AlreadyDenied);
+                fail!(AddError::AlreadyDenied);
             }
         }
         if !utils::has_unique_elements(&self.denied_ip_ranges) {
-            return Err(AddError::AlreadyDenied);
+            fail!(AddError::AlreadyDenied);
         }
         for ip_range in &self.denied_ip_ranges {
             if self.allowed_ip_ranges.contains(ip_range) {
-                return Err(AddError::AlreadyAllowed);
+                fail!(AddError::AlreadyAllowed);
             }
         }
-        if !utils::has_unique_elements(&self.static_dns_mapping) {
-            return Err(AddError::AlreadyAllowed);
-        }
-        for host in self.static_dns_mapping.keys() {
+        for (host, sock_addrs) in &self.static_dns_mapping {
             if !utils::authority::is_valid_host(host) {
-                return Err(AddError::Invalid);
+                fail!(AddError::Invalid);
+            }
+            if !utils::has_unique_elements(sock_addrs) {
+                fail!(AddError::AlreadyAllowed);
             }
         }
         if !utils::has_unique_elements(&self.allowed_url_paths) {
-            return Err(AddError::AlreadyAllowed);
+            fail!(AddError::AlreadyAllowed);
         }
         for url_path in &self.allowed_url_paths {
-            if self.denied_url_paths.contains(url_path)
-                || self.denied_url_paths_router.at(url_path).is_ok()
+            let key = self.url_path_key(url_path);
+            if self.denied_url_paths.contains(url_path) || denied_url_paths_router.at(&key).is_ok()
             {
-                return Err(AddError::AlreadyDenied);
-            } else if self.allowed_url_paths_router.at(url_path).is_ok() {
-                return Err(AddError::AlreadyAllowed);
-            } else {
-                self.allowed_url_paths_router
-                    .insert(url_path.clone(), ())
-                    .map_err(|_| AddError::Invalid)?;
+                fail!(AddError::AlreadyDenied);
+            } else if allowed_url_paths_router.at(&key).is_ok() {
+                fail!(AddError::AlreadyAllowed);
+            } else if allowed_url_paths_router
+                .insert(key.clone(), url_path.as_str().into())
+                .is_err()
+            {
+                fail!(AddError::Invalid);
             }
         }
         if !utils::has_unique_elements(&self.denied_url_paths) {
-            return Err(AddError::AlreadyDenied);
+            fail!(AddError::AlreadyDenied);
         }
         for url_path in &self.denied_url_paths {
+            let key = self.url_path_key(url_path);
             if self.allowed_url_paths.contains(url_path)
-                || self.allowed_url_paths_router.at(url_path).is_ok()
+                || allowed_url_paths_router.at(&key).is_ok()
             {
-                return Err(AddError::AlreadyAllowed);
-            } else if self.denied_url_paths_router.at(url_path).is_ok() {
-                return Err(AddError::AlreadyDenied);
-            } else {
-                self.denied_url_paths_router
-                    .insert(url_path.clone(), ())
-                    .map_err(|_| AddError::Invalid)?;
+                fail!(AddError::AlreadyAllowed);
+            } else if denied_url_paths_router.at(&key).is_ok() {
+                fail!(AddError::AlreadyDenied);
+            } else if denied_url_paths_router
+                .insert(key.clone(), url_path.as_str().into())
+                .is_err()
+            {
+                fail!(AddError::Invalid);
+            }
+        }
+        for (method, url_paths) in &self.allowed_method_url_paths {
+            if !utils::has_unique_elements(url_paths) {
+                fail!(AddError::AlreadyAllowed);
+            }
+            let denied_url_paths = self.denied_method_url_paths.get(method);
+            let mut router = Router::<Box<str>>::new();
+            for url_path in url_paths {
+                let key = self.url_path_key(url_path);
+                if denied_url_paths.is_some_and(|denied| denied.contains(url_path)) {
+                    fail!(AddError::AlreadyDenied);
+                } else if router.at(&key).is_ok() {
+                    fail!(AddError::AlreadyAllowed);
+                }
+                if router.insert(key, url_path.as_str().into()).is_err() {
+                    fail!(AddError::Invalid);
+                }
+            }
+            allowed_method_url_paths_routers.insert(method.clone(), router);
+        }
+        for (method, url_paths) in &self.denied_method_url_paths {
+            if !utils::has_unique_elements(url_paths) {
+                fail!(AddError::AlreadyDenied);
+            }
+            let allowed_router = allowed_method_url_paths_routers.get(method);
+            let mut router = Router::<Box<str>>::new();
+            for url_path in url_paths {
+                let key = self.url_path_key(url_path);
+                if allowed_router.is_some_and(|allowed| allowed.at(&key).is_ok()) {
+                    fail!(AddError::AlreadyAllowed);
+                } else if router.at(&key).is_ok() {
+                    fail!(AddError::AlreadyDenied);
+                }
+                if router.insert(key, url_path.as_str().into()).is_err() {
+                    fail!(AddError::Invalid);
+                }
             }
         }
-        Ok(self.build())
+        errors
+    }
+
+    /// Builds a URL path router from a list of URL paths, skipping the invalid URL paths.
+    fn url_paths_router(&self, url_paths: &[String]) -> Router<Box<str>> {
+        let mut router = Router::new();
+        for url_path in url_paths {
+            let _ = router.insert(self.url_path_key(url_path), url_path.as_str().into());
+        }
+        router
+    }
+
+    /// Returns the key of a URL path rule in the URL path routers, normalized the same
+    /// way as the URL paths of requests.
+    fn url_path_key(&self, url_path: &str) -> String {
+        let mut key = if self.normalize_url_paths {
+            utils::url::normalize_url_path(url_path)
+        } else {
+            url_path.to_string()
+        };
+        if self.strip_trailing_slashes {
+            key.truncate(utils::url::strip_trailing_slash(&key).len());
+        }
+        key
+    }
+
+    /// Rebuilds the URL path routers from the URL paths, after the URL path rules or
+    /// their normalization changed.
+    fn rebuild_url_path_routers(&mut self) {
+        self.allowed_url_paths_router = self.url_paths_router(&self.allowed_url_paths);
+        self.denied_url_paths_router = self.url_paths_router(&self.denied_url_paths);
+        self.allowed_method_url_paths_routers =
+            self.method_url_paths_routers(&self.allowed_method_url_paths);
+        self.denied_method_url_paths_routers =
+            self.method_url_paths_routers(&self.denied_method_url_paths);
+    }
+
+    /// Builds the URL path router of each method, skipping the invalid URL paths.
+    fn method_url_paths_routers(
+        &self,
+        method_url_paths: &HashMap<HttpRequestMethod, Vec<String>>,
+    ) -> HashMap<HttpRequestMethod, Router<Box<str>>> {
+        method_url_paths
+            .iter()
+            .map(|(method, url_paths)| (method.clone(), self.url_paths_router(url_paths)))
+            .collect()
+    }
+}
+
+/// A serializable configuration of an [`HttpAcl`].
+///
+/// Converting a configuration into an [`HttpAcl`] always validates the rules.
+/// Missing fields take the defaults of [`HttpAclBuilder::new`].
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpAclConfig {
+    /// Whether HTTP is allowed.
+    pub allow_http: bool,
+    /// Whether HTTPS is allowed.
+    pub allow_https: bool,
+    /// The allowed methods.
+    pub allowed_methods: Vec<HttpRequestMethod>,
+    /// The denied methods.
+    pub denied_methods: Vec<HttpRequestMethod>,
+    /// The allowed hosts.
+    pub allowed_hosts: Vec<String>,
+    /// The denied hosts.
+    pub denied_hosts: Vec<String>,
+    /// The allowed authorities, hosts with an optional port.
+    pub allowed_authorities: Vec<String>,
+    /// The denied authorities, hosts with an optional port.
+    pub denied_authorities: Vec<String>,
+    /// The regular expressions of the allowed hosts.
+    #[cfg(feature = "regex")]
+    pub allowed_host_regexes: Vec<String>,
+    /// The regular expressions of the denied hosts.
+    #[cfg(feature = "regex")]
+    pub denied_host_regexes: Vec<String>,
+    /// The allowed port ranges.
+    pub allowed_port_ranges: Vec<RangeInclusive<u16>>,
+    /// The denied port ranges.
+    pub denied_port_ranges: Vec<RangeInclusive<u16>>,
+    /// The allowed IP ranges.
+    pub allowed_ip_ranges: Vec<RangeInclusive<IpAddr>>,
+    /// The denied IP ranges.
+    pub denied_ip_ranges: Vec<RangeInclusive<IpAddr>>,
+    /// The static DNS mappings.
+    pub static_dns_mapping: HashMap<String, Vec<SocketAddr>>,
+    /// Whether static DNS mappings are authoritative.
+    pub static_mappings_authoritative: bool,
+    /// Whether the kill switch is engaged, denying every check.
+    pub kill_switch: bool,
+    /// The allowed headers.
+    pub allowed_headers: HashMap<String, Option<String>>,
+    /// The denied headers.
+    pub denied_headers: HashMap<String, Option<String>>,
+    /// The denied value sets of headers.
+    pub denied_header_values: HashMap<String, Vec<String>>,
+    /// The allowed header matchers.
+    pub allowed_header_matchers: HashMap<String, Vec<HeaderMatcher>>,
+    /// The denied header matchers.
+    pub denied_header_matchers: HashMap<String, Vec<HeaderMatcher>>,
+    /// The required headers.
+    pub required_headers: Vec<String>,
+    /// The maximum number of headers.
+    pub max_header_count: Option<usize>,
+    /// The maximum total size of header names and values in bytes.
+    pub max_total_header_bytes: Option<usize>,
+    /// The allowed compound rules.
+    pub allowed_rules: Vec<CompoundRule>,
+    /// The denied query substrings.
+    pub denied_query_substrings: Vec<String>,
+    /// The maximum length of a URL query in bytes.
+    pub max_query_length: Option<usize>,
+    /// The allowed URL paths.
+    pub allowed_url_paths: Vec<String>,
+    /// The denied URL paths.
+    pub denied_url_paths: Vec<String>,
+    /// The allowed URL paths for each method.
+    pub allowed_method_url_paths: HashMap<HttpRequestMethod, Vec<String>>,
+    /// The denied URL paths for each method.
+    pub denied_method_url_paths: HashMap<HttpRequestMethod, Vec<String>>,
+    /// Whether URL paths are percent-decoded before matching.
+    pub decode_url_paths: bool,
+    /// Whether URL paths are normalized before matching.
+    pub normalize_url_paths: bool,
+    /// Whether trailing slashes are stripped from URL paths before matching.
+    pub strip_trailing_slashes: bool,
+    /// Whether private IP ranges are allowed.
+    pub allow_private_ip_ranges: bool,
+    /// Whether loopback IP addresses are allowed.
+    pub allow_loopback_ip_addresses: bool,
+    /// Whether link-local IP addresses are allowed.
+    pub allow_link_local_ip_addresses: bool,
+    /// Whether the unspecified IP addresses are allowed.
+    pub allow_unspecified_ip_addresses: bool,
+    /// The allowed IP families.
+    pub ip_family_mode: IpFamilyMode,
+    /// Whether wildcard hosts also match their apex domain.
+    pub wildcard_includes_apex: bool,
+    /// The precedence of a header matching both an allowed and a denied rule.
+    pub precedence: Precedence,
+    /// The default action for methods if no ACL match is found.
+    pub method_acl_default: bool,
+    /// The default action for hosts if no ACL match is found.
+    pub host_acl_default: bool,
+    /// The default action for ports if no ACL match is found.
+    pub port_acl_default: bool,
+    /// The default action for IPs if no ACL match is found.
+    pub ip_acl_default: bool,
+    /// The default action for URL paths if no ACL match is found.
+    pub url_path_acl_default: bool,
+    /// The default action for headers if no ACL match is found.
+    pub header_acl_default: bool,
+    /// The default action for schemes other than HTTP and HTTPS.
+    pub scheme_acl_default: bool,
+}
+
+#[cfg(feature = "serde")]
+impl Default for HttpAclConfig {
+    fn default() -> Self {
+        HttpAclBuilder::new().into()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<HttpAclBuilder> for HttpAclConfig {
+    fn from(builder: HttpAclBuilder) -> Self {
+        Self {
+            allow_http: builder.allow_http,
+            allow_https: builder.allow_https,
+            allowed_methods: builder.allowed_methods,
+            denied_methods: builder.denied_methods,
+            allowed_hosts: builder.allowed_hosts,
+            denied_hosts: builder.denied_hosts,
+            allowed_authorities: builder.allowed_authorities,
+            denied_authorities: builder.denied_authorities,
+            #[cfg(feature = "regex")]
+            allowed_host_regexes: builder.allowed_host_regexes,
+            #[cfg(feature = "regex")]
+            denied_host_regexes: builder.denied_host_regexes,
+            allowed_port_ranges: builder.allowed_port_ranges,
+            denied_port_ranges: builder.denied_port_ranges,
+            allowed_ip_ranges: builder.allowed_ip_ranges,
+            denied_ip_ranges: builder.denied_ip_ranges,
+            static_dns_mapping: builder.static_dns_mapping,
+            static_mappings_authoritative: builder.static_mappings_authoritative,
+            kill_switch: builder.kill_switch,
+            allowed_headers: builder.allowed_headers,
+            denied_headers: builder.denied_headers,
+            denied_header_values: builder.denied_header_values,
+            allowed_header_matchers: builder.allowed_header_matchers,
+            denied_header_matchers: builder.denied_header_matchers,
+            required_headers: builder.required_headers,
+            max_header_count: builder.max_header_count,
+            max_total_header_bytes: builder.max_total_header_bytes,
+            allowed_rules: builder.allowed_rules,
+            denied_query_substrings: builder.denied_query_substrings,
+            max_query_length: builder.max_query_length,
+            allowed_url_paths: builder.allowed_url_paths,
+            denied_url_paths: builder.denied_url_paths,
+            allowed_method_url_paths: builder.allowed_method_url_paths,
+            denied_method_url_paths: builder.denied_method_url_paths,
+            decode_url_paths: builder.decode_url_paths,
+            normalize_url_paths: builder.normalize_url_paths,
+            strip_trailing_slashes: builder.strip_trailing_slashes,
+            allow_private_ip_ranges: builder.allow_private_ip_ranges,
+            allow_loopback_ip_addresses: builder.allow_loopback_ip_addresses,
+            allow_link_local_ip_addresses: builder.allow_link_local_ip_addresses,
+            allow_unspecified_ip_addresses: builder.allow_unspecified_ip_addresses,
+            ip_family_mode: builder.ip_family_mode,
+            wildcard_includes_apex: builder.wildcard_includes_apex,
+            precedence: builder.precedence,
+            method_acl_default: builder.method_acl_default,
+            host_acl_default: builder.host_acl_default,
+            port_acl_default: builder.port_acl_default,
+            ip_acl_default: builder.ip_acl_default,
+            url_path_acl_default: builder.url_path_acl_default,
+            header_acl_default: builder.header_acl_default,
+            scheme_acl_default: builder.scheme_acl_default,
+        }
+    }
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+impl HttpAclBuilder {
+    /// Parses an [`HttpAclConfig`] from a TOML string and builds the [`HttpAcl`], validating the rules.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> Result<HttpAcl, LoadError> {
+        let config =
+            toml::from_str::<HttpAclConfig>(toml).map_err(|e| LoadError::Parse(e.to_string()))?;
+        Ok(HttpAcl::try_from(config)?)
+    }
+
+    /// Parses an [`HttpAclConfig`] from a JSON string and builds the [`HttpAcl`], validating the rules.
+    #[cfg(feature = "json")]
+    pub fn from_json_str(json: &str) -> Result<HttpAcl, LoadError> {
+        let config = serde_json::from_str::<HttpAclConfig>(json)
+            .map_err(|e| LoadError::Parse(e.to_string()))?;
+        Ok(HttpAcl::try_from(config)?)
+    }
+
+    /// Reads an [`HttpAclConfig`] from a TOML file and builds the [`HttpAcl`], validating the rules.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_file(path: impl AsRef<std::path::Path>) -> Result<HttpAcl, LoadError> {
+        Self::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Reads an [`HttpAclConfig`] from a JSON file and builds the [`HttpAcl`], validating the rules.
+    #[cfg(feature = "json")]
+    pub fn from_json_file(path: impl AsRef<std::path::Path>) -> Result<HttpAcl, LoadError> {
+        Self::from_json_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Reads an [`HttpAclConfig`] from a file and builds the [`HttpAcl`], validating the rules.
+    ///
+    /// The format is detected from the `.toml` or `.json` extension of the file.
+    pub fn from_config_file(path: impl AsRef<std::path::Path>) -> Result<HttpAcl, LoadError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            #[cfg(feature = "toml")]
+            "toml" => Self::from_toml_file(path),
+            #[cfg(feature = "json")]
+            "json" => Self::from_json_file(path),
+            _ => Err(LoadError::UnsupportedFormat(extension)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<HttpAclConfig> for HttpAcl {
+    type Error = AddError;
+
+    fn try_from(config: HttpAclConfig) -> Result<Self, Self::Error> {
+        HttpAclBuilder {
+            allow_http: config.allow_http,
+            allow_https: config.allow_https,
+            allowed_methods: config.allowed_methods,
+            denied_methods: config.denied_methods,
+            allowed_hosts: config.allowed_hosts,
+            denied_hosts: config.denied_hosts,
+            allowed_authorities: config.allowed_authorities,
+            denied_authorities: config.denied_authorities,
+            #[cfg(feature = "regex")]
+            allowed_host_regexes: config.allowed_host_regexes,
+            #[cfg(feature = "regex")]
+            denied_host_regexes: config.denied_host_regexes,
+            allowed_port_ranges: config.allowed_port_ranges,
+            denied_port_ranges: config.denied_port_ranges,
+            allowed_ip_ranges: config.allowed_ip_ranges,
+            denied_ip_ranges: config.denied_ip_ranges,
+            expiring_denied_ip_ranges: Vec::new(),
+            static_dns_mapping: config.static_dns_mapping,
+            static_mappings_authoritative: config.static_mappings_authoritative,
+            kill_switch: config.kill_switch,
+            allowed_headers: config.allowed_headers,
+            denied_headers: config.denied_headers,
+            denied_header_values: config.denied_header_values,
+            allowed_header_matchers: config.allowed_header_matchers,
+            denied_header_matchers: config.denied_header_matchers,
+            required_headers: config.required_headers,
+            max_header_count: config.max_header_count,
+            max_total_header_bytes: config.max_total_header_bytes,
+            allowed_rules: config.allowed_rules,
+            denied_query_substrings: config.denied_query_substrings,
+            max_query_length: config.max_query_length,
+            allowed_url_paths: config.allowed_url_paths,
+            denied_url_paths: config.denied_url_paths,
+            allowed_method_url_paths: config.allowed_method_url_paths,
+            denied_method_url_paths: config.denied_method_url_paths,
+            decode_url_paths: config.decode_url_paths,
+            normalize_url_paths: config.normalize_url_paths,
+            strip_trailing_slashes: config.strip_trailing_slashes,
+            allow_private_ip_ranges: config.allow_private_ip_ranges,
+            allow_loopback_ip_addresses: config.allow_loopback_ip_addresses,
+            allow_link_local_ip_addresses: config.allow_link_local_ip_addresses,
+            allow_unspecified_ip_addresses: config.allow_unspecified_ip_addresses,
+            ip_family_mode: config.ip_family_mode,
+            wildcard_includes_apex: config.wildcard_includes_apex,
+            precedence: config.precedence,
+            method_acl_default: config.method_acl_default,
+            host_acl_default: config.host_acl_default,
+            port_acl_default: config.port_acl_default,
+            ip_acl_default: config.ip_acl_default,
+            url_path_acl_default: config.url_path_acl_default,
+            header_acl_default: config.header_acl_default,
+            scheme_acl_default: config.scheme_acl_default,
+            on_decision: None,
+            clock: None,
+            allowed_url_paths_router: Router::new(),
+            denied_url_paths_router: Router::new(),
+            allowed_method_url_paths_routers: HashMap::new(),
+            denied_method_url_paths_routers: HashMap::new(),
+        }
+        .try_build()
     }
 }
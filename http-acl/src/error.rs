@@ -15,4 +15,43 @@ pub enum AddError {
     /// The entity is not allowed or denied because it is invalid.
     #[error("The entity is not allowed or denied because it is invalid.")]
     Invalid,
+    /// The named entity is not allowed or denied because it is invalid.
+    #[error("The entity `{0}` is not allowed or denied because it is invalid.")]
+    InvalidEntity(String),
+    /// The named entity is already allowed so it cannot be denied.
+    #[error("The entity `{0}` is already allowed so it cannot be denied.")]
+    AlreadyAllowedEntity(String),
+    /// The named entity is already denied so it cannot be allowed.
+    #[error("The entity `{0}` is already denied so it cannot be allowed.")]
+    AlreadyDeniedEntity(String),
+}
+
+impl AddError {
+    /// Names the entity of an error that does not name one yet.
+    pub(crate) fn with_entity(self, entity: &str) -> Self {
+        match self {
+            AddError::AlreadyAllowed => AddError::AlreadyAllowedEntity(entity.to_string()),
+            AddError::AlreadyDenied => AddError::AlreadyDeniedEntity(entity.to_string()),
+            AddError::Invalid => AddError::InvalidEntity(entity.to_string()),
+            err => err,
+        }
+    }
+}
+
+/// Represents an error that can occur when loading an ACL from a configuration file.
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum LoadError {
+    /// The configuration file could not be read.
+    #[error("The configuration file could not be read: {0}")]
+    Io(#[from] std::io::Error),
+    /// The configuration could not be parsed.
+    #[error("The configuration could not be parsed: {0}")]
+    Parse(String),
+    /// The configuration file format is not supported.
+    #[error("The configuration file format `{0}` is not supported.")]
+    UnsupportedFormat(String),
+    /// The configuration contains an invalid rule.
+    #[error(transparent)]
+    Add(#[from] AddError),
 }
@@ -5,24 +5,44 @@ pub use ipnet::IpNet;
 
 pub mod acl;
 pub mod error;
+pub mod live;
+#[cfg(feature = "tower")]
+pub mod predicate;
 pub mod utils;
 
-pub use acl::{HttpAcl, HttpAclBuilder, HttpRequestMethod};
+#[cfg(feature = "serde")]
+pub use acl::HttpAclConfig;
+pub use acl::{
+    AclDiff, AclRule, AclWarning, AsyncValidateFn, ClockFn, CompoundRule, DecisionFn, Dimension,
+    HeaderMatcher, HeaderPolicy, HttpAcl, HttpAclBuilder, HttpRequestMethod, IpFamilyMode,
+    Precedence, RequestParts, RuleCounts, ShadowedRule, ValidateFn, ValidationReport,
+};
+pub use live::LiveHttpAcl;
+#[cfg(feature = "tower")]
+pub use predicate::{AclDenied, AclPredicate};
 pub use utils::IntoIpRange;
 
 #[cfg(test)]
 mod tests {
-    use super::HttpAclBuilder;
+    use std::sync::Arc;
+
+    use super::acl::AclClassification;
+    use super::error::AddError;
+    use super::utils::authority::Authority;
+    use super::{
+        AclWarning, CompoundRule, Dimension, HeaderMatcher, HeaderPolicy, HttpAclBuilder,
+        HttpRequestMethod, IpFamilyMode, Precedence, RequestParts,
+    };
     use ipnet::IpNet;
 
     #[test]
     fn acl() {
         let acl = HttpAclBuilder::new()
-            .add_allowed_host("example.com".to_string())
+            .add_allowed_host("example.com")
             .unwrap()
-            .add_allowed_host("example.org".to_string())
+            .add_allowed_host("example.org")
             .unwrap()
-            .add_denied_host("example.net".to_string())
+            .add_denied_host("example.net")
             .unwrap()
             .add_allowed_port_range(8080..=8080)
             .unwrap()
@@ -46,14 +66,84 @@ mod tests {
             .is_denied());
     }
 
+    #[test]
+    fn other_method_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_method(HttpRequestMethod::OTHER("PURGE".to_string()))
+            .unwrap()
+            .add_denied_method("mkcol")
+            .unwrap()
+            .build();
+
+        assert!(acl.is_method_allowed("purge").is_allowed());
+        assert!(acl.is_method_allowed("Purge").is_allowed());
+        assert!(acl.is_method_allowed("MKCOL").is_denied());
+        assert_eq!(
+            HttpRequestMethod::from("purge").as_str(),
+            "purge",
+            "the original casing is preserved"
+        );
+        assert!(HttpAclBuilder::new()
+            .add_allowed_method("PURGE")
+            .unwrap()
+            .add_denied_method("purge")
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn http_method_conversion() {
+        assert_eq!(
+            HttpRequestMethod::from(&http::Method::PATCH),
+            HttpRequestMethod::PATCH
+        );
+        assert_eq!(
+            HttpRequestMethod::from(&http::Method::from_bytes(b"MKCOL").unwrap()),
+            HttpRequestMethod::OTHER("MKCOL".to_string())
+        );
+
+        let acl = HttpAclBuilder::new()
+            .remove_allowed_method("PATCH")
+            .add_denied_method("PATCH")
+            .unwrap()
+            .build();
+        assert!(acl.is_method_allowed(&http::Method::PATCH).is_denied());
+    }
+
+    #[test]
+    fn lowercase_method_acl() {
+        assert_eq!(HttpRequestMethod::from("get"), HttpRequestMethod::GET);
+        assert_eq!(HttpRequestMethod::from("Get"), HttpRequestMethod::GET);
+        assert_eq!(HttpRequestMethod::from("patch"), HttpRequestMethod::PATCH);
+        assert_eq!(
+            HttpRequestMethod::from("mkcol"),
+            HttpRequestMethod::OTHER("mkcol".to_string())
+        );
+        assert_eq!(HttpRequestMethod::from("mkcol").as_str(), "mkcol");
+
+        let acl = HttpAclBuilder::new()
+            .remove_allowed_method("DELETE")
+            .add_denied_method("DELETE")
+            .unwrap()
+            .build();
+        assert_eq!(
+            acl.is_method_allowed("delete"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_method_allowed("get"),
+            AclClassification::AllowedUserAcl
+        );
+    }
+
     #[test]
     fn host_acl() {
         let acl = HttpAclBuilder::new()
-            .add_allowed_host("example.com".to_string())
+            .add_allowed_host("example.com")
             .unwrap()
-            .add_allowed_host("example.org".to_string())
+            .add_allowed_host("example.org")
             .unwrap()
-            .add_denied_host("example.net".to_string())
+            .add_denied_host("example.net")
             .unwrap()
             .build();
 
@@ -62,6 +152,34 @@ mod tests {
         assert!(!acl.is_host_allowed("example.net").is_allowed());
     }
 
+    #[test]
+    fn wildcard_host_acl() {
+        let builder = HttpAclBuilder::new()
+            .add_allowed_host("*.example.com")
+            .unwrap()
+            .add_denied_host("*.internal.example.com")
+            .unwrap();
+
+        let acl = builder.clone().build();
+
+        assert!(acl.is_host_allowed("www.example.com").is_allowed());
+        assert!(acl.is_host_allowed("a.b.example.com").is_allowed());
+        assert!(acl.is_host_allowed("example.com").is_denied());
+        assert!(acl.is_host_allowed("notexample.com").is_denied());
+        assert!(acl.is_host_allowed("db.internal.example.com").is_denied());
+        assert!(acl.is_host_allowed("internal.example.com").is_allowed());
+        assert_eq!(
+            acl.explain(Dimension::Host, "db.internal.example.com"),
+            "host db.internal.example.com is denied by the host rule `*.internal.example.com`."
+        );
+
+        let acl = builder.wildcard_includes_apex(true).build();
+
+        assert!(acl.is_host_allowed("www.example.com").is_allowed());
+        assert!(acl.is_host_allowed("example.com").is_allowed());
+        assert!(acl.is_host_allowed("internal.example.com").is_denied());
+    }
+
     #[test]
     fn port_acl() {
         let acl = HttpAclBuilder::new()
@@ -76,6 +194,43 @@ mod tests {
         assert!(!acl.is_port_allowed(8443).is_allowed());
     }
 
+    #[test]
+    fn min_max_allowed_port() {
+        let acl = HttpAclBuilder::new()
+            .clear_allowed_port_ranges()
+            .add_allowed_port_range(8000..=8999)
+            .unwrap()
+            .add_allowed_port_range(443..=443)
+            .unwrap()
+            .add_allowed_port_range(10000..=10100)
+            .unwrap()
+            .add_denied_port_range(10050..=10100)
+            .unwrap()
+            .build();
+
+        assert_eq!(acl.min_allowed_port(), Some(443));
+        assert_eq!(acl.max_allowed_port(), Some(10049));
+
+        let acl = HttpAclBuilder::new()
+            .clear_allowed_port_ranges()
+            .port_acl_default(true)
+            .add_denied_port_range(0..=1023)
+            .unwrap()
+            .build();
+
+        assert_eq!(acl.min_allowed_port(), Some(1024));
+        assert_eq!(acl.max_allowed_port(), Some(u16::MAX));
+
+        let acl = HttpAclBuilder::new().clear_allowed_port_ranges().build();
+
+        assert_eq!(acl.min_allowed_port(), None);
+        assert_eq!(acl.max_allowed_port(), None);
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let result = HttpAclBuilder::new().add_allowed_port_range(9000..=8000);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn ip_acl() {
         let acl = HttpAclBuilder::new()
@@ -105,6 +260,59 @@ mod tests {
             .is_allowed());
     }
 
+    #[test]
+    fn unspecified_ip_acl() {
+        let acl = HttpAclBuilder::new()
+            .private_ip_ranges(true)
+            .ip_acl_default(true)
+            .add_allowed_ip_range("0.0.0.0/8".parse::<IpNet>().unwrap())
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.is_ip_allowed(&"0.0.0.0".parse().unwrap()),
+            AclClassification::DeniedNotGlobal
+        );
+        assert!(acl.is_ip_allowed(&"0.0.0.1".parse().unwrap()).is_allowed());
+
+        let acl = HttpAclBuilder::new()
+            .unspecified_ip_addresses(true)
+            .add_allowed_ip_range("0.0.0.0/8".parse::<IpNet>().unwrap())
+            .unwrap()
+            .build();
+
+        assert!(acl.is_ip_allowed(&"0.0.0.0".parse().unwrap()).is_allowed());
+    }
+
+    #[test]
+    fn ip_family_mode_acl() {
+        let builder = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .private_ip_ranges(true);
+
+        let acl = builder.clone().build();
+        assert!(acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()).is_allowed());
+        assert!(acl
+            .is_ip_allowed(&"2606:4700::1111".parse().unwrap())
+            .is_allowed());
+
+        let acl = builder.clone().ip_family_mode(IpFamilyMode::V4Only).build();
+        assert!(acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()).is_allowed());
+        assert_eq!(
+            acl.is_ip_allowed(&"2606:4700::1111".parse().unwrap()),
+            AclClassification::Denied("IPv6 addresses are disabled".to_string())
+        );
+
+        let acl = builder.ip_family_mode(IpFamilyMode::V6Only).build();
+        assert_eq!(
+            acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()),
+            AclClassification::Denied("IPv4 addresses are disabled".to_string())
+        );
+        assert!(acl
+            .is_ip_allowed(&"2606:4700::1111".parse().unwrap())
+            .is_allowed());
+    }
+
     #[test]
     fn default_ip_acl() {
         let acl = HttpAclBuilder::new().build();
@@ -119,13 +327,13 @@ mod tests {
     #[test]
     fn url_path_acl() {
         let acl = HttpAclBuilder::new()
-            .add_allowed_url_path("/allowed".to_string())
+            .add_allowed_url_path("/allowed")
             .unwrap()
-            .add_allowed_url_path("/allowed/:id".to_string())
+            .add_allowed_url_path("/allowed/:id")
             .unwrap()
-            .add_denied_url_path("/denied".to_string())
+            .add_denied_url_path("/denied")
             .unwrap()
-            .add_denied_url_path("/denied/{*path}".to_string())
+            .add_denied_url_path("/denied/{*path}")
             .unwrap()
             .build();
 
@@ -135,4 +343,3100 @@ mod tests {
         assert!(acl.is_url_path_allowed("/denied/denied").is_denied());
         assert!(acl.is_url_path_allowed("/denied/denied/denied").is_denied());
     }
+
+    #[test]
+    fn url_path_for_method_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_url_path_for_method("GET", "/admin")
+            .unwrap()
+            .add_denied_url_path_for_method("POST", "/admin")
+            .unwrap()
+            .add_denied_url_path("/private")
+            .unwrap()
+            .build();
+
+        assert!(acl
+            .is_url_path_allowed_for_method("GET", "/admin")
+            .is_allowed());
+        assert!(acl
+            .is_url_path_allowed_for_method("POST", "/admin")
+            .is_denied());
+        assert!(acl
+            .is_url_path_allowed_for_method("POST", "/other")
+            .is_allowed());
+        assert!(acl
+            .is_url_path_allowed_for_method("GET", "/private")
+            .is_denied());
+    }
+
+    #[test]
+    fn url_path_normalization_acl() {
+        let acl = HttpAclBuilder::new()
+            .url_path_acl_default(false)
+            .add_allowed_url_path("/a")
+            .unwrap()
+            .add_denied_url_path("/b")
+            .unwrap()
+            .build();
+
+        assert!(acl.is_url_path_allowed("/a/../b").is_denied());
+        assert!(acl.is_url_path_allowed("//a").is_allowed());
+        assert!(acl.is_url_path_allowed("/a/").is_allowed());
+
+        let acl = HttpAclBuilder::new()
+            .url_path_acl_default(false)
+            .normalize_url_paths(false)
+            .strip_trailing_slashes(false)
+            .add_allowed_url_path("/a")
+            .unwrap()
+            .build();
+
+        assert!(acl.is_url_path_allowed("/a").is_allowed());
+        assert!(acl.is_url_path_allowed("/a/").is_denied());
+        assert!(acl.is_url_path_allowed("//a").is_denied());
+    }
+
+    #[test]
+    fn trailing_slash_url_path_rule_acl() {
+        let acl = HttpAclBuilder::new()
+            .url_path_acl_default(true)
+            .add_denied_url_path("/admin/")
+            .unwrap()
+            .add_denied_url_path_for_method("POST", "/users//")
+            .unwrap()
+            .build();
+        assert!(acl.is_url_path_allowed("/admin/").is_denied());
+        assert!(acl.is_url_path_allowed("/admin").is_denied());
+        assert!(acl
+            .is_url_path_allowed_for_method("POST", "/users/")
+            .is_denied());
+        assert_eq!(
+            acl.matching_url_path("/admin"),
+            Some(("/admin/".to_string(), false))
+        );
+        assert!(HttpAclBuilder::new()
+            .add_allowed_url_path("/admin")
+            .unwrap()
+            .add_denied_url_path("/admin/")
+            .is_err());
+
+        let acl = HttpAclBuilder::new()
+            .url_path_acl_default(true)
+            .add_denied_url_path("/admin/")
+            .unwrap()
+            .strip_trailing_slashes(false)
+            .build();
+        assert!(!acl.strip_trailing_slashes());
+        assert!(acl.is_url_path_allowed("/admin/").is_denied());
+        assert!(acl.is_url_path_allowed("/a/../admin/").is_denied());
+        assert!(acl.is_url_path_allowed("/admin").is_allowed());
+    }
+
+    #[test]
+    fn url_path_decoding_acl() {
+        let acl = HttpAclBuilder::new()
+            .url_path_acl_default(false)
+            .add_denied_url_path("/admin")
+            .unwrap()
+            .add_allowed_url_path("/files/{name}")
+            .unwrap()
+            .build();
+
+        assert!(acl.is_url_path_allowed("/%61dmin").is_denied());
+        assert!(acl.is_url_path_allowed("/%61%64%6d%69%6e").is_denied());
+        assert!(acl.is_url_path_allowed("/files/%2e%2e/admin").is_denied());
+        assert!(acl.is_url_path_allowed("/%66iles/secret").is_allowed());
+        assert!(acl.is_url_path_allowed("/files/a%2Fb").is_allowed());
+        assert!(acl.is_url_path_allowed("/files/a/b").is_denied());
+        assert!(acl
+            .is_url_path_allowed("/files/public%2F..%2Fsecret")
+            .is_allowed());
+
+        let acl = HttpAclBuilder::new()
+            .decode_url_paths(false)
+            .add_denied_url_path("/admin")
+            .unwrap()
+            .build();
+
+        assert!(acl.is_url_path_allowed("/%61dmin").is_allowed());
+    }
+
+    #[test]
+    fn explain_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_denied_host("example.net")
+            .unwrap()
+            .add_denied_port_range(8000..=8999)
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.explain(Dimension::Host, "example.net"),
+            "host example.net is denied by the host rule `example.net`."
+        );
+        assert_eq!(
+            acl.explain(Dimension::Host, "example.com"),
+            "host example.com is allowed by the host rule `example.com`."
+        );
+        assert_eq!(
+            acl.explain(Dimension::Host, "example.org"),
+            "host example.org is denied by default as no host rule matched."
+        );
+        assert_eq!(
+            acl.explain(Dimension::Port, "8080"),
+            "port 8080 is denied by the port rule `8000-8999`."
+        );
+        assert_eq!(
+            acl.explain(Dimension::Ip, "10.0.0.1"),
+            "ip 10.0.0.1 is denied as it is in a private range."
+        );
+        assert_eq!(
+            acl.explain(Dimension::Port, "http"),
+            "port http is invalid."
+        );
+    }
+
+    #[test]
+    fn matching_url_path_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_url_path("/users/{id}")
+            .unwrap()
+            .add_denied_url_path("/admin/{*path}")
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.matching_url_path("/users/42"),
+            Some(("/users/{id}".to_string(), true))
+        );
+        assert_eq!(
+            acl.matching_url_path("/admin/users"),
+            Some(("/admin/{*path}".to_string(), false))
+        );
+        assert_eq!(acl.matching_url_path("/other"), None);
+        assert!(acl.is_url_path_allowed("/users/42").is_allowed());
+        assert_eq!(
+            acl.explain(Dimension::UrlPath, "/users/42"),
+            "path /users/42 is allowed by the path rule `/users/{id}`."
+        );
+    }
+
+    #[test]
+    fn static_dns_mapping_acl() {
+        let v4 = "93.184.215.14:443".parse().unwrap();
+        let v6 = "[2606:2800:21f:cb07:6820:80da:af6b:8b2c]:443"
+            .parse()
+            .unwrap();
+        let acl = HttpAclBuilder::new()
+            .add_static_dns_mapping("example.com", v4)
+            .unwrap()
+            .add_static_dns_mapping("example.com", v6)
+            .unwrap()
+            .add_static_dns_mappings("example.org", vec![v4, v6, v4])
+            .unwrap()
+            .build();
+
+        assert_eq!(acl.resolve_static_dns_mapping("example.com"), &[v4, v6]);
+        assert_eq!(acl.resolve_static_dns_mapping("example.org"), &[v4, v6]);
+        assert!(acl.resolve_static_dns_mapping("example.net").is_empty());
+    }
+
+    #[test]
+    fn validate_fn_acl() {
+        let acl = HttpAclBuilder::new().build_full(Some(Arc::new(
+            |scheme, _authority, method, port, mut headers, _body| {
+                if *method == HttpRequestMethod::POST && port == 22 {
+                    AclClassification::Denied("POST to port 22".to_string())
+                } else if scheme == "http" && headers.any(|(name, _)| name == "authorization") {
+                    AclClassification::Denied("credentials over http".to_string())
+                } else {
+                    AclClassification::AllowedUserAcl
+                }
+            },
+        )));
+        let authority = Authority::parse("example.com").unwrap();
+
+        assert_eq!(
+            acl.is_valid(
+                "http",
+                &authority,
+                &HttpRequestMethod::GET,
+                80,
+                Box::new([("authorization", "secret")].into_iter()),
+                None
+            ),
+            AclClassification::Denied("credentials over http".to_string())
+        );
+        assert!(acl
+            .is_valid(
+                "https",
+                &authority,
+                &HttpRequestMethod::GET,
+                443,
+                Box::new([("authorization", "secret")].into_iter()),
+                None
+            )
+            .is_allowed());
+        assert_eq!(
+            acl.is_valid(
+                "https",
+                &authority,
+                &HttpRequestMethod::POST,
+                22,
+                Box::new(std::iter::empty()),
+                None
+            ),
+            AclClassification::Denied("POST to port 22".to_string())
+        );
+        assert!(acl
+            .is_valid(
+                "https",
+                &authority,
+                &HttpRequestMethod::GET,
+                22,
+                Box::new(std::iter::empty()),
+                None
+            )
+            .is_allowed());
+    }
+
+    #[tokio::test]
+    async fn async_validate_fn_acl() {
+        let acl = HttpAclBuilder::new()
+            .host_acl_default(true)
+            .build_full_async(Arc::new(
+                |_scheme, authority, method, port, _headers, _body| {
+                    Box::pin(async move {
+                        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                        AclClassification::Denied(format!(
+                            "{} {} on port {} is not allowed",
+                            method.as_str(),
+                            authority,
+                            port
+                        ))
+                    })
+                },
+            ));
+        let authority = Authority::parse("example.com").unwrap();
+
+        assert_eq!(
+            acl.is_valid_async(
+                "https",
+                &authority,
+                &HttpRequestMethod::GET,
+                443,
+                Box::new(std::iter::empty()),
+                None
+            )
+            .await,
+            AclClassification::Denied("GET example.com on port 443 is not allowed".to_string())
+        );
+        assert_eq!(
+            acl.is_valid(
+                "https",
+                &authority,
+                &HttpRequestMethod::GET,
+                443,
+                Box::new(std::iter::empty()),
+                None
+            ),
+            AclClassification::Denied(
+                "async validate function requires is_valid_async".to_string()
+            )
+        );
+        assert_eq!(
+            acl.first_denial(&RequestParts {
+                scheme: "https",
+                method: "GET",
+                host: "example.com",
+                path: "/",
+                ..Default::default()
+            }),
+            Some((
+                Dimension::Request,
+                AclClassification::Denied(
+                    "async validate function requires is_valid_async".to_string()
+                )
+            ))
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn evaluate_uri_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_denied_port_range(8000..=8999)
+            .unwrap()
+            .add_denied_url_path("/admin")
+            .unwrap()
+            .build();
+
+        let report = acl.evaluate_uri(
+            &"https://example.com:8080/admin".parse().unwrap(),
+            &http::Method::GET,
+        );
+        assert!(report.is_denied());
+        assert_eq!(
+            report.get(Dimension::Scheme),
+            Some(&AclClassification::AllowedUserAcl)
+        );
+        assert_eq!(
+            report.get(Dimension::Host),
+            Some(&AclClassification::AllowedUserAcl)
+        );
+        assert_eq!(
+            report.get(Dimension::Port),
+            Some(&AclClassification::DeniedUserAcl)
+        );
+        assert_eq!(
+            report.get(Dimension::UrlPath),
+            Some(&AclClassification::DeniedUserAcl)
+        );
+        assert_eq!(
+            report
+                .denials()
+                .map(|(dimension, _)| *dimension)
+                .collect::<Vec<_>>(),
+            vec![Dimension::Port, Dimension::UrlPath]
+        );
+
+        let report = acl.evaluate_uri(&"example.com:443".parse().unwrap(), &http::Method::CONNECT);
+        assert!(report.is_allowed());
+        assert_eq!(report.get(Dimension::Scheme), None);
+        assert_eq!(report.get(Dimension::UrlPath), None);
+        assert_eq!(
+            report.get(Dimension::Port),
+            Some(&AclClassification::AllowedUserAcl)
+        );
+
+        let report = acl.evaluate_uri(&"1.1.1.1:22".parse().unwrap(), &http::Method::CONNECT);
+        assert_eq!(
+            report.get(Dimension::Ip),
+            Some(&AclClassification::DeniedDefault)
+        );
+        assert_eq!(report.get(Dimension::Host), None);
+    }
+
+    #[test]
+    fn header_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_header("X-Allowed", None)
+            .unwrap()
+            .add_denied_header("X-Denied", Some("bad".to_string()))
+            .unwrap()
+            .header_acl_default(false)
+            .build();
+
+        assert!(acl.is_header_allowed("x-allowed", "anything").is_allowed());
+        assert!(acl.is_header_allowed("X-Denied", "bad").is_denied());
+        assert_eq!(
+            acl.is_header_allowed("x-denied", "good"),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            acl.explain(Dimension::Header, "X-Denied: bad"),
+            "header X-Denied: bad is denied by the header rule `x-denied: bad`."
+        );
+    }
+
+    #[test]
+    fn header_precedence_acl() {
+        let builder = HttpAclBuilder::new()
+            .add_allowed_header("x-debug", None)
+            .unwrap()
+            .add_denied_header("x-debug", Some("1".to_string()))
+            .unwrap();
+
+        let acl = builder.clone().build();
+        assert_eq!(acl.precedence(), Precedence::DenyWins);
+        assert!(acl.is_header_allowed("x-debug", "1").is_denied());
+        assert!(acl.is_header_allowed("x-debug", "0").is_allowed());
+
+        let acl = builder.precedence(Precedence::AllowWins).build();
+        assert!(acl.is_header_allowed("x-debug", "1").is_allowed());
+        assert!(acl.is_header_allowed("x-debug", "0").is_allowed());
+    }
+
+    #[test]
+    fn evaluate_acl() {
+        let acl = HttpAclBuilder::new()
+            .host_acl_default(true)
+            .remove_allowed_method("DELETE")
+            .add_denied_method("DELETE")
+            .unwrap()
+            .add_denied_header("x-debug", None)
+            .unwrap()
+            .build();
+
+        let report = acl.evaluate(&RequestParts {
+            scheme: "https",
+            method: "DELETE",
+            host: "example.com",
+            port: Some(8080),
+            path: "/",
+            query: None,
+            headers: &[("accept", "*/*"), ("x-debug", "1")],
+        });
+        assert!(report.is_denied());
+        assert_eq!(
+            report.denials().cloned().collect::<Vec<_>>(),
+            vec![
+                (Dimension::Method, AclClassification::DeniedUserAcl),
+                (Dimension::Port, AclClassification::DeniedDefault),
+                (Dimension::Header, AclClassification::DeniedUserAcl),
+            ]
+        );
+
+        let report = acl.evaluate(&RequestParts {
+            scheme: "https",
+            method: "GET",
+            host: "[2606:4700:4700::1111]",
+            path: "/",
+            ..Default::default()
+        });
+        assert_eq!(
+            report.get(Dimension::Port),
+            Some(&AclClassification::AllowedUserAcl)
+        );
+        assert_eq!(
+            report.get(Dimension::Ip),
+            Some(&AclClassification::DeniedDefault)
+        );
+        assert_eq!(report.get(Dimension::Host), None);
+    }
+
+    #[test]
+    fn builder_counts() {
+        let builder = HttpAclBuilder::new()
+            .clear_allowed_methods()
+            .add_allowed_method("GET")
+            .unwrap()
+            .add_denied_method("DELETE")
+            .unwrap()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_host("example.org")
+            .unwrap()
+            .add_denied_host("example.net")
+            .unwrap()
+            .add_denied_port_range(8000..=8999)
+            .unwrap()
+            .add_allowed_ip_range("1.0.0.0/8".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_ip_range("9.0.0.0/8".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_ip_range("10.0.0.0/8".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_url_path("/api/{*path}")
+            .unwrap()
+            .add_denied_header("x-debug", None)
+            .unwrap()
+            .remove_allowed_host("example.org")
+            .remove_denied_ip_range("10.0.0.0/8".parse::<IpNet>().unwrap())
+            .unwrap();
+
+        assert_eq!(builder.allowed_method_count(), 1);
+        assert_eq!(builder.denied_method_count(), 1);
+        assert_eq!(builder.allowed_host_count(), 1);
+        assert_eq!(builder.denied_host_count(), 1);
+        assert_eq!(builder.allowed_port_range_count(), 2);
+        assert_eq!(builder.denied_port_range_count(), 1);
+        assert_eq!(builder.allowed_ip_range_count(), 1);
+        assert_eq!(builder.denied_ip_range_count(), 1);
+        assert_eq!(builder.allowed_url_path_count(), 1);
+        assert_eq!(builder.denied_url_path_count(), 0);
+        assert_eq!(builder.allowed_header_count(), 0);
+        assert_eq!(builder.denied_header_count(), 1);
+    }
+
+    #[test]
+    fn first_denial_acl() {
+        let acl = HttpAclBuilder::new()
+            .http(false)
+            .host_acl_default(true)
+            .build_full(Some(Arc::new(
+                |_scheme, _authority, _method, _port, _headers, _body| {
+                    AclClassification::Denied("validate function".to_string())
+                },
+            )));
+
+        let request = RequestParts {
+            scheme: "http",
+            method: "GET",
+            host: "example.com",
+            port: Some(8080),
+            path: "/",
+            ..Default::default()
+        };
+        assert_eq!(
+            acl.first_denial(&request),
+            Some((Dimension::Scheme, AclClassification::DeniedUserAcl))
+        );
+        assert_eq!(
+            acl.evaluate(&request)
+                .denials()
+                .map(|(dimension, _)| *dimension)
+                .collect::<Vec<_>>(),
+            vec![Dimension::Scheme, Dimension::Port, Dimension::Request]
+        );
+
+        let request = RequestParts {
+            scheme: "https",
+            ..request
+        };
+        assert_eq!(
+            acl.first_denial(&request),
+            Some((Dimension::Port, AclClassification::DeniedDefault))
+        );
+
+        let request = RequestParts {
+            port: None,
+            ..request
+        };
+        assert_eq!(
+            acl.first_denial(&request),
+            Some((
+                Dimension::Request,
+                AclClassification::Denied("validate function".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn compound_rule_acl() {
+        let acl = HttpAclBuilder::new().build();
+        assert_eq!(
+            acl.is_compound_allowed("https", "GET", "api.example.com"),
+            AclClassification::AllowedDefault
+        );
+
+        let acl = HttpAclBuilder::new()
+            .add_allowed_rule(CompoundRule::new("https", "GET", "api.example.com"))
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.is_compound_allowed("https", "GET", "api.example.com"),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            acl.is_compound_allowed("https", "POST", "api.example.com"),
+            AclClassification::DeniedDefault
+        );
+        assert!(acl
+            .is_compound_allowed("http", "GET", "api.example.com")
+            .is_denied());
+        assert!(acl
+            .is_compound_allowed("https", "GET", "www.example.com")
+            .is_denied());
+    }
+
+    #[test]
+    fn compound_rule_evaluate_acl() {
+        let acl = HttpAclBuilder::new()
+            .host_acl_default(true)
+            .add_allowed_rule(CompoundRule::new("https", "GET", "good.com"))
+            .unwrap()
+            .build();
+        let request = RequestParts {
+            scheme: "https",
+            method: "GET",
+            host: "other.com",
+            path: "/",
+            ..Default::default()
+        };
+
+        assert_eq!(
+            acl.check_url("GET", "https://other.com/"),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            acl.first_denial(&request),
+            Some((Dimension::Compound, AclClassification::DeniedDefault))
+        );
+        assert_eq!(
+            acl.evaluate(&request).get(Dimension::Compound),
+            Some(&AclClassification::DeniedDefault)
+        );
+        assert_eq!(
+            acl.first_denial(&RequestParts {
+                host: "good.com",
+                ..request.clone()
+            }),
+            None
+        );
+        #[cfg(feature = "http")]
+        assert_eq!(
+            acl.evaluate_uri(&"https://other.com/".parse().unwrap(), &http::Method::GET)
+                .get(Dimension::Compound),
+            Some(&AclClassification::DeniedDefault)
+        );
+    }
+
+    #[test]
+    fn export_import_ip_rules() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_ip_range("1.0.0.0/8".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_ip_range("2001:db8::/32".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_ip_range("9.0.0.0/8".parse::<IpNet>().unwrap())
+            .unwrap()
+            .build();
+
+        let (allowed, denied) = acl.export_ip_rules();
+        assert_eq!(
+            allowed,
+            vec![
+                "1.0.0.0/8".parse::<IpNet>().unwrap(),
+                "2001:db8::/32".parse().unwrap()
+            ]
+        );
+        assert_eq!(denied, vec!["9.0.0.0/8".parse::<IpNet>().unwrap()]);
+
+        let imported = HttpAclBuilder::new()
+            .add_denied_ip_range("8.0.0.0/8".parse::<IpNet>().unwrap())
+            .unwrap()
+            .import_ip_rules(allowed, denied)
+            .unwrap()
+            .build();
+        assert_eq!(imported, acl);
+
+        let acl = HttpAclBuilder::new()
+            .add_denied_ip_range(("1.1.1.1", "1.1.1.4"))
+            .unwrap()
+            .ip_acl_default(true)
+            .build();
+        let (allowed, denied) = acl.export_ip_rules();
+        assert_eq!(denied.len(), 3);
+        let imported = HttpAclBuilder::new()
+            .import_ip_rules(allowed, denied)
+            .unwrap()
+            .ip_acl_default(true)
+            .build();
+        for ip in ["1.1.1.0", "1.1.1.1", "1.1.1.3", "1.1.1.4", "1.1.1.5"] {
+            let ip = ip.parse().unwrap();
+            assert_eq!(imported.is_ip_allowed(&ip), acl.is_ip_allowed(&ip));
+        }
+    }
+
+    #[test]
+    fn extend_hosts() {
+        let builder = HttpAclBuilder::new()
+            .extend_allowed_hosts(["example.com".to_string(), "example.org".to_string()])
+            .unwrap()
+            .extend_denied_hosts(vec!["example.net".to_string()])
+            .unwrap();
+        assert_eq!(builder.allowed_host_count(), 2);
+        assert_eq!(builder.denied_host_count(), 1);
+
+        assert_eq!(
+            builder
+                .clone()
+                .extend_allowed_hosts(["example.net".to_string()]),
+            Err(AddError::AlreadyDeniedEntity("example.net".to_string()))
+        );
+        let err = builder
+            .clone()
+            .extend_denied_hosts(["example.info".to_string(), "example.org".to_string()])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            AddError::AlreadyAllowedEntity("example.org".to_string())
+        );
+        assert_eq!(
+            err.to_string(),
+            "The entity `example.org` is already allowed so it cannot be denied."
+        );
+
+        let err = builder
+            .extend_denied_hosts([
+                "example.info".to_string(),
+                "exa mple.org".to_string(),
+                "example.biz".to_string(),
+            ])
+            .unwrap_err();
+        assert_eq!(err, AddError::InvalidEntity("exa mple.org".to_string()));
+        assert_eq!(
+            err.to_string(),
+            "The entity `exa mple.org` is not allowed or denied because it is invalid."
+        );
+    }
+
+    #[test]
+    fn denied_from_str() {
+        let list = "
+            # Blocklist
+            example.com
+            10.0.0.0/8 # internal
+
+            1.1.1.1
+            example.com
+        ";
+
+        let builder = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .add_denied_from_str(list)
+            .unwrap();
+        assert_eq!(builder.denied_host_count(), 1);
+        assert_eq!(builder.denied_ip_range_count(), 2);
+        let acl = builder.build();
+        assert!(acl.is_host_allowed("example.com").is_denied());
+        assert!(acl.is_ip_allowed(&"10.1.2.3".parse().unwrap()).is_denied());
+        assert!(acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()).is_denied());
+        assert!(acl.is_ip_allowed(&"1.1.1.2".parse().unwrap()).is_allowed());
+
+        assert_eq!(
+            HttpAclBuilder::new().add_denied_hosts_from_str(list),
+            Err(AddError::InvalidEntity("10.0.0.0/8".to_string()))
+        );
+        assert_eq!(
+            HttpAclBuilder::new().add_denied_ip_ranges_from_str(list),
+            Err(AddError::InvalidEntity("example.com".to_string()))
+        );
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_denied_from_str("example.com\nnot a host\n")
+                .unwrap_err()
+                .to_string(),
+            "The entity `not a host` is not allowed or denied because it is invalid."
+        );
+
+        let builder = HttpAclBuilder::new()
+            .add_denied_hosts_from_str("example.com\n# comment\nexample.org")
+            .unwrap()
+            .add_denied_ip_ranges_from_str("1.0.0.0/8\n2001:db8::/32")
+            .unwrap();
+        assert_eq!(builder.denied_host_count(), 2);
+        assert_eq!(builder.denied_ip_range_count(), 2);
+    }
+
+    #[test]
+    fn host_reachable_acl() {
+        let builder = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap();
+
+        let acl = builder.clone().build();
+        assert_eq!(
+            acl.is_host_reachable("example.com"),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            acl.is_host_reachable("example.org"),
+            AclClassification::DeniedDefault
+        );
+
+        let acl = builder
+            .clone()
+            .clear_allowed_port_ranges()
+            .add_denied_port_range(1..=65535)
+            .unwrap()
+            .build();
+        assert_eq!(
+            acl.is_host_reachable("example.com"),
+            AclClassification::Denied("no port is allowed".to_string())
+        );
+
+        let builder = builder.http(false).https(false);
+        let acl = builder.clone().build();
+        assert_eq!(
+            acl.is_host_reachable("example.com"),
+            AclClassification::Denied("no scheme is allowed".to_string())
+        );
+
+        let acl = builder.scheme_acl_default(true).build();
+        assert_eq!(
+            acl.is_host_reachable("example.com"),
+            AclClassification::AllowedUserAcl
+        );
+    }
+
+    #[test]
+    fn as_ref_str_arguments() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_host(String::from("example.org"))
+            .unwrap()
+            .add_denied_host(std::borrow::Cow::Borrowed("example.net"))
+            .unwrap()
+            .add_allowed_url_path("/api/{*path}")
+            .unwrap()
+            .add_denied_url_path(String::from("/admin"))
+            .unwrap()
+            .add_allowed_url_path_for_method("GET", std::borrow::Cow::from("/status"))
+            .unwrap()
+            .add_denied_header("X-Debug", None)
+            .unwrap()
+            .add_static_dns_mapping("example.com", "1.1.1.1:443".parse().unwrap())
+            .unwrap()
+            .build();
+
+        assert!(acl.is_host_allowed("example.com").is_allowed());
+        assert!(acl.is_host_allowed("example.org").is_allowed());
+        assert!(acl.is_host_allowed("example.net").is_denied());
+        assert!(acl.is_url_path_allowed("/admin").is_denied());
+        assert!(acl.is_header_allowed("x-debug", "1").is_denied());
+    }
+
+    #[test]
+    fn authority_acl() {
+        let acl = HttpAclBuilder::new()
+            .host_acl_default(true)
+            .ip_acl_default(true)
+            .add_denied_ip_range("93.184.216.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_host("example.net")
+            .unwrap()
+            .build();
+
+        let authority = Authority::parse("93.184.216.34").unwrap();
+        assert!(acl.is_host_allowed("93.184.216.34").is_allowed());
+        assert_eq!(
+            acl.is_authority_allowed(&authority),
+            AclClassification::DeniedUserAcl
+        );
+
+        assert_eq!(
+            acl.is_authority_allowed(&Authority::parse("1.1.1.1:443").unwrap()),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.is_authority_allowed(&Authority::parse("1.1.1.1:8080").unwrap()),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            acl.is_authority_allowed(&Authority::parse("example.net").unwrap()),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_authority_allowed(&Authority::parse("example.com").unwrap()),
+            AclClassification::AllowedDefault
+        );
+
+        for host in ["2130706433", "0x7f.0.0.1", "0177.0.0.1"] {
+            assert_eq!(
+                acl.is_authority_allowed(&Authority::parse(host).unwrap()),
+                AclClassification::DeniedNotGlobal
+            );
+        }
+    }
+
+    #[test]
+    fn reason_codes() {
+        for (classification, code) in [
+            (AclClassification::AllowedUserAcl, "allowed_user"),
+            (AclClassification::AllowedDefault, "allowed_default"),
+            (AclClassification::DeniedUserAcl, "denied_user"),
+            (AclClassification::DeniedDefault, "denied_default"),
+            (
+                AclClassification::Denied("a custom reason".to_string()),
+                "denied_custom",
+            ),
+            (AclClassification::DeniedNotGlobal, "denied_not_global"),
+            (
+                AclClassification::DeniedPrivateRange,
+                "denied_private_range",
+            ),
+        ] {
+            assert_eq!(classification.reason_code(), code);
+        }
+    }
+
+    #[test]
+    fn classification_display() {
+        assert_eq!(
+            AclClassification::AllowedUserAcl.to_string(),
+            "The entity is allowed according to the allowed ACL."
+        );
+        assert_eq!(
+            AclClassification::DeniedUserAcl.to_string(),
+            "The entity is denied according to the denied ACL."
+        );
+        assert_eq!(
+            AclClassification::Denied("it is forbidden".to_string()).to_string(),
+            "The entity is denied because it is forbidden."
+        );
+    }
+
+    #[test]
+    fn query_substring_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_denied_query_substring("javascript:")
+            .unwrap()
+            .build();
+
+        assert!(acl
+            .is_url_query_allowed("q=javascript:alert(1)")
+            .is_denied());
+        assert!(acl
+            .is_url_query_allowed("q=javascript%3Aalert(1)")
+            .is_denied());
+        assert!(acl
+            .is_url_query_allowed("q=JavaScript%3aalert(1)")
+            .is_denied());
+        assert_eq!(
+            acl.is_url_query_allowed("q=javascript"),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.explain(Dimension::Query, "q=javascript%3Aalert(1)"),
+            "query q=javascript%3Aalert(1) is denied by the query rule `javascript:`."
+        );
+
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_denied_query_substring("javascript:")
+                .unwrap()
+                .add_denied_query_substring("JavaScript:")
+                .unwrap_err(),
+            AddError::AlreadyDenied
+        );
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_denied_query_substring("")
+                .unwrap_err(),
+            AddError::Invalid
+        );
+    }
+
+    #[test]
+    fn query_evaluate_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_denied_query_substring("<script")
+            .unwrap()
+            .max_query_length(Some(32))
+            .build();
+        let request = RequestParts {
+            scheme: "https",
+            method: "GET",
+            host: "example.com",
+            path: "/search",
+            query: Some("q=%3Cscript%3E"),
+            ..Default::default()
+        };
+
+        assert!(acl
+            .check_url("GET", "https://example.com/search?q=%3Cscript%3E")
+            .is_denied());
+        assert_eq!(
+            acl.first_denial(&request),
+            Some((Dimension::Query, AclClassification::DeniedUserAcl))
+        );
+        assert_eq!(
+            acl.evaluate(&request).get(Dimension::Query),
+            Some(&AclClassification::DeniedUserAcl)
+        );
+        assert!(acl
+            .evaluate(&RequestParts {
+                query: Some(&"a".repeat(33)),
+                ..request.clone()
+            })
+            .get(Dimension::Query)
+            .is_some_and(AclClassification::is_denied));
+        assert_eq!(
+            acl.first_denial(&RequestParts {
+                query: Some("q=rust"),
+                ..request
+            }),
+            None
+        );
+
+        #[cfg(feature = "har")]
+        {
+            let har = r#"{"log": {"entries": [
+                {"request": {"method": "GET", "url": "https://example.com/search?q=%3Cscript%3E"}}
+            ]}}"#;
+            let reports = acl.evaluate_har(har);
+            assert_eq!(
+                reports[0].1.get(Dimension::Query),
+                Some(&AclClassification::DeniedUserAcl)
+            );
+        }
+    }
+
+    #[test]
+    fn non_global_toggles_acl() {
+        let loopback = "127.0.0.1".parse().unwrap();
+        let loopback_v6 = "::1".parse().unwrap();
+        let private = "10.0.0.1".parse().unwrap();
+        let link_local = "169.254.169.254".parse().unwrap();
+        let link_local_v6 = "fe80::1".parse().unwrap();
+
+        let acl = HttpAclBuilder::new().ip_acl_default(true).build();
+        assert_eq!(
+            acl.is_ip_allowed(&loopback),
+            AclClassification::DeniedNotGlobal
+        );
+        assert_eq!(
+            acl.is_ip_allowed(&private),
+            AclClassification::DeniedPrivateRange
+        );
+
+        let acl = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .loopback_ip_addresses(true)
+            .build();
+        assert!(acl.allow_loopback_ip_addresses());
+        assert_eq!(
+            acl.is_ip_allowed(&loopback),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.is_ip_allowed(&loopback_v6),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.is_ip_allowed(&private),
+            AclClassification::DeniedPrivateRange
+        );
+        assert_eq!(
+            acl.is_ip_allowed(&link_local),
+            AclClassification::DeniedNotGlobal
+        );
+        assert_eq!(
+            acl.is_ip_allowed(&link_local_v6),
+            AclClassification::DeniedNotGlobal
+        );
+
+        let acl = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .link_local_ip_addresses(true)
+            .build();
+        assert_eq!(
+            acl.is_ip_allowed(&loopback),
+            AclClassification::DeniedNotGlobal
+        );
+        assert_eq!(
+            acl.is_ip_allowed(&link_local),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.is_ip_allowed(&link_local_v6),
+            AclClassification::AllowedDefault
+        );
+
+        let acl = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .non_global_ip_ranges(true)
+            .add_denied_ip_range("127.0.0.2/32".parse::<IpNet>().unwrap())
+            .unwrap()
+            .build();
+        for ip in [loopback, loopback_v6, private, link_local, link_local_v6] {
+            assert_eq!(acl.is_ip_allowed(&ip), AclClassification::AllowedDefault);
+        }
+        assert_eq!(
+            acl.is_ip_allowed(&"127.0.0.2".parse().unwrap()),
+            AclClassification::DeniedUserAcl
+        );
+    }
+
+    #[test]
+    fn from_env() {
+        let vars = [
+            ("HTTP_ACL_TEST_FROM_ENV_DENIED_HOSTS", "a.com, b.com"),
+            (
+                "HTTP_ACL_TEST_FROM_ENV_ALLOWED_IP_RANGES",
+                "1.1.1.0/24,8.8.8.8",
+            ),
+            ("HTTP_ACL_TEST_FROM_ENV_ALLOWED_PORTS", "443,8000-8999"),
+            ("HTTP_ACL_TEST_FROM_ENV_ALLOWED_METHODS", "GET,HEAD"),
+            ("HTTP_ACL_TEST_FROM_ENV_DENIED_METHODS", "DELETE"),
+            ("HTTP_ACL_TEST_FROM_ENV_HTTP", "false"),
+            ("HTTP_ACL_TEST_FROM_ENV_HOST_ACL_DEFAULT", "true"),
+        ];
+        for (key, value) in vars {
+            std::env::set_var(key, value);
+        }
+
+        let acl = HttpAclBuilder::from_env("HTTP_ACL_TEST_FROM_ENV")
+            .unwrap()
+            .build();
+
+        assert!(acl.is_host_allowed("a.com").is_denied());
+        assert!(acl.is_host_allowed("b.com").is_denied());
+        assert_eq!(
+            acl.is_host_allowed("c.com"),
+            AclClassification::AllowedDefault
+        );
+        assert!(acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()).is_allowed());
+        assert!(acl.is_ip_allowed(&"8.8.8.8".parse().unwrap()).is_allowed());
+        assert!(acl.is_ip_allowed(&"8.8.4.4".parse().unwrap()).is_denied());
+        assert!(acl.is_port_allowed(8080).is_allowed());
+        assert!(acl.is_port_allowed(80).is_denied());
+        assert!(acl.is_method_allowed("GET").is_allowed());
+        assert!(acl.is_method_allowed("POST").is_denied());
+        assert_eq!(
+            acl.is_method_allowed("DELETE"),
+            AclClassification::DeniedUserAcl
+        );
+        assert!(acl.is_scheme_allowed("http").is_denied());
+        assert!(acl.is_scheme_allowed("https").is_allowed());
+
+        std::env::set_var("HTTP_ACL_TEST_FROM_ENV_INVALID_ALLOWED_PORTS", "80,http");
+        assert_eq!(
+            HttpAclBuilder::from_env("HTTP_ACL_TEST_FROM_ENV_INVALID").unwrap_err(),
+            AddError::InvalidEntity("http".to_string())
+        );
+
+        for (key, _) in vars {
+            std::env::remove_var(key);
+        }
+        std::env::remove_var("HTTP_ACL_TEST_FROM_ENV_INVALID_ALLOWED_PORTS");
+    }
+
+    #[test]
+    fn from_env_denied_defaults() {
+        let vars = [
+            ("HTTP_ACL_TEST_FROM_ENV_DEFAULTS_DENIED_METHODS", "TRACE"),
+            ("HTTP_ACL_TEST_FROM_ENV_DEFAULTS_DENIED_PORTS", "80"),
+        ];
+        for (key, value) in vars {
+            std::env::set_var(key, value);
+        }
+
+        let acl = HttpAclBuilder::from_env("HTTP_ACL_TEST_FROM_ENV_DEFAULTS")
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.is_method_allowed("TRACE"),
+            AclClassification::DeniedUserAcl
+        );
+        assert!(acl.is_method_allowed("GET").is_allowed());
+        assert_eq!(acl.is_port_allowed(80), AclClassification::DeniedUserAcl);
+        assert!(acl.is_port_allowed(443).is_allowed());
+
+        std::env::set_var("HTTP_ACL_TEST_FROM_ENV_DEFAULTS_ALLOWED_METHODS", "TRACE");
+        assert_eq!(
+            HttpAclBuilder::from_env("HTTP_ACL_TEST_FROM_ENV_DEFAULTS").unwrap_err(),
+            AddError::AlreadyAllowed
+        );
+
+        for (key, _) in vars {
+            std::env::remove_var(key);
+        }
+        std::env::remove_var("HTTP_ACL_TEST_FROM_ENV_DEFAULTS_ALLOWED_METHODS");
+    }
+
+    #[test]
+    #[cfg(feature = "har")]
+    fn evaluate_har() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_denied_host("evil.example")
+            .unwrap()
+            .build();
+
+        let har = r#"{
+            "log": {
+                "version": "1.2",
+                "entries": [
+                    {
+                        "request": {
+                            "method": "GET",
+                            "url": "https://example.com/index.html",
+                            "headers": [{ "name": "accept", "value": "text/html" }]
+                        }
+                    },
+                    {
+                        "request": {
+                            "method": "POST",
+                            "url": "http://evil.example/upload",
+                            "headers": []
+                        }
+                    }
+                ]
+            }
+        }"#;
+
+        let reports = acl.evaluate_har(har);
+        assert_eq!(reports.len(), 2);
+
+        let (request, report) = &reports[0];
+        assert_eq!(request, "GET https://example.com/index.html");
+        assert!(report.is_allowed());
+
+        let (request, report) = &reports[1];
+        assert_eq!(request, "POST http://evil.example/upload");
+        assert!(report.is_denied());
+        assert_eq!(
+            report.get(Dimension::Host),
+            Some(&AclClassification::DeniedUserAcl)
+        );
+
+        assert!(acl.evaluate_har("not a har").is_empty());
+    }
+
+    #[test]
+    fn redundant_rule_warnings() {
+        assert!(HttpAclBuilder::new().warnings().is_empty());
+
+        let builder = HttpAclBuilder::new()
+            .host_acl_default(true)
+            .add_allowed_host("example.com")
+            .unwrap();
+        assert_eq!(
+            builder.warnings(),
+            vec![AclWarning::RedundantRule {
+                dimension: Dimension::Host,
+                rule: "example.com".to_string(),
+            }]
+        );
+        assert_eq!(
+            builder.warnings()[0].to_string(),
+            "The host rule `example.com` has no effect as it matches the default action."
+        );
+
+        let builder = builder.add_denied_host("*.example.com").unwrap();
+        assert!(builder.warnings().is_empty());
+
+        let builder = HttpAclBuilder::new()
+            .host_acl_default(true)
+            .add_denied_host("example.com")
+            .unwrap();
+        assert!(builder.warnings().is_empty());
+
+        let builder = HttpAclBuilder::new()
+            .add_denied_ip_range("1.1.1.0/24".parse::<IpNet>().unwrap())
+            .unwrap();
+        assert_eq!(
+            builder.warnings(),
+            vec![AclWarning::RedundantRule {
+                dimension: Dimension::Ip,
+                rule: "1.1.1.0-1.1.1.255".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn config_toml_round_trip() {
+        use super::{HttpAcl, HttpAclConfig};
+
+        let builder = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_url_path("/public/{*path}")
+            .unwrap()
+            .add_denied_url_path("/admin")
+            .unwrap()
+            .url_path_acl_default(false);
+        let config = HttpAclConfig::from(builder);
+
+        let toml = toml::to_string(&config).unwrap();
+        let deserialized = toml::from_str::<HttpAclConfig>(&toml).unwrap();
+        assert_eq!(deserialized, config);
+
+        let acl = HttpAcl::try_from(deserialized).unwrap();
+        assert!(acl.is_host_allowed("example.com").is_allowed());
+        assert_eq!(
+            acl.is_url_path_allowed("/public/index.html"),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            acl.is_url_path_allowed("/admin"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_url_path_allowed("/private"),
+            AclClassification::DeniedDefault
+        );
+
+        let config = toml::from_str::<HttpAclConfig>(
+            r#"
+            allowed_hosts = ["example.com"]
+            denied_url_paths = ["/admin"]
+            "#,
+        )
+        .unwrap();
+        let acl = HttpAcl::try_from(config).unwrap();
+        assert!(acl.is_host_allowed("example.com").is_allowed());
+        assert!(acl.is_url_path_allowed("/admin").is_denied());
+        assert!(acl.is_url_path_allowed("/").is_allowed());
+        assert!(acl.is_port_allowed(443).is_allowed());
+    }
+
+    #[test]
+    fn ip_range_overlaps() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_ip_range("1.1.1.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_ip_range("1.1.2.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_ip_range("8.8.8.8/32".parse::<IpNet>().unwrap())
+            .unwrap()
+            .build();
+
+        assert!(acl.overlaps_allowed_ip_ranges("1.1.0.0/16".parse::<IpNet>().unwrap()));
+        assert!(acl.overlaps_allowed_ip_ranges("1.1.2.128/25".parse::<IpNet>().unwrap()));
+        assert!(!acl.overlaps_allowed_ip_ranges("1.1.3.0/24".parse::<IpNet>().unwrap()));
+        assert!(acl.overlaps_denied_ip_ranges("8.8.0.0/16".parse::<IpNet>().unwrap()));
+        assert!(!acl.overlaps_denied_ip_ranges("8.8.4.0/24".parse::<IpNet>().unwrap()));
+        assert!(acl.is_ip_allowed(&"1.1.2.1".parse().unwrap()).is_allowed());
+        assert!(acl.is_ip_allowed(&"8.8.8.8".parse().unwrap()).is_denied());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialized_builder_url_paths() {
+        let builder = HttpAclBuilder::new()
+            .add_allowed_url_path("/public/{*path}")
+            .unwrap()
+            .add_denied_url_path("/admin")
+            .unwrap()
+            .add_denied_url_path_for_method("POST", "/upload")
+            .unwrap()
+            .url_path_acl_default(false);
+
+        let json = serde_json::to_string(&builder).unwrap();
+        let deserialized = serde_json::from_str::<HttpAclBuilder>(&json).unwrap();
+
+        let acl = deserialized.clone().build();
+        assert_eq!(
+            acl.is_url_path_allowed("/public/index.html"),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            acl.is_url_path_allowed("/admin"),
+            AclClassification::DeniedUserAcl
+        );
+        assert!(acl
+            .is_url_path_allowed_for_method("POST", "/upload")
+            .is_denied());
+
+        assert_eq!(deserialized.try_build().unwrap(), acl);
+        assert_eq!(builder.try_build().unwrap(), acl);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialized_baseline_builder() {
+        let json = r#"{
+            "allow_http": false,
+            "allow_https": true,
+            "allowed_methods": ["GET", "POST"],
+            "denied_methods": [],
+            "allowed_hosts": ["example.com"],
+            "denied_hosts": [],
+            "allowed_port_ranges": [{"start": 443, "end": 443}],
+            "denied_port_ranges": [],
+            "allowed_ip_ranges": [],
+            "denied_ip_ranges": [],
+            "static_dns_mapping": {},
+            "allowed_url_paths": ["/public"],
+            "denied_url_paths": [],
+            "allow_private_ip_ranges": false,
+            "method_acl_default": false,
+            "host_acl_default": false,
+            "port_acl_default": false,
+            "ip_acl_default": false,
+            "url_path_acl_default": false
+        }"#;
+
+        let acl = serde_json::from_str::<HttpAclBuilder>(json)
+            .unwrap()
+            .try_build()
+            .unwrap();
+        assert!(acl.is_scheme_allowed("http").is_denied());
+        assert!(acl.is_host_allowed("example.com").is_allowed());
+        assert!(acl.is_port_allowed(443).is_allowed());
+        assert!(acl.is_url_path_allowed("/public").is_allowed());
+        assert!(acl.is_url_path_allowed("/private").is_denied());
+        assert!(acl.decode_url_paths());
+        assert!(acl.normalize_url_paths());
+
+        assert_eq!(
+            serde_json::from_str::<HttpAclBuilder>("{}").unwrap(),
+            HttpAclBuilder::new()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serialized_other_method_url_paths() {
+        use super::{HttpAcl, HttpAclConfig};
+
+        let builder = HttpAclBuilder::new()
+            .add_allowed_url_path_for_method("PURGE", "/cache")
+            .unwrap()
+            .add_denied_url_path_for_method("GET", "/cache")
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&HttpRequestMethod::OTHER("PURGE".to_string())).unwrap(),
+            "\"PURGE\""
+        );
+        assert_eq!(
+            serde_json::from_str::<HttpRequestMethod>("\"GET\"").unwrap(),
+            HttpRequestMethod::GET
+        );
+
+        let json = serde_json::to_string(&builder).unwrap();
+        let deserialized = serde_json::from_str::<HttpAclBuilder>(&json).unwrap();
+        assert_eq!(deserialized, builder);
+
+        let config = HttpAclConfig::from(builder.clone());
+        let json = serde_json::to_string(&config).unwrap();
+        let deserialized = serde_json::from_str::<HttpAclConfig>(&json).unwrap();
+        let acl = HttpAcl::try_from(deserialized).unwrap();
+        assert_eq!(acl, builder.build());
+        assert_eq!(
+            acl.is_url_path_allowed_for_method("PURGE", "/cache"),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            acl.is_url_path_allowed_for_method("GET", "/cache"),
+            AclClassification::DeniedUserAcl
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialized_baseline_other_method() {
+        assert_eq!(
+            serde_json::from_str::<HttpRequestMethod>("{\"OTHER\":\"PURGE\"}").unwrap(),
+            HttpRequestMethod::OTHER("PURGE".to_string())
+        );
+        assert_eq!(
+            serde_json::from_str::<HttpRequestMethod>("\"PURGE\"").unwrap(),
+            HttpRequestMethod::OTHER("PURGE".to_string())
+        );
+        assert!(serde_json::from_str::<HttpRequestMethod>("{\"PATCH\":\"PURGE\"}").is_err());
+
+        let builder = serde_json::from_str::<HttpAclBuilder>(
+            r#"{"allowed_methods":["GET",{"OTHER":"PURGE"}]}"#,
+        )
+        .unwrap();
+        let acl = builder.build();
+        assert!(acl.is_method_allowed("PURGE").is_allowed());
+        assert!(acl.is_method_allowed("POST").is_denied());
+    }
+
+    #[test]
+    fn union_intersect_precedence_acl() {
+        let allow_wins = HttpAclBuilder::new()
+            .precedence(Precedence::AllowWins)
+            .build();
+        let deny_wins = HttpAclBuilder::new()
+            .precedence(Precedence::DenyWins)
+            .build();
+
+        assert_eq!(
+            allow_wins.union(&deny_wins).precedence(),
+            Precedence::AllowWins
+        );
+        assert_eq!(
+            deny_wins.union(&allow_wins).precedence(),
+            Precedence::AllowWins
+        );
+        assert_eq!(
+            allow_wins.intersect(&deny_wins).precedence(),
+            Precedence::DenyWins
+        );
+        assert_eq!(
+            deny_wins.intersect(&allow_wins).precedence(),
+            Precedence::DenyWins
+        );
+        assert_eq!(
+            allow_wins.intersect(&allow_wins).precedence(),
+            Precedence::AllowWins
+        );
+        assert_eq!(
+            deny_wins.union(&deny_wins).precedence(),
+            Precedence::DenyWins
+        );
+    }
+
+    #[test]
+    fn union_intersect_acl() {
+        let a = HttpAclBuilder::new()
+            .add_allowed_host("a.example.com")
+            .unwrap()
+            .add_allowed_host("shared.example.com")
+            .unwrap()
+            .add_denied_host("evil.example.com")
+            .unwrap()
+            .add_denied_host("a-only.example.com")
+            .unwrap()
+            .build();
+        let b = HttpAclBuilder::new()
+            .add_allowed_host("b.example.com")
+            .unwrap()
+            .add_allowed_host("shared.example.com")
+            .unwrap()
+            .add_denied_host("evil.example.com")
+            .unwrap()
+            .remove_allowed_method("DELETE")
+            .add_denied_method("DELETE")
+            .unwrap()
+            .build();
+
+        let union = a.union(&b);
+        for host in ["a.example.com", "b.example.com", "shared.example.com"] {
+            assert_eq!(
+                union.is_host_allowed(host),
+                AclClassification::AllowedUserAcl,
+                "{}",
+                host
+            );
+        }
+        assert_eq!(
+            union.is_host_allowed("evil.example.com"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            union.is_host_allowed("a-only.example.com"),
+            AclClassification::DeniedDefault
+        );
+        assert!(union.is_method_allowed("DELETE").is_allowed());
+
+        let intersection = a.intersect(&b);
+        assert_eq!(
+            intersection.is_host_allowed("shared.example.com"),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            intersection.is_host_allowed("a.example.com"),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            intersection.is_host_allowed("a-only.example.com"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            intersection.is_method_allowed("DELETE"),
+            AclClassification::DeniedUserAcl
+        );
+    }
+
+    #[test]
+    fn header_value_set_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_denied_header_values(
+                "X-Forwarded-Proto",
+                vec!["http".to_string(), "ftp".to_string()],
+            )
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.is_header_allowed("x-forwarded-proto", "http"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_header_allowed("X-Forwarded-Proto", "ftp"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_header_allowed("x-forwarded-proto", "https"),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.explain(Dimension::Header, "x-forwarded-proto: ftp"),
+            "header x-forwarded-proto: ftp is denied by the header rule `x-forwarded-proto: http | ftp`."
+        );
+
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_allowed_header("x-forwarded-proto", Some("https".to_string()))
+                .unwrap()
+                .add_denied_header_values("x-forwarded-proto", vec!["https".to_string()])
+                .unwrap_err(),
+            AddError::AlreadyAllowed
+        );
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_denied_header_values("x-forwarded-proto", Vec::new())
+                .unwrap_err(),
+            AddError::Invalid
+        );
+    }
+
+    #[test]
+    fn required_header_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_required_header("Authorization")
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.are_required_headers_present([("accept", "*/*")].into_iter()),
+            AclClassification::Denied("missing required header authorization".to_string())
+        );
+        assert_eq!(
+            acl.are_required_headers_present(
+                [("accept", "*/*"), ("Authorization", "Bearer token")].into_iter()
+            ),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            HttpAclBuilder::new()
+                .build()
+                .are_required_headers_present(std::iter::empty()),
+            AclClassification::AllowedDefault
+        );
+
+        let report = acl.evaluate(&RequestParts {
+            scheme: "https",
+            method: "GET",
+            host: "example.com",
+            path: "/",
+            ..Default::default()
+        });
+        assert!(report
+            .denials()
+            .any(|(dimension, _)| *dimension == Dimension::Header));
+    }
+
+    #[test]
+    fn sorted_ranges_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_ip_range("8.8.8.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_ip_range(
+                "1.1.1.1".parse::<std::net::IpAddr>().unwrap()..="1.1.1.9".parse().unwrap(),
+            )
+            .unwrap()
+            .add_allowed_ip_range("2606:4700::/32".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_ip_range(
+                "1.0.0.1".parse::<std::net::IpAddr>().unwrap()..="1.0.0.1".parse().unwrap(),
+            )
+            .unwrap()
+            .add_allowed_port_range(8443..=8443)
+            .unwrap()
+            .add_allowed_port_range(8000..=8080)
+            .unwrap()
+            .build();
+
+        let starts = acl
+            .sorted_allowed_ip_ranges()
+            .iter()
+            .map(|range| range.start().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(starts, vec!["1.0.0.1", "1.1.1.1", "8.8.8.0", "2606:4700::"]);
+        assert!(acl.is_ip_allowed(&"1.1.1.5".parse().unwrap()).is_allowed());
+        assert!(acl.is_port_allowed(8001).is_allowed());
+        assert!(acl.is_port_allowed(443).is_allowed());
+    }
+
+    #[test]
+    fn header_limits_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_ip_range("1.1.1.1/32".parse::<IpNet>().unwrap())
+            .unwrap()
+            .max_header_count(Some(2))
+            .max_total_header_bytes(Some(10))
+            .build();
+
+        assert!(acl
+            .are_headers_within_limits([("a", "1"), ("b", "2")])
+            .is_allowed());
+        assert_eq!(
+            acl.are_headers_within_limits([("a", "1"), ("b", "2"), ("c", "3")]),
+            AclClassification::Denied("3 headers exceed the maximum header count of 2".to_string())
+        );
+        assert!(acl
+            .are_headers_within_limits([("name", "value1")])
+            .is_allowed());
+        assert_eq!(
+            acl.are_headers_within_limits([("name", "value12")]),
+            AclClassification::Denied(
+                "11 header bytes exceed the maximum total header size of 10 bytes".to_string()
+            )
+        );
+        assert_eq!(
+            HttpAclBuilder::new()
+                .build()
+                .are_headers_within_limits([("a", "1")]),
+            AclClassification::AllowedDefault
+        );
+
+        let headers = [("a", "1"), ("b", "2"), ("c", "3")];
+        let request = RequestParts {
+            scheme: "https",
+            method: "GET",
+            host: "1.1.1.1",
+            port: None,
+            path: "/",
+            query: None,
+            headers: &headers,
+        };
+        assert_eq!(
+            acl.first_denial(&request),
+            Some((
+                Dimension::Header,
+                AclClassification::Denied(
+                    "3 headers exceed the maximum header count of 2".to_string()
+                )
+            ))
+        );
+    }
+
+    #[test]
+    fn ip_range_subset_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_ip_range("1.1.0.0/16".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_ip_range("1.1.1.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .build();
+
+        assert!(acl.is_ip_range_subset_of_allowed(&"1.1.1.0/24".parse().unwrap()));
+        assert!(acl.is_ip_range_subset_of_allowed(&"1.1.0.0/16".parse().unwrap()));
+        assert!(!acl.is_ip_range_subset_of_allowed(&"1.0.0.0/15".parse().unwrap()));
+        assert!(!acl.is_ip_range_subset_of_allowed(&"8.8.8.0/24".parse().unwrap()));
+    }
+
+    #[test]
+    fn check_url_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_denied_host("example.com")
+            .unwrap()
+            .build();
+        assert_eq!(
+            acl.check_url("GET", "http://example.com/"),
+            AclClassification::DeniedUserAcl
+        );
+
+        let acl = HttpAclBuilder::new()
+            .host_acl_default(true)
+            .add_allowed_url_path_for_method("GET", "/admin")
+            .unwrap()
+            .add_denied_url_path_for_method("POST", "/admin")
+            .unwrap()
+            .add_denied_query_substring("token=")
+            .unwrap()
+            .build();
+        assert_eq!(
+            acl.check_url("GET", "http://example.com/admin"),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.check_url("POST", "http://example.com/admin"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.check_url("GET", "http://example.com/?token=secret"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.check_url("GET", "ftp://example.com/"),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            acl.check_url("GET", "http://example.com:8080/"),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            acl.check_url("GET", "http://[::1]/"),
+            AclClassification::DeniedNotGlobal
+        );
+        assert!(matches!(
+            acl.check_url("GET", "not a url"),
+            AclClassification::Denied(_)
+        ));
+    }
+
+    #[test]
+    fn port_helpers_acl() {
+        let acl = HttpAclBuilder::new()
+            .port_acl_default(true)
+            .deny_ephemeral_ports()
+            .unwrap()
+            .build();
+        assert!(acl.is_port_allowed(80).is_allowed());
+        assert!(acl.is_port_allowed(49151).is_allowed());
+        assert!(acl.is_port_allowed(49152).is_denied());
+        assert!(acl.is_port_allowed(65535).is_denied());
+
+        let acl = HttpAclBuilder::new()
+            .allow_only_well_known_ports()
+            .unwrap()
+            .build();
+        assert!(acl.is_port_allowed(80).is_allowed());
+        assert!(acl.is_port_allowed(1023).is_allowed());
+        assert!(acl.is_port_allowed(1024).is_denied());
+        assert!(acl.is_port_allowed(8080).is_denied());
+    }
+
+    #[test]
+    fn str_pair_ip_range_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_ip_range(("1.1.1.5", "1.1.1.50"))
+            .unwrap()
+            .build();
+        assert!(acl.is_ip_allowed(&"1.1.1.5".parse().unwrap()).is_allowed());
+        assert!(acl.is_ip_allowed(&"1.1.1.50".parse().unwrap()).is_allowed());
+        assert!(acl.is_ip_allowed(&"1.1.1.51".parse().unwrap()).is_denied());
+
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_allowed_ip_range(("1.1.1.50", "1.1.1.5"))
+                .unwrap_err(),
+            AddError::Invalid
+        );
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_denied_ip_range(("1.1.1.5".to_string(), "1.1.1".to_string()))
+                .unwrap_err(),
+            AddError::Invalid
+        );
+    }
+
+    #[test]
+    fn shadowed_rules_acl() {
+        use super::ShadowedRule;
+
+        let acl = HttpAclBuilder::new()
+            .add_denied_ip_range("1.1.0.0/16".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_ip_range("1.1.1.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_ip_range("8.8.8.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_port_range(8000..=9000)
+            .unwrap()
+            .add_allowed_port_range(8080..=8080)
+            .unwrap()
+            .add_denied_host("*.example.com")
+            .unwrap()
+            .add_allowed_host("api.example.com")
+            .unwrap()
+            .add_allowed_host("*.internal.example.com")
+            .unwrap()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .build();
+
+        let shadowed = acl.shadowed_rules();
+        assert_eq!(
+            shadowed,
+            vec![
+                ShadowedRule {
+                    dimension: Dimension::Host,
+                    rule: "api.example.com".to_string()
+                },
+                ShadowedRule {
+                    dimension: Dimension::Host,
+                    rule: "*.internal.example.com".to_string()
+                },
+                ShadowedRule {
+                    dimension: Dimension::Port,
+                    rule: "8080-8080".to_string()
+                },
+                ShadowedRule {
+                    dimension: Dimension::Ip,
+                    rule: "1.1.1.0-1.1.1.255".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            shadowed[3].to_string(),
+            "The ip rule `1.1.1.0-1.1.1.255` is shadowed by a rule that takes precedence."
+        );
+        assert!(HttpAclBuilder::new().build().shadowed_rules().is_empty());
+
+        let acl = HttpAclBuilder::new()
+            .precedence(Precedence::AllowWins)
+            .add_allowed_ip_range("1.1.0.0/16".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_ip_range("1.1.1.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .build();
+        assert_eq!(
+            acl.shadowed_rules(),
+            vec![ShadowedRule {
+                dimension: Dimension::Ip,
+                rule: "1.1.1.0-1.1.1.255".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn overlapping_ip_ranges_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_ip_range("1.1.0.0/16".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_ip_range(("1.1.255.0", "1.2.0.255"))
+            .unwrap()
+            .add_denied_ip_range("1.2.0.0/16".parse::<IpNet>().unwrap())
+            .unwrap()
+            .try_build()
+            .unwrap();
+
+        assert!(acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()).is_allowed());
+        assert!(acl.is_ip_allowed(&"1.2.0.1".parse().unwrap()).is_denied());
+        assert!(acl.is_ip_allowed(&"1.2.1.1".parse().unwrap()).is_denied());
+
+        let acl = acl.to_builder().precedence(Precedence::AllowWins).build();
+        assert!(acl.is_ip_allowed(&"1.2.0.1".parse().unwrap()).is_allowed());
+        assert!(acl.is_ip_allowed(&"1.2.1.1".parse().unwrap()).is_denied());
+    }
+
+    #[test]
+    fn label_glob_host_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("api-*.example.com")
+            .unwrap()
+            .build();
+
+        assert!(acl.is_host_allowed("api-1.example.com").is_allowed());
+        assert!(acl.is_host_allowed("x.api-1.example.com").is_denied());
+        assert!(acl.is_host_allowed("example.com").is_denied());
+    }
+
+    #[cfg(all(feature = "toml", feature = "json"))]
+    #[test]
+    fn config_file_loaders() {
+        use super::error::LoadError;
+
+        let dir = std::env::temp_dir().join(format!("http-acl-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let toml_path = dir.join("acl.toml");
+        std::fs::write(
+            &toml_path,
+            "allowed_hosts = [\"example.com\"]\ndenied_url_paths = [\"/admin\"]\n",
+        )
+        .unwrap();
+        let acl = HttpAclBuilder::from_toml_file(&toml_path).unwrap();
+        assert!(acl.is_host_allowed("example.com").is_allowed());
+        assert!(acl.is_url_path_allowed("/admin").is_denied());
+        assert_eq!(HttpAclBuilder::from_config_file(&toml_path).unwrap(), acl);
+
+        let json_path = dir.join("acl.json");
+        std::fs::write(&json_path, r#"{"allowed_hosts": ["example.com"]}"#).unwrap();
+        let acl = HttpAclBuilder::from_json_file(&json_path).unwrap();
+        assert!(acl.is_host_allowed("example.com").is_allowed());
+
+        std::fs::write(
+            &json_path,
+            r#"{"allowed_hosts": ["example.com", "example.com"]}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            HttpAclBuilder::from_config_file(&json_path),
+            Err(LoadError::Add(AddError::AlreadyAllowed))
+        ));
+        assert!(matches!(
+            HttpAclBuilder::from_json_str("{"),
+            Err(LoadError::Parse(_))
+        ));
+        assert!(matches!(
+            HttpAclBuilder::from_toml_file(dir.join("missing.toml")),
+            Err(LoadError::Io(_))
+        ));
+        assert!(matches!(
+            HttpAclBuilder::from_config_file(dir.join("acl.yaml")),
+            Err(LoadError::UnsupportedFormat(extension)) if extension == "yaml"
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn diff_acl() {
+        use super::{AclDiff, AclRule};
+
+        let acl = HttpAclBuilder::new()
+            .add_denied_host("example.com")
+            .unwrap()
+            .add_allowed_url_path_for_method("GET", "/public")
+            .unwrap()
+            .build();
+        let other = HttpAclBuilder::new()
+            .add_denied_host("example.org")
+            .unwrap()
+            .add_allowed_url_path_for_method("GET", "/public")
+            .unwrap()
+            .host_acl_default(true)
+            .build();
+
+        assert!(acl.diff(&acl).is_empty());
+        let diff = acl.diff(&other);
+        assert_eq!(
+            diff,
+            AclDiff {
+                added: vec![AclRule {
+                    dimension: Dimension::Host,
+                    allowed: false,
+                    rule: "example.org".to_string()
+                }],
+                removed: vec![AclRule {
+                    dimension: Dimension::Host,
+                    allowed: false,
+                    rule: "example.com".to_string()
+                }],
+                changed_flags: vec![("host_acl_default", false, true)],
+                changed_settings: Vec::new(),
+            }
+        );
+        assert_eq!(diff.added[0].to_string(), "denied host `example.org`");
+
+        let other = HttpAclBuilder::new()
+            .add_denied_host("example.com")
+            .unwrap()
+            .add_allowed_url_path_for_method("GET", "/public")
+            .unwrap()
+            .ip_family_mode(IpFamilyMode::V6Only)
+            .precedence(Precedence::AllowWins)
+            .add_required_header("x-api-key")
+            .unwrap()
+            .max_header_count(Some(10))
+            .max_query_length(Some(64))
+            .add_static_dns_mapping("example.net", "1.1.1.1:443".parse().unwrap())
+            .unwrap()
+            .build();
+        assert_eq!(
+            acl.diff(&other).changed_settings,
+            vec![
+                (
+                    "ip_family_mode",
+                    "DualStack".to_string(),
+                    "V6Only".to_string()
+                ),
+                (
+                    "precedence",
+                    "DenyWins".to_string(),
+                    "AllowWins".to_string()
+                ),
+                ("required_headers", String::new(), "x-api-key".to_string()),
+                ("max_header_count", "none".to_string(), "10".to_string()),
+                ("max_query_length", "none".to_string(), "64".to_string()),
+                (
+                    "static_dns_mapping",
+                    String::new(),
+                    "example.net => 1.1.1.1:443".to_string()
+                ),
+            ]
+        );
+        assert!(!acl.diff(&other).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn diff_host_regex_acl() {
+        let acl = HttpAclBuilder::new().build();
+        let other = HttpAclBuilder::new()
+            .add_denied_host_regex(r"^.*\.internal$")
+            .unwrap()
+            .build();
+
+        let diff = acl.diff(&other);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].to_string(), r"denied host `/^.*\.internal$/`");
+        assert_eq!(other.diff(&acl).removed, diff.added);
+    }
+
+    #[test]
+    fn rule_counts_acl() {
+        use super::RuleCounts;
+
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_host("example.org")
+            .unwrap()
+            .add_denied_host("example.net")
+            .unwrap()
+            .add_denied_ip_range("1.1.1.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_port_range(8080..=8080)
+            .unwrap()
+            .remove_allowed_method("TRACE")
+            .add_denied_method("TRACE")
+            .unwrap()
+            .add_allowed_header("x-allowed", None)
+            .unwrap()
+            .add_allowed_url_path("/public")
+            .unwrap()
+            .add_allowed_url_path_for_method("GET", "/status")
+            .unwrap()
+            .add_denied_url_path("/admin")
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.rule_counts(),
+            RuleCounts {
+                allowed_methods: 8,
+                denied_methods: 1,
+                allowed_hosts: 2,
+                denied_hosts: 1,
+                allowed_port_ranges: 3,
+                denied_port_ranges: 0,
+                allowed_ip_ranges: 0,
+                denied_ip_ranges: 1,
+                allowed_headers: 1,
+                denied_headers: 0,
+                allowed_url_paths: 2,
+                denied_url_paths: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn combined_reason_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_ip_range("1.1.0.0/16".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_host("example.com")
+            .unwrap()
+            .host_acl_default(true)
+            .build();
+        let ip = "1.1.1.1".parse().ok();
+
+        let report = acl.evaluate_authority(&Authority::parse("example.com:443").unwrap(), ip);
+        assert!(report.is_denied());
+        assert_eq!(
+            report.get(Dimension::Ip),
+            Some(&AclClassification::AllowedUserAcl)
+        );
+        assert_eq!(
+            report.combined_reason(),
+            "The host decision governed the denial (host: denied_user, ip: allowed_user, port: allowed_user)."
+        );
+
+        let report = acl.evaluate_authority(&Authority::parse("example.org").unwrap(), ip);
+        assert!(report.is_allowed());
+        assert_eq!(
+            report.combined_reason(),
+            "Every decision allowed the request (host: allowed_default, ip: allowed_user)."
+        );
+    }
+
+    #[test]
+    fn max_query_length_acl() {
+        let acl = HttpAclBuilder::new().max_query_length(Some(8)).build();
+
+        assert_eq!(acl.max_query_length(), Some(8));
+        assert!(acl.is_url_query_allowed("a=123456").is_allowed());
+        assert_eq!(
+            acl.is_url_query_allowed("a=1234567"),
+            AclClassification::Denied("query too long".to_string())
+        );
+        assert!(HttpAclBuilder::new()
+            .build()
+            .is_url_query_allowed("a=1234567")
+            .is_allowed());
+    }
+
+    #[test]
+    fn clear_all_builder() {
+        let builder = HttpAclBuilder::new()
+            .http(false)
+            .https(false)
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_denied_ip_range("1.1.1.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_url_path("/public")
+            .unwrap()
+            .add_denied_url_path_for_method("POST", "/admin")
+            .unwrap()
+            .add_required_header("authorization")
+            .unwrap()
+            .max_query_length(Some(16))
+            .host_acl_default(true)
+            .url_path_acl_default(false)
+            .private_ip_ranges(true)
+            .precedence(Precedence::AllowWins);
+
+        assert_ne!(builder, HttpAclBuilder::new());
+        assert_eq!(builder.clear_all(), HttpAclBuilder::new());
+    }
+
+    #[test]
+    fn with_methods_last_writer_wins() {
+        let acl = HttpAclBuilder::new()
+            .add_denied_host("example.com")
+            .unwrap()
+            .with_allowed_host("example.com")
+            .with_allowed_host("example.com")
+            .with_allowed_host("not a host")
+            .with_denied_method("TRACE")
+            .with_allowed_method(HttpRequestMethod::CONNECT)
+            .build();
+
+        assert_eq!(
+            acl.is_host_allowed("example.com"),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(acl.rule_counts().allowed_hosts, 1);
+        assert_eq!(acl.rule_counts().denied_hosts, 0);
+        assert!(acl.is_method_allowed("TRACE").is_denied());
+        assert!(acl.is_method_allowed("CONNECT").is_allowed());
+
+        let acl = HttpAclBuilder::new()
+            .with_allowed_host("example.com")
+            .remove_allowed_method("GET")
+            .with_denied_method("GET")
+            .with_allowed_method("GET")
+            .build();
+        assert!(acl.is_method_allowed("GET").is_allowed());
+        assert!(acl.denied_methods().is_empty());
+    }
+
+    #[test]
+    fn normalize_builder() {
+        let mut builder = HttpAclBuilder::new()
+            .host_acl_default(true)
+            .allowed_hosts(vec![
+                "Example.COM".to_string(),
+                "example.com".to_string(),
+                "API.example.com".to_string(),
+            ])
+            .unwrap()
+            .add_denied_port_range(9000..=9100)
+            .unwrap()
+            .add_denied_port_range(8000..=8080)
+            .unwrap()
+            .add_denied_port_range(8050..=8100)
+            .unwrap()
+            .add_allowed_ip_range("1.1.2.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_ip_range("1.1.1.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_ip_range("1.1.1.128/25".parse::<IpNet>().unwrap())
+            .unwrap();
+
+        let warnings = builder.normalize();
+
+        let expected = HttpAclBuilder::new()
+            .host_acl_default(true)
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_host("api.example.com")
+            .unwrap()
+            .add_denied_port_range(8000..=8100)
+            .unwrap()
+            .add_denied_port_range(9000..=9100)
+            .unwrap()
+            .add_allowed_ip_range(("1.1.1.0", "1.1.1.255"))
+            .unwrap()
+            .add_allowed_ip_range(("1.1.2.0", "1.1.2.255"))
+            .unwrap();
+        assert_eq!(builder, expected);
+        assert_eq!(
+            warnings,
+            vec![
+                AclWarning::RedundantRule {
+                    dimension: Dimension::Host,
+                    rule: "example.com".to_string(),
+                },
+                AclWarning::RedundantRule {
+                    dimension: Dimension::Host,
+                    rule: "api.example.com".to_string(),
+                },
+            ]
+        );
+
+        let acl = builder.build();
+        assert!(acl.is_port_allowed(8090).is_denied());
+        assert!(acl
+            .is_ip_allowed(&"1.1.1.200".parse().unwrap())
+            .is_allowed());
+    }
+
+    #[test]
+    fn on_decision_acl() {
+        use std::sync::Mutex;
+
+        let decisions = Arc::new(Mutex::new(Vec::new()));
+        let recorded = decisions.clone();
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .on_decision(move |dimension, classification| {
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push((dimension.to_string(), classification.clone()));
+            })
+            .build();
+
+        assert!(acl.is_host_allowed("example.com").is_allowed());
+        assert!(acl.is_port_allowed(8080).is_denied());
+        assert_eq!(acl.min_allowed_port(), Some(80));
+        assert_eq!(
+            acl.explain(Dimension::Port, "8080"),
+            "port 8080 is denied by default as no port rule matched."
+        );
+        assert_eq!(
+            acl.explain(Dimension::Compound, "GET https://example.com"),
+            "compound GET https://example.com is allowed by default as no compound rule matched."
+        );
+
+        assert_eq!(
+            *decisions.lock().unwrap(),
+            vec![
+                ("host".to_string(), AclClassification::AllowedUserAcl),
+                ("port".to_string(), AclClassification::DeniedDefault),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    #[tracing_test::traced_test]
+    fn tracing_denial_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_denied_host("example.com")
+            .unwrap()
+            .build();
+
+        assert!(acl.is_port_allowed(443).is_allowed());
+        assert!(acl.is_host_allowed("example.com").is_denied());
+
+        assert!(logs_contain("http_acl::decision"));
+        assert!(logs_contain("category=\"host\""));
+        assert!(logs_contain("entity=example.com"));
+        assert!(logs_contain("reason_code=\"denied_user\""));
+        assert!(!logs_contain("category=\"port\""));
+    }
+
+    #[test]
+    fn scheme_acl_default() {
+        let acl = HttpAclBuilder::new().build();
+        assert_eq!(
+            acl.is_scheme_allowed("ftp"),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            acl.is_scheme_allowed("https"),
+            AclClassification::AllowedUserAcl
+        );
+
+        let acl = HttpAclBuilder::new()
+            .http(false)
+            .scheme_acl_default(true)
+            .build();
+        assert_eq!(
+            acl.is_scheme_allowed("ftp"),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.is_scheme_allowed("http"),
+            AclClassification::DeniedUserAcl
+        );
+    }
+
+    #[test]
+    fn is_valid_simple_acl() {
+        let acl = HttpAclBuilder::new().build_full(Some(Arc::new(
+            |scheme, _authority, method, port, _headers, body| {
+                if scheme == "https" && *method == HttpRequestMethod::GET && port == 443 {
+                    assert!(body.is_none());
+                    AclClassification::AllowedUserAcl
+                } else {
+                    AclClassification::Denied("only HTTPS is valid".to_string())
+                }
+            },
+        )));
+
+        let authority = Authority::parse("example.com").unwrap();
+        assert_eq!(
+            acl.is_valid_simple("https", &authority),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            acl.is_valid_simple("http", &authority),
+            AclClassification::Denied("only HTTPS is valid".to_string())
+        );
+        assert_eq!(
+            HttpAclBuilder::new()
+                .build()
+                .is_valid_simple("http", &authority),
+            AclClassification::AllowedDefault
+        );
+    }
+
+    #[test]
+    fn debug_url_paths_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_url_path("/public/{*rest}")
+            .unwrap()
+            .add_denied_url_path("/admin")
+            .unwrap()
+            .build();
+
+        let debug = format!("{:?}", acl);
+        assert!(
+            debug.contains(r#"allowed_url_paths: ["/public/{*rest}"]"#),
+            "{}",
+            debug
+        );
+        assert!(
+            debug.contains(r#"denied_url_paths: ["/admin"]"#),
+            "{}",
+            debug
+        );
+    }
+
+    #[test]
+    fn url_path_rules_equality() {
+        let builder = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap();
+        let acl = builder.clone().build();
+        let other = builder.add_denied_url_path("/admin").unwrap().build();
+
+        assert_ne!(acl, other);
+        assert_eq!(acl, acl.clone());
+    }
+
+    #[test]
+    fn deny_all_acl() {
+        let acl = HttpAclBuilder::deny_all().try_build().unwrap();
+        assert!(acl.check_url("GET", "https://example.com:443/").is_denied());
+        assert!(acl.is_scheme_allowed("https").is_denied());
+        assert!(acl.is_method_allowed("GET").is_denied());
+        assert!(acl.is_port_allowed(443).is_denied());
+        assert!(acl.is_url_path_allowed("/").is_denied());
+        assert!(acl.is_header_allowed("accept", "*/*").is_denied());
+
+        let acl = HttpAclBuilder::deny_all()
+            .https(true)
+            .add_allowed_method("GET")
+            .unwrap()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_port_range(443..=443)
+            .unwrap()
+            .add_allowed_url_path("/")
+            .unwrap()
+            .try_build()
+            .unwrap();
+        assert!(acl
+            .check_url("GET", "https://example.com:443/")
+            .is_allowed());
+        assert!(acl
+            .check_url("GET", "https://example.com/admin")
+            .is_denied());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn host_regex_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host_regex(r"^db-\d+\.internal$")
+            .unwrap()
+            .add_denied_host_regex(r"^db-0+\.internal$")
+            .unwrap()
+            .add_allowed_host("db-0.internal")
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.is_host_allowed("db-12.internal"),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            acl.is_host_allowed("db-x.internal"),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            acl.is_host_allowed("db-00.internal"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_host_allowed("db-0.internal"),
+            AclClassification::AllowedUserAcl
+        );
+
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_allowed_host_regex("db-(")
+                .unwrap_err(),
+            AddError::InvalidEntity("db-(".to_string())
+        );
+        assert_ne!(acl, HttpAclBuilder::new().build());
+    }
+
+    #[test]
+    fn header_policy_acl() {
+        let policy = HeaderPolicy::new()
+            .deny("X-Forwarded-For", HeaderMatcher::Any)
+            .deny("X-Forwarded-Host", HeaderMatcher::Any)
+            .deny("X-Original-URL", HeaderMatcher::Any)
+            .deny(
+                "Transfer-Encoding",
+                HeaderMatcher::Contains("chunked".to_string()),
+            )
+            .deny(
+                "Content-Type",
+                HeaderMatcher::Prefix("multipart/".to_string()),
+            )
+            .allow(
+                "Content-Type",
+                HeaderMatcher::Exact("application/json".to_string()),
+            );
+        let acl = HttpAclBuilder::new()
+            .header_policy(policy.clone())
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.is_header_allowed("x-forwarded-for", "127.0.0.1"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_header_allowed("X-Original-URL", "/admin"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_header_allowed("Transfer-Encoding", "gzip, chunked"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_header_allowed("Transfer-Encoding", "gzip"),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.is_header_allowed("Content-Type", "multipart/form-data"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_header_allowed("Content-Type", "application/json"),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            acl.is_header_allowed("Accept", "*/*"),
+            AclClassification::AllowedDefault
+        );
+
+        assert_eq!(
+            HttpAclBuilder::new()
+                .header_policy(policy.clone())
+                .unwrap()
+                .header_policy(policy)
+                .unwrap()
+                .build(),
+            acl
+        );
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_denied_header_matcher("x-forwarded-for", HeaderMatcher::Any)
+                .unwrap()
+                .header_policy(HeaderPolicy::new().allow("X-Forwarded-For", HeaderMatcher::Any))
+                .unwrap_err(),
+            AddError::AlreadyDenied
+        );
+    }
+
+    #[test]
+    fn invalid_authority_classification() {
+        assert_eq!(
+            AclClassification::from(Authority::parse("exa mple.com").unwrap_err()),
+            AclClassification::Denied("invalid authority: invalid host".to_string())
+        );
+        let classification =
+            AclClassification::from(Authority::parse("user@example.com").unwrap_err());
+        assert!(classification.is_denied());
+        assert_eq!(
+            classification.to_string(),
+            "The entity is denied because invalid authority: authority contains userinfo."
+        );
+    }
+
+    #[test]
+    fn authority_rules_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_authority("Example.com:8080")
+            .unwrap()
+            .add_allowed_host("example.org")
+            .unwrap()
+            .add_denied_authority("example.org:443")
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.is_authority_allowed(&Authority::parse("example.com:8080").unwrap()),
+            AclClassification::AllowedUserAcl
+        );
+        assert_eq!(
+            acl.is_authority_allowed(&Authority::parse("example.com:9090").unwrap()),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            acl.is_authority_allowed(&Authority::parse("example.org:443").unwrap()),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_authority_allowed(&Authority::parse("example.org:80").unwrap()),
+            AclClassification::AllowedUserAcl
+        );
+        assert!(acl
+            .evaluate_authority(&Authority::parse("example.com:8080").unwrap(), None)
+            .is_allowed());
+
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_allowed_authority("example.com:8080")
+                .unwrap()
+                .add_denied_authority("example.com:8080")
+                .unwrap_err(),
+            AddError::AlreadyAllowed
+        );
+        assert_eq!(
+            HttpAclBuilder::new()
+                .add_allowed_authority("example.com:port")
+                .unwrap_err(),
+            AddError::InvalidEntity("example.com:port".to_string())
+        );
+    }
+
+    #[test]
+    fn filter_allowed_ips_acl() {
+        let acl = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .add_denied_ip_range("1.0.0.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .build();
+        let ips = [
+            "1.1.1.1".parse().unwrap(),
+            "10.0.0.1".parse().unwrap(),
+            "1.0.0.1".parse().unwrap(),
+            "8.8.8.8".parse().unwrap(),
+            "127.0.0.1".parse().unwrap(),
+            "2606:4700:4700::1111".parse().unwrap(),
+        ];
+
+        let (allowed, denied) = acl.filter_allowed_ips(ips.iter());
+        assert_eq!(allowed, [ips[0], ips[3], ips[5]]);
+        assert_eq!(denied, [ips[1], ips[2], ips[4]]);
+        assert_eq!(acl.filter_allowed_ips(&[]), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn unspecified_ip_denied_by_default_acl() {
+        let acl = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .non_global_ip_ranges(true)
+            .build();
+
+        for ip in ["0.0.0.0", "::"] {
+            assert_eq!(
+                acl.is_ip_allowed(&ip.parse().unwrap()),
+                AclClassification::DeniedNotGlobal,
+                "{}",
+                ip
+            );
+        }
+
+        let acl = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .unspecified_ip_addresses(true)
+            .build();
+
+        for ip in ["0.0.0.0", "::"] {
+            assert!(
+                acl.is_ip_allowed(&ip.parse().unwrap()).is_allowed(),
+                "{}",
+                ip
+            );
+        }
+    }
+
+    #[test]
+    fn special_purpose_ip_acl() {
+        let acl = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .private_ip_ranges(true)
+            .build();
+
+        for ip in [
+            "100.64.0.1",
+            "100.127.255.254",
+            "192.0.0.8",
+            "198.18.0.1",
+            "198.19.255.254",
+            "64:ff9b::a00:1",
+            "64:ff9b:1::1",
+            "3fff::1",
+            "5f00::1",
+        ] {
+            assert_eq!(
+                acl.is_ip_allowed(&ip.parse().unwrap()),
+                AclClassification::DeniedNotGlobal,
+                "{}",
+                ip
+            );
+        }
+        for ip in ["100.128.0.1", "198.20.0.1", "2606:4700:4700::1111"] {
+            assert!(
+                acl.is_ip_allowed(&ip.parse().unwrap()).is_allowed(),
+                "{}",
+                ip
+            );
+        }
+    }
+
+    #[test]
+    fn ipv6_non_global_acl() {
+        let acl = HttpAclBuilder::new().ip_acl_default(true).build();
+
+        for ip in ["2001:db8::1", "fe80::1", "febf::1", "ff02::1", "ff0e::1"] {
+            assert_eq!(
+                acl.is_ip_allowed(&ip.parse().unwrap()),
+                AclClassification::DeniedNotGlobal,
+                "{}",
+                ip
+            );
+        }
+        // Unique local addresses are the IPv6 private ranges.
+        for ip in ["fc00::1", "fd12:3456::1"] {
+            assert_eq!(
+                acl.is_ip_allowed(&ip.parse().unwrap()),
+                AclClassification::DeniedPrivateRange,
+                "{}",
+                ip
+            );
+        }
+        assert!(acl
+            .is_ip_allowed(&"2606:4700:4700::1111".parse().unwrap())
+            .is_allowed());
+    }
+
+    #[test]
+    fn try_build_collect_acl() {
+        let builder = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_port_range(8000..=9000)
+            .unwrap();
+        assert!(builder.clone().try_build_collect().is_ok());
+
+        let builder = HttpAclBuilder::new()
+            .allowed_hosts(vec!["a.com".into(), "a.com".into()])
+            .unwrap()
+            .denied_hosts(vec!["b.com".into(), "b.com".into()])
+            .unwrap()
+            .allowed_port_ranges(vec![8000..=9000, 8000..=9000])
+            .unwrap();
+
+        assert_eq!(
+            builder.clone().try_build().unwrap_err(),
+            AddError::AlreadyAllowed
+        );
+        assert_eq!(
+            builder.try_build_collect().unwrap_err(),
+            vec![
+                AddError::AlreadyAllowed,
+                AddError::AlreadyDenied,
+                AddError::AlreadyAllowed
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_builder() {
+        let builder = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_url_path("/public/{*path}")
+            .unwrap();
+
+        assert_eq!(builder.validate(), Ok(()));
+        assert_eq!(builder.validate(), Ok(()));
+        assert!(builder
+            .build()
+            .is_url_path_allowed("/public/index.html")
+            .is_allowed());
+    }
+
+    #[test]
+    fn retain_builder() {
+        let builder = HttpAclBuilder::new()
+            .add_denied_host("a.evil.test")
+            .unwrap()
+            .add_denied_host("b.evil.test")
+            .unwrap()
+            .add_denied_host("evil.test.example.com")
+            .unwrap()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_denied_ip_range("1.0.0.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_ip_range("2.0.0.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_denied_port_range(8000..=8100)
+            .unwrap()
+            .retain_denied_hosts(|host| !host.ends_with(".evil.test"))
+            .retain_allowed_hosts(|host| host != "example.com")
+            .retain_denied_ip_ranges(|range| {
+                !range.contains(&"1.0.0.1".parse::<std::net::IpAddr>().unwrap())
+            })
+            .retain_allowed_port_ranges(|range| *range.start() == 443)
+            .retain_denied_port_ranges(|_| false);
+
+        let acl = builder.build();
+        assert_eq!(
+            acl.is_host_allowed("a.evil.test"),
+            AclClassification::DeniedDefault
+        );
+        assert_eq!(
+            acl.is_host_allowed("evil.test.example.com"),
+            AclClassification::DeniedUserAcl
+        );
+        assert_eq!(
+            acl.is_host_allowed("example.com"),
+            AclClassification::DeniedDefault
+        );
+        let counts = acl.rule_counts();
+        assert_eq!(counts.denied_hosts, 1);
+        assert_eq!(counts.allowed_hosts, 0);
+        assert_eq!(counts.denied_ip_ranges, 1);
+        assert_eq!(counts.allowed_port_ranges, 1);
+        assert_eq!(counts.denied_port_ranges, 0);
+    }
+
+    #[test]
+    fn expiring_denied_ip_range_acl() {
+        use std::sync::Mutex;
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let now = Arc::new(Mutex::new(start));
+        let clock = now.clone();
+        let mut acl = HttpAclBuilder::new()
+            .ip_acl_default(true)
+            .clock(move || *clock.lock().unwrap())
+            .add_denied_ip_range_until(
+                "1.1.1.1/32".parse::<IpNet>().unwrap(),
+                start + Duration::from_secs(600),
+            )
+            .unwrap()
+            .add_denied_ip_range_until(
+                "8.8.8.8/32".parse::<IpNet>().unwrap(),
+                start + Duration::from_secs(3600),
+            )
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()),
+            AclClassification::DeniedUserAcl
+        );
+
+        *now.lock().unwrap() += Duration::from_secs(601);
+        assert_eq!(
+            acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()),
+            AclClassification::AllowedDefault
+        );
+        assert_eq!(
+            acl.is_ip_allowed(&"8.8.8.8".parse().unwrap()),
+            AclClassification::DeniedUserAcl
+        );
+
+        assert_eq!(acl.expiring_denied_ip_ranges().len(), 2);
+        acl.prune_expired();
+        assert_eq!(acl.expiring_denied_ip_ranges().len(), 1);
+        assert_eq!(
+            acl.is_ip_allowed(&"8.8.8.8".parse().unwrap()),
+            AclClassification::DeniedUserAcl
+        );
+    }
+
+    #[cfg(feature = "tower")]
+    #[tokio::test]
+    async fn tower_predicate_acl() {
+        use super::{AclDenied, AclPredicate};
+        use tower::{filter::FilterLayer, service_fn, ServiceBuilder, ServiceExt};
+
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_denied_header("x-debug", None)
+            .unwrap()
+            .build();
+        let service = ServiceBuilder::new()
+            .layer(FilterLayer::new(AclPredicate::new(acl)))
+            .service(service_fn(|request: http::Request<()>| async move {
+                Ok::<_, tower::BoxError>(request.uri().to_string())
+            }));
+
+        let request = http::Request::get("https://example.com/").body(()).unwrap();
+        assert_eq!(
+            service.clone().oneshot(request).await.unwrap(),
+            "https://example.com/"
+        );
+
+        let request = http::Request::get("https://example.net/").body(()).unwrap();
+        let err = service.clone().oneshot(request).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<AclDenied>(),
+            Some(&AclDenied {
+                dimension: Dimension::Host,
+                classification: AclClassification::DeniedDefault,
+            })
+        );
+
+        let request = http::Request::get("https://example.com/")
+            .header("x-debug", "1")
+            .body(())
+            .unwrap();
+        let err = service.oneshot(request).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "header is denied - The entity is denied according to the denied ACL."
+        );
+    }
+
+    #[cfg(feature = "tower")]
+    #[test]
+    fn tower_predicate_full_checks_acl() {
+        use super::{AclDenied, AclPredicate};
+
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_required_header("x-api-key")
+            .unwrap()
+            .max_header_count(Some(2))
+            .add_allowed_rule(CompoundRule::new("https", "GET", "example.com"))
+            .unwrap()
+            .add_allowed_rule(CompoundRule::new("https", "DELETE", "example.com"))
+            .unwrap()
+            .build_full(Some(Arc::new(|_, _, method, _, _, _| {
+                if *method == HttpRequestMethod::DELETE {
+                    AclClassification::Denied("no deletes".to_string())
+                } else {
+                    AclClassification::AllowedDefault
+                }
+            })));
+        let predicate = AclPredicate::new(acl);
+
+        let request = http::Request::get("https://example.com/").body(()).unwrap();
+        assert_eq!(
+            predicate.first_denial(&request),
+            Some(AclDenied {
+                dimension: Dimension::Header,
+                classification: AclClassification::Denied(
+                    "missing required header x-api-key".to_string()
+                ),
+            })
+        );
+
+        let request = http::Request::get("https://example.com/")
+            .header("x-api-key", "key")
+            .body(())
+            .unwrap();
+        assert_eq!(predicate.first_denial(&request), None);
+
+        let request = http::Request::get("https://example.com/")
+            .header("x-api-key", "key")
+            .header("x-a", "1")
+            .header("x-b", "2")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            predicate.first_denial(&request),
+            Some(AclDenied {
+                dimension: Dimension::Header,
+                classification: AclClassification::Denied(
+                    "3 headers exceed the maximum header count of 2".to_string()
+                ),
+            })
+        );
+
+        let request = http::Request::delete("https://example.com/")
+            .header("x-api-key", "key")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            predicate.first_denial(&request),
+            Some(AclDenied {
+                dimension: Dimension::Request,
+                classification: AclClassification::Denied("no deletes".to_string()),
+            })
+        );
+
+        let request = http::Request::post("https://example.com/")
+            .header("x-api-key", "key")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            predicate.first_denial(&request),
+            Some(AclDenied {
+                dimension: Dimension::Compound,
+                classification: AclClassification::DeniedDefault,
+            })
+        );
+    }
+
+    #[test]
+    fn kill_switch_acl() {
+        let acl = HttpAclBuilder::new()
+            .add_allowed_host("example.com")
+            .unwrap()
+            .add_allowed_ip_range("1.1.1.0/24".parse::<IpNet>().unwrap())
+            .unwrap()
+            .add_allowed_header("x-api-key", None)
+            .unwrap()
+            .add_allowed_url_path("/api/*path")
+            .unwrap()
+            .add_required_header("x-api-key")
+            .unwrap()
+            .max_header_count(Some(10))
+            .build();
+        let authority = Authority::parse("example.com:443").unwrap();
+        let engaged = AclClassification::Denied("acl kill switch engaged".to_string());
+
+        let live = super::LiveHttpAcl::new(acl);
+        live.set_kill_switch(true);
+        let acl = live.snapshot();
+        assert!(acl.kill_switch_engaged());
+        assert_eq!(acl.is_scheme_allowed("https"), engaged);
+        assert_eq!(acl.is_method_allowed("GET"), engaged);
+        assert_eq!(acl.is_host_allowed("example.com"), engaged);
+        assert_eq!(acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()), engaged);
+        assert_eq!(acl.is_port_allowed(443), engaged);
+        assert_eq!(acl.is_authority_allowed(&authority), engaged);
+        assert_eq!(acl.is_url_path_allowed("/api/users"), engaged);
+        assert_eq!(acl.is_url_query_allowed("page=1"), engaged);
+        assert_eq!(acl.is_header_allowed("x-api-key", "secret"), engaged);
+        assert_eq!(
+            acl.are_required_headers_present([("x-api-key", "secret")].into_iter()),
+            engaged
+        );
+        assert_eq!(
+            acl.are_headers_within_limits([("x-api-key", "secret")]),
+            engaged
+        );
+        assert_eq!(acl.is_valid_simple("https", &authority), engaged);
+        assert_eq!(
+            acl.check_url("GET", "https://example.com/api/users"),
+            engaged
+        );
+        assert_eq!(
+            acl.explain(Dimension::Host, "example.com"),
+            "host example.com is denied because acl kill switch engaged."
+        );
+
+        live.update(|builder| builder.add_allowed_host("example.org"))
+            .unwrap();
+        assert!(live.snapshot().is_host_allowed("example.org").is_denied());
+
+        live.set_kill_switch(false);
+        let acl = live.snapshot();
+        assert!(!acl.kill_switch_engaged());
+        assert!(acl.is_host_allowed("example.com").is_allowed());
+        assert!(acl.is_host_allowed("example.org").is_allowed());
+        assert!(acl.is_ip_allowed(&"1.1.1.1".parse().unwrap()).is_allowed());
+        assert!(acl.is_url_path_allowed("/api/users").is_allowed());
+        assert!(acl
+            .check_url("GET", "https://example.com/api/users")
+            .is_allowed());
+    }
 }
@@ -0,0 +1,232 @@
+//! Contains the [`LiveHttpAcl`], an [`HttpAcl`] that can be updated while in use.
+
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::Instant;
+
+use crate::{
+    acl::{HttpAcl, HttpAclBuilder},
+    error::AddError,
+    utils::IntoIpRange,
+};
+
+/// A thread-safe [`HttpAcl`] that can be updated without rebuilding its users.
+///
+/// Each update rebuilds the ACL from the current one and atomically swaps it in, so a check
+/// holding a [`LiveHttpAcl::snapshot`] always sees a consistent ACL, either before or after
+/// the update. Updates are serialized, and clones share the same ACL.
+#[derive(Clone)]
+pub struct LiveHttpAcl {
+    acl: Arc<RwLock<Arc<HttpAcl>>>,
+    update_lock: Arc<Mutex<()>>,
+}
+
+impl std::fmt::Debug for LiveHttpAcl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveHttpAcl")
+            .field("acl", &self.snapshot())
+            .finish()
+    }
+}
+
+impl Default for LiveHttpAcl {
+    fn default() -> Self {
+        Self::new(HttpAcl::default())
+    }
+}
+
+impl From<HttpAcl> for LiveHttpAcl {
+    fn from(acl: HttpAcl) -> Self {
+        Self::new(acl)
+    }
+}
+
+impl LiveHttpAcl {
+    /// Create a new [`LiveHttpAcl`] starting with an ACL.
+    pub fn new(acl: HttpAcl) -> Self {
+        Self {
+            acl: Arc::new(RwLock::new(Arc::new(acl))),
+            update_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Returns the current ACL, which is not affected by later updates.
+    pub fn snapshot(&self) -> Arc<HttpAcl> {
+        self.acl
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the ACL.
+    pub fn store(&self, acl: HttpAcl) {
+        let _update = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.swap(acl);
+    }
+
+    /// Updates the ACL with the rules changed by `f`, as [`HttpAcl::rebuild`] does.
+    ///
+    /// The ACL is left unchanged if `f` or the validation of the new rules fails.
+    pub fn update(
+        &self,
+        f: impl FnOnce(HttpAclBuilder) -> Result<HttpAclBuilder, AddError>,
+    ) -> Result<(), AddError> {
+        let _update = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let acl = self.snapshot().rebuild(f)?;
+        self.swap(acl);
+        Ok(())
+    }
+
+    /// Swaps in a new ACL, only holding the write lock for the swap itself.
+    fn swap(&self, acl: HttpAcl) {
+        *self.acl.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(acl);
+    }
+
+    /// Adds a host to the allowed hosts.
+    pub fn add_allowed_host(&self, host: impl AsRef<str>) -> Result<(), AddError> {
+        self.update(|builder| builder.add_allowed_host(host))
+    }
+
+    /// Removes a host from the allowed hosts.
+    pub fn remove_allowed_host(&self, host: impl AsRef<str>) -> Result<(), AddError> {
+        self.update(|builder| Ok(builder.remove_allowed_host(host)))
+    }
+
+    /// Adds a host to the denied hosts.
+    pub fn add_denied_host(&self, host: impl AsRef<str>) -> Result<(), AddError> {
+        self.update(|builder| builder.add_denied_host(host))
+    }
+
+    /// Removes a host from the denied hosts.
+    pub fn remove_denied_host(&self, host: impl AsRef<str>) -> Result<(), AddError> {
+        self.update(|builder| Ok(builder.remove_denied_host(host)))
+    }
+
+    /// Adds an IP range to the denied IP ranges.
+    pub fn add_denied_ip_range<Ip: IntoIpRange>(&self, ip_range: Ip) -> Result<(), AddError> {
+        self.update(|builder| builder.add_denied_ip_range(ip_range))
+    }
+
+    /// Adds an IP range to the denied IP ranges until an expiry,
+    /// see [`HttpAclBuilder::add_denied_ip_range_until`].
+    pub fn add_denied_ip_range_until<Ip: IntoIpRange>(
+        &self,
+        ip_range: Ip,
+        expiry: Instant,
+    ) -> Result<(), AddError> {
+        self.update(|builder| builder.add_denied_ip_range_until(ip_range, expiry))
+    }
+
+    /// Removes an IP range from the denied IP ranges.
+    pub fn remove_denied_ip_range<Ip: IntoIpRange>(&self, ip_range: Ip) -> Result<(), AddError> {
+        self.update(|builder| builder.remove_denied_ip_range(ip_range))
+    }
+
+    /// Removes the expired IP ranges, see [`HttpAcl::prune_expired`].
+    pub fn prune_expired(&self) {
+        let _update = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut acl = HttpAcl::clone(&self.snapshot());
+        acl.prune_expired();
+        self.swap(acl);
+    }
+
+    /// Engages or releases the kill switch, see [`HttpAcl::set_kill_switch`].
+    pub fn set_kill_switch(&self, engaged: bool) {
+        let _update = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut acl = HttpAcl::clone(&self.snapshot());
+        acl.set_kill_switch(engaged);
+        self.swap(acl);
+    }
+
+    /// Retains only the denied IP ranges for which the predicate returns `true`.
+    pub fn retain_denied_ip_ranges(
+        &self,
+        f: impl FnMut(&RangeInclusive<IpAddr>) -> bool,
+    ) -> Result<(), AddError> {
+        self.update(|builder| Ok(builder.retain_denied_ip_ranges(f)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::acl::AclClassification;
+
+    #[test]
+    fn test_live_acl_updates_while_checking() {
+        let live = LiveHttpAcl::new(
+            HttpAclBuilder::new()
+                .add_allowed_host("example.com")
+                .unwrap()
+                .build(),
+        );
+
+        let checkers = (0..4)
+            .map(|_| {
+                let live = live.clone();
+                thread::spawn(move || {
+                    for _ in 0..1_000 {
+                        let acl = live.snapshot();
+                        // A snapshot never sees half of an update, so exactly one host is allowed.
+                        assert_ne!(
+                            acl.is_host_allowed("example.com").is_allowed(),
+                            acl.is_host_allowed("example.net").is_allowed()
+                        );
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for _ in 0..100 {
+            live.update(|builder| {
+                builder
+                    .remove_allowed_host("example.com")
+                    .add_denied_host("example.com")?
+                    .remove_denied_host("example.net")
+                    .add_allowed_host("example.net")
+            })
+            .unwrap();
+            live.update(|builder| {
+                builder
+                    .remove_denied_host("example.com")
+                    .add_allowed_host("example.com")?
+                    .remove_allowed_host("example.net")
+                    .add_denied_host("example.net")
+            })
+            .unwrap();
+        }
+        for checker in checkers {
+            checker.join().unwrap();
+        }
+
+        live.add_denied_host("example.org").unwrap();
+        assert_eq!(
+            live.add_allowed_host("example.org").unwrap_err(),
+            AddError::AlreadyDenied
+        );
+        assert_eq!(
+            live.snapshot().is_host_allowed("example.org"),
+            AclClassification::DeniedUserAcl
+        );
+        live.remove_denied_host("example.org").unwrap();
+        assert_eq!(
+            live.snapshot().is_host_allowed("example.org"),
+            AclClassification::DeniedDefault
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Contains the [`AclPredicate`], a [`tower::filter::Predicate`] that enforces an [`HttpAcl`].
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use tower::{filter::Predicate, BoxError};
+
+use crate::acl::{AclClassification, Dimension, HttpAcl, HttpRequestMethod};
+use crate::utils::authority::Authority;
+
+/// A [`tower::filter::Predicate`] that rejects the requests denied by an [`HttpAcl`].
+///
+/// Use it with [`tower::filter::FilterLayer`] to enforce the ACL in a `ServiceBuilder` stack.
+/// The URI and method of a request are checked with [`HttpAcl::evaluate_uri`], followed by
+/// the header limits, each of its headers, the required headers and the validate function,
+/// as the reqwest middleware does. The URI of the request needs to include a scheme and a host.
+#[derive(Clone, Debug)]
+pub struct AclPredicate(pub Arc<HttpAcl>);
+
+impl AclPredicate {
+    /// Create a new predicate enforcing an ACL.
+    pub fn new(acl: HttpAcl) -> Self {
+        Self(Arc::new(acl))
+    }
+
+    /// Returns the first denied dimension of a request.
+    ///
+    /// The validate function is called without a body. An async validate function cannot
+    /// be called by a predicate, so every request is denied if the ACL was built with one.
+    pub fn first_denial<B>(&self, request: &http::Request<B>) -> Option<AclDenied> {
+        let acl = &self.0;
+        let report = acl.evaluate_uri(request.uri(), request.method());
+        let headers = request.headers();
+        let limits = std::iter::once_with(|| {
+            (
+                Dimension::Header,
+                acl.are_headers_within_limits(
+                    headers
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.as_bytes())),
+                ),
+            )
+        });
+        let header_rules = headers.iter().map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            (
+                Dimension::Header,
+                acl.is_header_allowed(name.as_str(), &value),
+            )
+        });
+        let required = std::iter::once_with(|| {
+            (
+                Dimension::Header,
+                acl.are_required_headers_present(
+                    headers
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.to_str().unwrap_or_default())),
+                ),
+            )
+        });
+        let valid = std::iter::once_with(|| (Dimension::Request, self.is_valid(request)));
+        let denial = report
+            .iter()
+            .cloned()
+            .chain(limits)
+            .chain(header_rules)
+            .chain(required)
+            .chain(valid)
+            .find(|(_, classification)| classification.is_denied());
+        denial.map(|(dimension, classification)| AclDenied {
+            dimension,
+            classification,
+        })
+    }
+
+    /// Returns whether a request is valid according to the validate function of the ACL.
+    fn is_valid<B>(&self, request: &http::Request<B>) -> AclClassification {
+        let uri = request.uri();
+        let mut authority = match Authority::parse(uri.host().unwrap_or_default()) {
+            Ok(authority) => authority,
+            Err(e) => return e.into(),
+        };
+        let port = uri
+            .port_u16()
+            .or(HttpAcl::default_port(uri.scheme_str().unwrap_or_default()))
+            .unwrap_or_default();
+        authority.port = Some(port);
+        let headers = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        self.0.is_valid(
+            uri.scheme_str().unwrap_or_default(),
+            &authority,
+            &HttpRequestMethod::from(request.method()),
+            port,
+            Box::new(headers),
+            None,
+        )
+    }
+}
+
+impl From<HttpAcl> for AclPredicate {
+    fn from(acl: HttpAcl) -> Self {
+        Self::new(acl)
+    }
+}
+
+impl From<Arc<HttpAcl>> for AclPredicate {
+    fn from(acl: Arc<HttpAcl>) -> Self {
+        Self(acl)
+    }
+}
+
+impl<B> Predicate<http::Request<B>> for AclPredicate {
+    type Request = http::Request<B>;
+
+    fn check(&mut self, request: http::Request<B>) -> Result<Self::Request, BoxError> {
+        match self.first_denial(&request) {
+            Some(denied) => Err(Box::new(denied)),
+            None => Ok(request),
+        }
+    }
+}
+
+/// The error returned by [`AclPredicate`] for a denied request.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+#[error("{dimension} is denied - {classification}")]
+pub struct AclDenied {
+    /// The first denied dimension of the request.
+    pub dimension: Dimension,
+    /// The classification of the denied dimension.
+    pub classification: AclClassification,
+}
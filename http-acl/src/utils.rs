@@ -2,13 +2,13 @@
 
 use std::collections::HashSet;
 use std::hash::Hash;
-use std::net::IpAddr;
-use std::ops::RangeInclusive;
 
-use ipnet::IpNet;
+pub(crate) use http_acl_core::{ip, range};
+pub use http_acl_core::{ip_range_to_ip_nets, saturating_range, IntoIpRange};
 
 pub mod authority;
-pub(crate) mod ip;
+pub(crate) mod host;
+pub(crate) mod method;
 pub mod url;
 
 // Taken from https://stackoverflow.com/a/46767732
@@ -21,37 +21,8 @@ where
     iter.into_iter().all(move |x| uniq.insert(x))
 }
 
-/// Converts a type into an IP range.
-pub trait IntoIpRange {
-    /// Converts the type into an IP range.
-    fn into_range(self) -> Option<RangeInclusive<IpAddr>>;
-
-    /// Validates the IP range.
-    fn validate(ip_range: RangeInclusive<IpAddr>) -> Option<RangeInclusive<IpAddr>> {
-        if ip_range.start() <= ip_range.end() {
-            Some(ip_range)
-        } else {
-            None
-        }
-    }
-}
-
-impl IntoIpRange for IpNet {
-    fn into_range(self) -> Option<RangeInclusive<IpAddr>> {
-        let start = self.network();
-        let end = self.broadcast();
-        Some(start..=end)
-    }
-}
-
-impl IntoIpRange for RangeInclusive<IpAddr> {
-    fn into_range(self) -> Option<RangeInclusive<IpAddr>> {
-        Self::validate(self)
-    }
-}
-
-impl IntoIpRange for (IpAddr, IpAddr) {
-    fn into_range(self) -> Option<RangeInclusive<IpAddr>> {
-        Self::validate(self.0..=self.1)
-    }
+/// Removes the duplicate elements of a list, keeping the first occurrence of each.
+pub(crate) fn dedup<T: Eq + Hash + Clone>(list: &mut Vec<T>) {
+    let mut uniq = HashSet::new();
+    list.retain(|x| uniq.insert(x.clone()));
 }
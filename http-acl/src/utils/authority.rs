@@ -12,16 +12,15 @@ pub fn is_valid_host(host: &str) -> bool {
 pub struct Authority {
     /// The host, which can be a domain or an IP address.
     pub host: Host,
-    /// The port.
-    pub port: u16,
+    /// The port, if specified.
+    pub port: Option<u16>,
 }
 
 impl std::fmt::Display for Authority {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.port == 0 {
-            write!(f, "{}", self.host)
-        } else {
-            write!(f, "{}:{}", self.host, self.port)
+        match self.port {
+            Some(port) => write!(f, "{}:{}", self.host, port),
+            None => write!(f, "{}", self.host),
         }
     }
 }
@@ -53,61 +52,107 @@ impl std::fmt::Display for Host {
 pub enum AuthorityError {
     /// The host is invalid.
     InvalidHost,
+    /// The authority contains userinfo, such as `user:pass@`.
+    ContainsUserinfo,
 }
 
 impl std::fmt::Display for AuthorityError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AuthorityError::InvalidHost => write!(f, "invalid host"),
+            AuthorityError::ContainsUserinfo => write!(f, "authority contains userinfo"),
         }
     }
 }
 
 impl Authority {
     /// Parses an authority from a string.
+    ///
+    /// The host is normalized with [`normalize_host`]. An authority with userinfo,
+    /// such as `user:pass@example.com`, returns [`AuthorityError::ContainsUserinfo`],
+    /// use [`Authority::parse_stripping_userinfo`] to ignore it instead.
+    ///
+    /// The zone identifier of an IPv6 address, such as `%eth0` in `[fe80::1%eth0]:80`,
+    /// is stripped, as the ACL applies to the address regardless of its interface.
     pub fn parse(authority: &str) -> Result<Self, AuthorityError> {
+        if authority.contains('@') {
+            return Err(AuthorityError::ContainsUserinfo);
+        }
+
+        if let Some(authority) = strip_zone_id(authority) {
+            return match Self::parse(&authority?) {
+                Ok(authority) if matches!(authority.host, Host::Ip(std::net::IpAddr::V6(_))) => {
+                    Ok(authority)
+                }
+                _ => Err(AuthorityError::InvalidHost),
+            };
+        }
+
         if let Ok(addr) = authority.parse::<std::net::SocketAddr>() {
             return Ok(Self {
                 host: Host::Ip(addr.ip()),
-                port: addr.port(),
+                port: Some(addr.port()),
             });
         }
 
-        if let Ok(ip) = authority.parse::<std::net::IpAddr>() {
-            return Ok(Self {
-                host: Host::Ip(ip),
-                port: 0,
-            });
+        if let Some(host) = normalize_host(authority) {
+            return Ok(Self { host, port: None });
         }
 
-        match url::Host::parse(authority) {
-            Ok(url::Host::Domain(domain)) => Ok(Self {
-                host: Host::Domain(domain),
-                port: 0,
-            }),
-            Ok(url::Host::Ipv4(ip)) => Ok(Self {
-                host: Host::Ip(ip.into()),
-                port: 0,
-            }),
-            Ok(url::Host::Ipv6(ip)) => Ok(Self {
-                host: Host::Ip(ip.into()),
-                port: 0,
-            }),
-            Err(_) => {
-                if let Some((domain, port)) = authority.split_once(':') {
-                    if let Ok(port) = port.parse::<u16>() {
-                        url::Host::parse(domain).map_err(|_| AuthorityError::InvalidHost)?;
-
-                        return Ok(Self {
-                            host: Host::Domain(domain.to_string()),
-                            port,
-                        });
-                    }
-                }
-
-                Err(AuthorityError::InvalidHost)
+        if let Some((host, port)) = authority.rsplit_once(':') {
+            if let (Some(host), Ok(port)) = (normalize_host(host), port.parse::<u16>()) {
+                return Ok(Self {
+                    host,
+                    port: Some(port),
+                });
             }
         }
+
+        Err(AuthorityError::InvalidHost)
+    }
+
+    /// Parses an authority from a string, stripping its userinfo if any.
+    ///
+    /// Everything up to the last `@` is stripped, as the userinfo cannot contain an unescaped `@`.
+    pub fn parse_stripping_userinfo(authority: &str) -> Result<Self, AuthorityError> {
+        Self::parse(
+            authority
+                .rsplit_once('@')
+                .map_or(authority, |(_, authority)| authority),
+        )
+    }
+}
+
+/// Strips the zone identifier of an IPv6 address from an authority, if it has one.
+fn strip_zone_id(authority: &str) -> Option<Result<String, AuthorityError>> {
+    let (address, zone) = authority.split_once('%')?;
+    let (zone, rest) = match zone.split_once(']') {
+        Some((zone, rest)) => (zone, format!("]{}", rest)),
+        None => (zone, String::new()),
+    };
+    if zone.is_empty()
+        || !zone
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Some(Err(AuthorityError::InvalidHost));
+    }
+    Some(Ok(format!("{}{}", address, rest)))
+}
+
+/// Normalizes a host into a domain or an IP address.
+///
+/// Alternate IPv4 encodings such as `2130706433`, `0x7f.0.0.1` and `0177.0.0.1`
+/// are converted to their canonical IP address, as URL parsers resolve them.
+pub fn normalize_host(host: &str) -> Option<Host> {
+    if let Ok(ip) = host.parse::<std::net::IpAddr>() {
+        return Some(Host::Ip(ip));
+    }
+
+    match url::Host::parse(host).ok()? {
+        url::Host::Domain(domain) => Some(Host::Domain(domain)),
+        url::Host::Ipv4(ip) => Some(Host::Ip(ip.into())),
+        url::Host::Ipv6(ip) => Some(Host::Ip(ip.into())),
     }
 }
 
@@ -131,64 +176,193 @@ mod tests {
             Authority::parse("localhost").unwrap(),
             Authority {
                 host: Host::Domain("localhost".to_string()),
-                port: 0
+                port: None
             }
         );
         assert_eq!(
             Authority::parse("localhost:5000").unwrap(),
             Authority {
                 host: Host::Domain("localhost".to_string()),
-                port: 5000
+                port: Some(5000)
             }
         );
         assert_eq!(
             Authority::parse("example.com").unwrap(),
             Authority {
                 host: Host::Domain("example.com".to_string()),
-                port: 0
+                port: None
             }
         );
         assert_eq!(
             Authority::parse("example.com:443").unwrap(),
             Authority {
                 host: Host::Domain("example.com".to_string()),
-                port: 443
+                port: Some(443)
             }
         );
         assert_eq!(
             Authority::parse("127.0.0.1").unwrap(),
             Authority {
                 host: Host::Ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
-                port: 0
+                port: None
             }
         );
         assert_eq!(
             Authority::parse("127.0.0.1:80").unwrap(),
             Authority {
                 host: Host::Ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
-                port: 80
+                port: Some(80)
             }
         );
         assert_eq!(
             Authority::parse("::1").unwrap(),
             Authority {
                 host: Host::Ip(IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1))),
-                port: 0
+                port: None
             }
         );
         assert_eq!(
             Authority::parse("[::1]").unwrap(),
             Authority {
                 host: Host::Ip(IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1))),
-                port: 0
+                port: None
             }
         );
         assert_eq!(
             Authority::parse("[::1]:80").unwrap(),
             Authority {
                 host: Host::Ip(IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1))),
-                port: 80
+                port: Some(80)
+            }
+        );
+    }
+
+    #[test]
+    fn test_authority_parse_alternate_ipv4() {
+        let localhost = Host::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        for host in [
+            "2130706433",
+            "0x7f.0.0.1",
+            "0x7f000001",
+            "0177.0.0.1",
+            "127.1",
+        ] {
+            assert_eq!(normalize_host(host), Some(localhost.clone()), "{}", host);
+            assert_eq!(
+                Authority::parse(host).unwrap(),
+                Authority {
+                    host: localhost.clone(),
+                    port: None
+                }
+            );
+        }
+        assert_eq!(
+            Authority::parse("0x7f.0.0.1:8080").unwrap(),
+            Authority {
+                host: localhost,
+                port: Some(8080)
+            }
+        );
+        assert_eq!(
+            normalize_host("Example.COM"),
+            Some(Host::Domain("example.com".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_authority_parse_userinfo() {
+        assert_eq!(
+            Authority::parse("user@example.com"),
+            Err(AuthorityError::ContainsUserinfo)
+        );
+        assert_eq!(
+            Authority::parse("user:pass@example.com:8080"),
+            Err(AuthorityError::ContainsUserinfo)
+        );
+        assert_eq!(
+            Authority::parse_stripping_userinfo("user@example.com").unwrap(),
+            Authority {
+                host: Host::Domain("example.com".to_string()),
+                port: None
             }
         );
+        assert_eq!(
+            Authority::parse_stripping_userinfo("user:pass@example.com:8080").unwrap(),
+            Authority {
+                host: Host::Domain("example.com".to_string()),
+                port: Some(8080)
+            }
+        );
+        assert_eq!(
+            Authority::parse_stripping_userinfo("a@b@127.0.0.1:80").unwrap(),
+            Authority {
+                host: Host::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+                port: Some(80)
+            }
+        );
+        assert_eq!(
+            Authority::parse("example.com:8080").unwrap(),
+            Authority {
+                host: Host::Domain("example.com".to_string()),
+                port: Some(8080)
+            }
+        );
+        assert_eq!(
+            Authority::parse_stripping_userinfo("example.com:8080"),
+            Authority::parse("example.com:8080")
+        );
+    }
+
+    #[test]
+    fn test_authority_parse_zone_id() {
+        let link_local = Host::Ip("fe80::1".parse().unwrap());
+        assert_eq!(
+            Authority::parse("[fe80::1%eth0]:80").unwrap(),
+            Authority {
+                host: link_local.clone(),
+                port: Some(80)
+            }
+        );
+        assert_eq!(
+            Authority::parse("[fe80::1%25eth0]").unwrap(),
+            Authority {
+                host: link_local.clone(),
+                port: None
+            }
+        );
+        assert_eq!(
+            Authority::parse("fe80::1%eth0").unwrap(),
+            Authority {
+                host: link_local,
+                port: None
+            }
+        );
+        assert_eq!(
+            Authority::parse("example.com%eth0"),
+            Err(AuthorityError::InvalidHost)
+        );
+        assert_eq!(
+            Authority::parse("127.0.0.1%eth0:80"),
+            Err(AuthorityError::InvalidHost)
+        );
+        assert_eq!(
+            Authority::parse("[fe80::1%]:80"),
+            Err(AuthorityError::InvalidHost)
+        );
+    }
+
+    #[test]
+    fn test_authority_parse_port() {
+        assert_eq!(Authority::parse("example.com").unwrap().port, None);
+        assert_eq!(Authority::parse("example.com:0").unwrap().port, Some(0));
+        assert_eq!(Authority::parse("[::1]:0").unwrap().port, Some(0));
+        assert_eq!(
+            Authority::parse("example.com:0").unwrap().to_string(),
+            "example.com:0"
+        );
+        assert_eq!(
+            Authority::parse("example.com").unwrap().to_string(),
+            "example.com"
+        );
     }
 }
@@ -0,0 +1,214 @@
+//! Utilities for matching hosts.
+
+use std::collections::{HashMap, HashSet};
+
+/// Returns whether a host matches a host rule.
+///
+/// A wildcard host such as `*.example.com` matches any subdomain of
+/// `example.com`, and `example.com` itself if the apex is included.
+/// A label glob such as `api-*.example.com` matches hosts with the same labels,
+/// where the `*` matches one or more characters within its label.
+pub(crate) fn matches_host(pattern: &str, host: &str, wildcard_includes_apex: bool) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(apex) => {
+            host.strip_suffix(apex)
+                .and_then(|subdomain| subdomain.strip_suffix('.'))
+                .is_some_and(|subdomain| !subdomain.is_empty())
+                || (wildcard_includes_apex && host == apex)
+        }
+        None if pattern.contains('*') => matches_label_glob(pattern, host),
+        None => pattern == host,
+    }
+}
+
+/// Returns whether a host matches a label glob, label by label.
+fn matches_label_glob(pattern: &str, host: &str) -> bool {
+    let mut labels = host.split('.');
+    pattern.split('.').all(|pattern_label| {
+        labels
+            .next()
+            .is_some_and(|label| match pattern_label.split_once('*') {
+                Some((prefix, suffix)) => {
+                    label.len() > prefix.len() + suffix.len()
+                        && label.starts_with(prefix)
+                        && label.ends_with(suffix)
+                }
+                None => pattern_label == label,
+            })
+    }) && labels.next().is_none()
+}
+
+/// An index over a list of host rules, matching exact hosts in `O(1)`
+/// and wildcard hosts in `O(labels)`.
+///
+/// Wildcard hosts are keyed by their apex, so a host is matched by looking up each of
+/// its parent domains. Only the label glob host rules are scanned.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct HostIndex {
+    exact: HashSet<String>,
+    label_globs: Vec<String>,
+    wildcards: HashMap<String, String>,
+}
+
+impl HostIndex {
+    /// Creates an index over a list of host rules.
+    pub(crate) fn new(hosts: &[String]) -> Self {
+        let mut index = Self::default();
+        for host in hosts {
+            if let Some(apex) = host.strip_prefix("*.") {
+                index
+                    .wildcards
+                    .entry(apex.to_string())
+                    .or_insert_with(|| host.clone());
+            } else if host.contains('*') {
+                index.label_globs.push(host.clone());
+            } else {
+                index.exact.insert(host.clone());
+            }
+        }
+        index
+    }
+
+    /// Returns the host rule that matches a host.
+    ///
+    /// An exact match is preferred over a label glob, which is preferred over a wildcard.
+    /// Among the wildcards, the one with the longest apex is preferred.
+    pub(crate) fn find(&self, host: &str, wildcard_includes_apex: bool) -> Option<&String> {
+        self.exact
+            .get(host)
+            .or_else(|| {
+                self.label_globs
+                    .iter()
+                    .find(|pattern| matches_label_glob(pattern, host))
+            })
+            .or_else(|| self.find_wildcard(host, wildcard_includes_apex))
+    }
+
+    /// Returns the wildcard host rule with the longest apex that matches a host.
+    fn find_wildcard(&self, host: &str, wildcard_includes_apex: bool) -> Option<&String> {
+        if self.wildcards.is_empty() {
+            return None;
+        }
+        let apex = wildcard_includes_apex
+            .then(|| self.wildcards.get(host))
+            .flatten();
+        apex.or_else(|| {
+            host.match_indices('.')
+                .filter(|(i, _)| *i > 0)
+                .find_map(|(i, _)| self.wildcards.get(&host[i + 1..]))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_glob() {
+        assert!(matches_host(
+            "api-*.example.com",
+            "api-1.example.com",
+            false
+        ));
+        assert!(matches_host(
+            "*-api.example.com",
+            "eu-api.example.com",
+            false
+        ));
+        assert!(!matches_host(
+            "api-*.example.com",
+            "x.api-1.example.com",
+            false
+        ));
+        assert!(!matches_host(
+            "api-*.example.com",
+            "api-.example.com",
+            false
+        ));
+        assert!(!matches_host(
+            "api-*.example.com",
+            "api-1.example.org",
+            false
+        ));
+        assert!(!matches_host(
+            "api-*.example.com",
+            "web-1.example.com",
+            false
+        ));
+
+        let index = HostIndex::new(&[
+            "*.example.com".to_string(),
+            "api-*.example.com".to_string(),
+            "api-1.example.com".to_string(),
+        ]);
+        assert_eq!(
+            index.find("api-1.example.com", false).unwrap(),
+            "api-1.example.com"
+        );
+        assert_eq!(
+            index.find("api-2.example.com", false).unwrap(),
+            "api-*.example.com"
+        );
+        assert_eq!(
+            index.find("x.api-2.example.com", false).unwrap(),
+            "*.example.com"
+        );
+    }
+
+    #[test]
+    fn test_host_index_matches_scan() {
+        let hosts = (0..10_000)
+            .map(|i| match i % 100 {
+                0 => format!("*.wildcard{}.com", i),
+                _ => format!("host{}.example.com", i),
+            })
+            .collect::<Vec<_>>();
+        let index = HostIndex::new(&hosts);
+
+        for i in (0..12_000).step_by(7) {
+            for host in [
+                format!("host{}.example.com", i),
+                format!("a.wildcard{}.com", i),
+                format!("wildcard{}.com", i),
+            ] {
+                for apex in [false, true] {
+                    assert_eq!(
+                        index.find(&host, apex),
+                        hosts
+                            .iter()
+                            .find(|pattern| matches_host(pattern, &host, apex)),
+                        "{}",
+                        host
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_host_index_large_wildcard_list() {
+        let hosts = (0..20_000)
+            .map(|i| format!("*.tenant{}.example.com", i))
+            .chain(["*.example.com".to_string()])
+            .collect::<Vec<_>>();
+        let index = HostIndex::new(&hosts);
+
+        for i in (0..25_000).step_by(13) {
+            let host = format!("a.b.tenant{}.example.com", i);
+            let expected = if i < 20_000 {
+                format!("*.tenant{}.example.com", i)
+            } else {
+                "*.example.com".to_string()
+            };
+            assert_eq!(index.find(&host, false), Some(&expected));
+        }
+        assert_eq!(index.find("example.com", false), None);
+        assert_eq!(
+            index.find("tenant7.example.com", true).unwrap(),
+            "*.tenant7.example.com"
+        );
+        assert_eq!(index.find(".example.com", false), None);
+        assert_eq!(index.find("example.org", true), None);
+    }
+}
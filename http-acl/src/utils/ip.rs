@@ -1,3 +0,0 @@
-mod global_ip;
-
-pub(crate) use global_ip::{is_global_ip, is_private_ip};
@@ -0,0 +1,103 @@
+//! Utilities for matching methods.
+
+use std::collections::HashSet;
+
+use crate::acl::HttpRequestMethod;
+
+/// The bits of the standard methods, every method but [`HttpRequestMethod::OTHER`].
+const STANDARD_METHODS: u16 = (1 << 9) - 1;
+
+/// An index over a list of methods, matching the standard methods with a bit test.
+///
+/// The standard methods are stored as a bitset keyed by their position in
+/// [`HttpRequestMethod`], and only the [`HttpRequestMethod::OTHER`] methods are hashed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct MethodIndex {
+    standard: u16,
+    other: HashSet<HttpRequestMethod>,
+}
+
+impl MethodIndex {
+    /// Creates an index over a list of methods.
+    pub(crate) fn new(methods: &[HttpRequestMethod]) -> Self {
+        let mut index = Self::default();
+        for method in methods {
+            match method {
+                HttpRequestMethod::OTHER(_) => {
+                    index.other.insert(method.clone());
+                }
+                _ => index.standard |= 1 << method.index(),
+            }
+        }
+        index
+    }
+
+    /// Creates an index over every standard method.
+    pub(crate) fn standard() -> Self {
+        Self {
+            standard: STANDARD_METHODS,
+            other: HashSet::new(),
+        }
+    }
+
+    /// Returns whether the index contains a method.
+    pub(crate) fn contains(&self, method: &HttpRequestMethod) -> bool {
+        match method {
+            HttpRequestMethod::OTHER(_) => !self.other.is_empty() && self.other.contains(method),
+            _ => self.standard & (1 << method.index()) != 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_index_matches_list() {
+        let methods = [
+            HttpRequestMethod::CONNECT,
+            HttpRequestMethod::DELETE,
+            HttpRequestMethod::GET,
+            HttpRequestMethod::HEAD,
+            HttpRequestMethod::OPTIONS,
+            HttpRequestMethod::PATCH,
+            HttpRequestMethod::POST,
+            HttpRequestMethod::PUT,
+            HttpRequestMethod::TRACE,
+            HttpRequestMethod::OTHER("PURGE".to_string()),
+            HttpRequestMethod::OTHER("MKCOL".to_string()),
+        ];
+        let probes = methods
+            .iter()
+            .cloned()
+            .chain([
+                HttpRequestMethod::OTHER("purge".to_string()),
+                HttpRequestMethod::OTHER("LOCK".to_string()),
+            ])
+            .collect::<Vec<_>>();
+
+        for subset in 0..1u32 << methods.len() {
+            let list = methods
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| subset & (1 << i) != 0)
+                .map(|(_, method)| method.clone())
+                .collect::<Vec<_>>();
+            let index = MethodIndex::new(&list);
+            for probe in &probes {
+                assert_eq!(
+                    index.contains(probe),
+                    list.contains(probe),
+                    "{:?} in {:?}",
+                    probe,
+                    list
+                );
+            }
+        }
+
+        let standard = MethodIndex::standard();
+        assert_eq!(standard, MethodIndex::new(&methods[..9]));
+        assert!(!standard.contains(&HttpRequestMethod::OTHER("PURGE".to_string())));
+    }
+}
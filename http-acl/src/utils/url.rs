@@ -1,5 +1,6 @@
 //! URL utilities.
 
+use percent_encoding::percent_decode_str;
 use url::Url;
 
 /// Get the path from a URL.
@@ -7,3 +8,112 @@ pub fn get_url_path(url: &str) -> Option<String> {
     let url = Url::parse(url).ok()?;
     Some(url.path().to_string())
 }
+
+/// Normalize a URL path by resolving `.` and `..` segments and collapsing duplicate slashes.
+///
+/// A trailing slash is kept, use [`strip_trailing_slash`] to remove it.
+pub fn normalize_url_path(url_path: &str) -> String {
+    let mut segments = Vec::new();
+    for segment in url_path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    let trailing_slash = url_path.ends_with('/') && !segments.is_empty();
+    format!(
+        "/{}{}",
+        segments.join("/"),
+        if trailing_slash { "/" } else { "" }
+    )
+}
+
+/// Strip the trailing slashes of a URL path, keeping the root path `/`.
+pub fn strip_trailing_slash(url_path: &str) -> &str {
+    match url_path.trim_end_matches('/') {
+        "" if url_path.starts_with('/') => "/",
+        stripped => stripped,
+    }
+}
+
+/// Percent-decode a URL path.
+///
+/// Encoded slashes (`%2F`) are kept encoded so that decoding
+/// never changes the segment boundaries of the path.
+pub fn decode_url_path(url_path: &str) -> String {
+    let mut decoded = String::with_capacity(url_path.len());
+    let mut rest = url_path;
+    while let Some(index) = find_encoded_slash(rest) {
+        decoded.push_str(&percent_decode_str(&rest[..index]).decode_utf8_lossy());
+        decoded.push_str("%2F");
+        rest = &rest[index + 3..];
+    }
+    decoded.push_str(&percent_decode_str(rest).decode_utf8_lossy());
+    decoded
+}
+
+/// Percent-decode a URL query, decoding `+` as a space.
+pub fn decode_url_query(query: &str) -> String {
+    percent_decode_str(&query.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// Finds the index of the first encoded slash in a string.
+fn find_encoded_slash(s: &str) -> Option<usize> {
+    s.as_bytes()
+        .windows(3)
+        .position(|w| w[0] == b'%' && w[1] == b'2' && w[2].eq_ignore_ascii_case(&b'f'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url_path() {
+        assert_eq!(normalize_url_path(""), "/");
+        assert_eq!(normalize_url_path("/"), "/");
+        assert_eq!(normalize_url_path("/a/../b"), "/b");
+        assert_eq!(normalize_url_path("/a/./b"), "/a/b");
+        assert_eq!(normalize_url_path("/../../a"), "/a");
+        assert_eq!(normalize_url_path("//a"), "/a");
+        assert_eq!(normalize_url_path("/a//b/"), "/a/b/");
+        assert_eq!(normalize_url_path("/a/"), "/a/");
+        assert_eq!(normalize_url_path("/a/b/.."), "/a");
+        assert_eq!(normalize_url_path("//"), "/");
+    }
+
+    #[test]
+    fn test_strip_trailing_slash() {
+        assert_eq!(strip_trailing_slash("/"), "/");
+        assert_eq!(strip_trailing_slash("//"), "/");
+        assert_eq!(strip_trailing_slash("/a/"), "/a");
+        assert_eq!(strip_trailing_slash("/a//"), "/a");
+        assert_eq!(strip_trailing_slash("/a"), "/a");
+        assert_eq!(strip_trailing_slash(""), "");
+    }
+
+    #[test]
+    fn test_decode_url_path() {
+        assert_eq!(decode_url_path("/admin"), "/admin");
+        assert_eq!(decode_url_path("/%61dmin"), "/admin");
+        assert_eq!(decode_url_path("/%2e%2e/admin"), "/../admin");
+        assert_eq!(decode_url_path("/a%2Fb"), "/a%2Fb");
+        assert_eq!(decode_url_path("/a%2fb%2F%63"), "/a%2Fb%2Fc");
+        assert_eq!(decode_url_path("/%252F"), "/%2F");
+    }
+
+    #[test]
+    fn test_decode_url_query() {
+        assert_eq!(decode_url_query("a=1&b=2"), "a=1&b=2");
+        assert_eq!(
+            decode_url_query("q=javascript%3Aalert(1)"),
+            "q=javascript:alert(1)"
+        );
+        assert_eq!(decode_url_query("q=a+b%2Bc"), "q=a b+c");
+    }
+}